@@ -0,0 +1,66 @@
+//! cargo +nightly bench --features "nightly" --verbose
+#![cfg(feature = "nightly")]
+#![feature(test)]
+
+extern crate merkle_light;
+extern crate test;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use test::Bencher;
+
+#[derive(Default)]
+struct A(DefaultHasher);
+
+impl Hasher for A {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<[u8; 8]> for A {
+    #[inline]
+    fn hash(&mut self) -> [u8; 8] {
+        self.0.finish().to_le_bytes()
+    }
+}
+
+/// 4096 leafs of 64 bytes.
+fn leaves() -> Vec<Vec<u8>> {
+    (0..4096u32).map(|i| vec![i as u8; 64]).collect()
+}
+
+#[bench]
+fn bench_hash_leaves_single(b: &mut Bencher) {
+    let data = leaves();
+    let mut a = A::default();
+    b.iter(|| {
+        let hashes: Vec<[u8; 8]> = data.iter().map(|x| a.hash_bytes(x)).collect();
+        test::black_box(hashes)
+    });
+}
+
+#[bench]
+fn bench_hash_leaves_batch(b: &mut Bencher) {
+    let data = leaves();
+    let slices: Vec<&[u8]> = data.iter().map(Vec::as_slice).collect();
+    let mut a = A::default();
+    b.iter(|| test::black_box(a.hash_leaves_batch(&slices)));
+}
+
+#[bench]
+fn bench_from_byte_slices(b: &mut Bencher) {
+    let data = leaves();
+    b.iter(|| {
+        let mt: MerkleTree<[u8; 8], A> = MerkleTree::from_byte_slices(&data);
+        test::black_box(mt.root())
+    });
+}
@@ -0,0 +1,69 @@
+//! cargo +nightly bench --features "nightly" --verbose
+#![cfg(feature = "nightly")]
+#![feature(test)]
+
+extern crate merkle_light;
+extern crate test;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use merkle_light::proof::{Proof, ProofRef};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use test::Bencher;
+
+#[derive(Default)]
+struct A(DefaultHasher);
+
+impl Hasher for A {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<[u8; 8]> for A {
+    #[inline]
+    fn hash(&mut self) -> [u8; 8] {
+        self.0.finish().to_le_bytes()
+    }
+}
+
+/// Proofs of all the leafs as they would be received, flat buffers.
+fn proofs() -> Vec<(Vec<[u8; 8]>, Vec<bool>)> {
+    let mt: MerkleTree<[u8; 8], A> = MerkleTree::from_data((0..1024u64).map(|x| x.to_le_bytes()));
+    (0..mt.leafs())
+        .map(|i| {
+            let p = mt.gen_proof(i);
+            (p.lemma().to_vec(), p.path().to_vec())
+        })
+        .collect()
+}
+
+#[bench]
+fn bench_validate_owned(b: &mut Bencher) {
+    let proofs = proofs();
+    b.iter(|| {
+        for (lemma, path) in &proofs {
+            // owned proof has to copy the buffers
+            let p = Proof::new(lemma.clone(), path.clone());
+            assert!(p.validate::<A>());
+        }
+    });
+}
+
+#[bench]
+fn bench_validate_borrowed(b: &mut Bencher) {
+    let proofs = proofs();
+    b.iter(|| {
+        for (lemma, path) in &proofs {
+            let p = ProofRef::new(lemma, path);
+            assert!(p.validate::<A>());
+        }
+    });
+}
@@ -0,0 +1,54 @@
+//! cargo bench --features "rayon" --verbose
+#![cfg(feature = "rayon")]
+#![feature(test)]
+
+extern crate merkle_light;
+extern crate rayon;
+extern crate test;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use rayon::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use test::Bencher;
+
+#[derive(Default)]
+struct A(DefaultHasher);
+
+impl Hasher for A {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<[u8; 8]> for A {
+    #[inline]
+    fn hash(&mut self) -> [u8; 8] {
+        self.0.finish().to_le_bytes()
+    }
+}
+
+const LEAFS: u32 = 1 << 20;
+
+#[bench]
+fn bench_from_data_1m(b: &mut Bencher) {
+    b.iter(|| MerkleTree::<[u8; 8], A>::from_data((0..LEAFS).map(|x| u64::from(x).to_le_bytes())));
+}
+
+#[bench]
+fn bench_par_from_data_1m(b: &mut Bencher) {
+    b.iter(|| {
+        MerkleTree::<[u8; 8], A>::par_from_data(
+            (0..LEAFS)
+                .into_par_iter()
+                .map(|x| u64::from(x).to_le_bytes()),
+        )
+    });
+}
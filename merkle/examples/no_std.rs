@@ -1,5 +1,5 @@
 // This is just for no_std testing
-#[allow(unused_imports)]
-use merkle_light;
+#[allow(unused_extern_crates)]
+extern crate merkle_light;
 
 fn main() {}
@@ -0,0 +1,246 @@
+#[cfg(feature = "blake2b")]
+pub use self::blake2b::Blake2bAlgorithm;
+#[cfg(feature = "keccak")]
+pub use self::keccak::Keccak256Algorithm;
+#[cfg(feature = "bitcoin")]
+pub use self::sha256d::Sha256dAlgorithm;
+
+#[cfg(feature = "blake2b")]
+mod blake2b {
+    use crate::hash::Algorithm;
+    use blake2::digest::consts::U32;
+    use blake2::{Blake2b, Digest};
+    use core::fmt;
+    use core::hash::Hasher;
+
+    /// BLAKE2b-256 hashing algorithm (`blake2b` feature).
+    ///
+    /// Leafs and nodes are hashed with the default [`Algorithm`] hooks, that
+    /// is with `0x00` and `0x01` prefixes of RFC 6962 domain separation.
+    /// Override [`Algorithm::leaf`] and [`Algorithm::node`] in a wrapper to
+    /// hash raw concatenations instead.
+    #[derive(Clone, Default)]
+    pub struct Blake2bAlgorithm(Blake2b<U32>);
+
+    impl Blake2bAlgorithm {
+        /// Creates new BLAKE2b-256 algorithm.
+        pub fn new() -> Blake2bAlgorithm {
+            Blake2bAlgorithm::default()
+        }
+    }
+
+    impl fmt::Debug for Blake2bAlgorithm {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("Blake2bAlgorithm")
+        }
+    }
+
+    impl Hasher for Blake2bAlgorithm {
+        #[inline]
+        fn write(&mut self, msg: &[u8]) {
+            self.0.update(msg)
+        }
+
+        /// Returns the first 8 bytes of the digest, little-endian.
+        #[inline]
+        fn finish(&self) -> u64 {
+            let h = self.0.clone().finalize();
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&h[..8]);
+            u64::from_le_bytes(bytes)
+        }
+    }
+
+    impl Algorithm<[u8; 32]> for Blake2bAlgorithm {
+        /// Returns the digest and resets the state.
+        #[inline]
+        fn hash(&mut self) -> [u8; 32] {
+            self.0.finalize_reset().into()
+        }
+
+        #[inline]
+        fn reset(&mut self) {
+            Digest::reset(&mut self.0);
+        }
+    }
+}
+
+#[cfg(feature = "keccak")]
+mod keccak {
+    use crate::hash::Algorithm;
+    use core::fmt;
+    use core::hash::Hasher;
+    use sha3::{Digest, Keccak256};
+
+    /// Keccak-256 hashing algorithm of Ethereum (`keccak` feature).
+    ///
+    /// Leafs and nodes are hashed as Solidity contracts do, without the RFC
+    /// 6962 prefixes: the leaf is the Keccak-256 of the data as is, and the
+    /// node is the Keccak-256 of the raw concatenation of the children.
+    /// Build the tree with [`NodeOrder::Sorted`] and validate proofs with
+    /// [`Proof::validate_sorted`] to match OpenZeppelin `MerkleProof.sol`:
+    ///
+    /// ```text
+    /// let mt: MerkleTree<[u8; 32], Keccak256Algorithm> =
+    ///     MerkleTree::from_data_with_order(addresses, NodeOrder::Sorted);
+    /// ```
+    ///
+    /// Leafs hash `abi.encodePacked` of the fields, e.g. an address leaf is
+    /// the hash of its 20 bytes, and a string leaf the hash of its bytes,
+    /// `as_bytes()`, [`Hashable`] of `str` terminates them. Roots of the trees with odd width levels
+    /// match only those which duplicate the last node as well.
+    ///
+    /// Without the prefixes an interior node can be presented as a leaf, so
+    /// leaf data must not be 64 bytes long, or must be hashed twice as
+    /// OpenZeppelin `StandardMerkleTree` does.
+    ///
+    /// [`NodeOrder::Sorted`]: crate::merkle::NodeOrder::Sorted
+    /// [`Hashable`]: crate::hash::Hashable
+    /// [`Proof::validate_sorted`]: crate::proof::Proof::validate_sorted
+    #[derive(Clone, Default)]
+    pub struct Keccak256Algorithm(Keccak256);
+
+    impl Keccak256Algorithm {
+        /// Creates new Keccak-256 algorithm.
+        pub fn new() -> Keccak256Algorithm {
+            Keccak256Algorithm::default()
+        }
+    }
+
+    impl fmt::Debug for Keccak256Algorithm {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("Keccak256Algorithm")
+        }
+    }
+
+    impl Hasher for Keccak256Algorithm {
+        #[inline]
+        fn write(&mut self, msg: &[u8]) {
+            self.0.update(msg)
+        }
+
+        /// Returns the first 8 bytes of the digest, little-endian.
+        #[inline]
+        fn finish(&self) -> u64 {
+            let h = self.0.clone().finalize();
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&h[..8]);
+            u64::from_le_bytes(bytes)
+        }
+    }
+
+    impl Algorithm<[u8; 32]> for Keccak256Algorithm {
+        /// Returns the digest and resets the state.
+        #[inline]
+        fn hash(&mut self) -> [u8; 32] {
+            self.0.finalize_reset().into()
+        }
+
+        #[inline]
+        fn reset(&mut self) {
+            Digest::reset(&mut self.0);
+        }
+
+        /// Returns the leaf hash unchanged, it is hashed by the tree already.
+        #[inline]
+        fn leaf(&mut self, leaf: [u8; 32]) -> [u8; 32] {
+            leaf
+        }
+
+        #[inline]
+        fn node(&mut self, left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+            self.write(&left);
+            self.write(&right);
+            self.hash()
+        }
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+mod sha256d {
+    use crate::hash::Algorithm;
+    use core::fmt;
+    use core::hash::Hasher;
+    use sha2::{Digest, Sha256};
+
+    /// Double SHA-256 hashing algorithm of Bitcoin (`bitcoin` feature).
+    ///
+    /// Hash is `SHA256(SHA256(data))`, leafs and nodes are hashed as Bitcoin
+    /// does, without the RFC 6962 prefixes: the leaf is the txid unchanged,
+    /// and the node is the hash of the raw concatenation of the children.
+    /// Build the tree from the txids with [`PaddingPolicy::DuplicateLast`],
+    /// the default, to get the block merkle root:
+    ///
+    /// ```text
+    /// let mt: MerkleTree<[u8; 32], Sha256dAlgorithm> = MerkleTree::new(txids);
+    /// ```
+    ///
+    /// Txids and roots are displayed, e.g. by block explorers and RPC, in
+    /// reversed byte order. Reverse the displayed txids to the internal
+    /// little-endian order before building the tree, and the root back for
+    /// display.
+    ///
+    /// Duplicating the last node makes leaf sets of the same root possible
+    /// (CVE-2012-2459), see [`MerkleTree::from_data_checked`].
+    ///
+    /// [`PaddingPolicy::DuplicateLast`]: crate::merkle::PaddingPolicy::DuplicateLast
+    /// [`MerkleTree::from_data_checked`]: crate::merkle::MerkleTree::from_data_checked
+    #[derive(Clone, Default)]
+    pub struct Sha256dAlgorithm(Sha256);
+
+    impl Sha256dAlgorithm {
+        /// Creates new double SHA-256 algorithm.
+        pub fn new() -> Sha256dAlgorithm {
+            Sha256dAlgorithm::default()
+        }
+    }
+
+    impl fmt::Debug for Sha256dAlgorithm {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("Sha256dAlgorithm")
+        }
+    }
+
+    impl Hasher for Sha256dAlgorithm {
+        #[inline]
+        fn write(&mut self, msg: &[u8]) {
+            self.0.update(msg)
+        }
+
+        /// Returns the first 8 bytes of the digest, little-endian.
+        #[inline]
+        fn finish(&self) -> u64 {
+            let h = Sha256::digest(self.0.clone().finalize());
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&h[..8]);
+            u64::from_le_bytes(bytes)
+        }
+    }
+
+    impl Algorithm<[u8; 32]> for Sha256dAlgorithm {
+        /// Returns the digest and resets the state.
+        #[inline]
+        fn hash(&mut self) -> [u8; 32] {
+            let h = self.0.finalize_reset();
+            Sha256::digest(h).into()
+        }
+
+        #[inline]
+        fn reset(&mut self) {
+            Digest::reset(&mut self.0);
+        }
+
+        /// Returns the txid unchanged.
+        #[inline]
+        fn leaf(&mut self, leaf: [u8; 32]) -> [u8; 32] {
+            leaf
+        }
+
+        #[inline]
+        fn node(&mut self, left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+            self.write(&left);
+            self.write(&right);
+            self.hash()
+        }
+    }
+}
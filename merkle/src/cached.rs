@@ -0,0 +1,203 @@
+extern crate alloc;
+
+use crate::hash::{Algorithm, HashContext, Hashable};
+use crate::merkle::{log2_pow2, next_pow2};
+use crate::proof::Proof;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+/// Merkle tree which keeps only the leafs and the top `k` levels in memory.
+///
+/// Interior nodes of the levels in between are recomputed from the leafs on
+/// demand during [`gen_proof`], which trades proof generation time for the
+/// memory the fully stored [`MerkleTree`] takes. Root is always available.
+///
+/// ```text
+///     leafs: [h1 h2 h3 h4 h5 h6 h7 h8]
+///     top (k = 2): [h1234 h5678 root]
+/// ```
+///
+/// Odd levels are padded with the duplicate of the last node, exactly as
+/// [`MerkleTree::from_data`] does, so proofs are the same.
+///
+/// [`gen_proof`]: CachedMerkleTree::gen_proof
+/// [`MerkleTree`]: crate::merkle::MerkleTree
+/// [`MerkleTree::from_data`]: crate::merkle::MerkleTree::from_data
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CachedMerkleTree<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    leafs: Vec<T>,
+    top: Vec<T>,
+    root: T,
+    height: usize,
+    depth: usize,
+    _a: PhantomData<fn() -> A>,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> CachedMerkleTree<T, A> {
+    /// Creates new cached merkle tree of cache `depth` from a sequence of hashes.
+    ///
+    /// Any number of leafs is accepted, as [`MerkleTree::new`] does: the tree
+    /// of a single leaf is the leaf, the empty tree has the root of the
+    /// empty input.
+    ///
+    /// [`MerkleTree::new`]: crate::merkle::MerkleTree::new
+    pub fn new<I: IntoIterator<Item = T>>(data: I, depth: usize) -> CachedMerkleTree<T, A> {
+        let mut a = A::default();
+        let leafs: Vec<T> = data
+            .into_iter()
+            .map(|item| {
+                a.reset();
+                a.leaf(item)
+            })
+            .collect();
+
+        // the empty tree of the hash of the empty input, as MerkleTree's
+        if leafs.is_empty() {
+            a.reset();
+            return CachedMerkleTree {
+                leafs,
+                top: Vec::new(),
+                root: a.hash(),
+                height: 0,
+                depth: 0,
+                _a: PhantomData,
+            };
+        }
+
+        let height = log2_pow2(2 * next_pow2(leafs.len()));
+        let depth = depth.min(height);
+        let cached = height - depth;
+
+        // leafs are never stored twice
+        let first = cached.max(1);
+
+        let mut top = Vec::new();
+        let mut level = leafs.clone();
+        for l in 0..height {
+            if l > 0 {
+                level = level
+                    .chunks(2)
+                    .map(|pair| {
+                        a.reset();
+                        a.node(pair[0].clone(), pair[1].clone())
+                    })
+                    .collect();
+            }
+
+            // if there is odd num of elements, fill in to the even
+            if level.len() > 1 && level.len() & 1 == 1 {
+                let he = level[level.len() - 1].clone();
+                level.push(he);
+            }
+
+            if l >= first {
+                top.extend(level.iter().cloned());
+            }
+        }
+
+        CachedMerkleTree {
+            leafs,
+            top,
+            root: level[0].clone(),
+            height,
+            depth,
+            _a: PhantomData,
+        }
+    }
+
+    /// Creates new cached merkle tree of cache `depth` from a list of hashable objects.
+    pub fn from_data<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        depth: usize,
+    ) -> CachedMerkleTree<T, A> {
+        let mut a = A::default();
+        Self::new(
+            data.into_iter().map(|x| {
+                a.reset();
+                x.hash_with_context(&mut a, HashContext::Leaf);
+                a.hash()
+            }),
+            depth,
+        )
+    }
+
+    /// Returns width of `level` without padding.
+    fn width(&self, level: usize) -> usize {
+        let mut width = self.leafs.len();
+        for _ in 0..level {
+            width = (width + 1) >> 1;
+        }
+        width
+    }
+
+    /// Returns node `i` of `level`, recomputing it from the leafs if the
+    /// level is not cached.
+    fn node(&self, a: &mut A, level: usize, i: usize) -> T {
+        if level == 0 {
+            return self.leafs[i].clone();
+        }
+
+        let first = (self.height - self.depth).max(1);
+        if level >= first {
+            let mut base = 0;
+            for l in first..level {
+                let w = self.width(l);
+                base += if w > 1 { w + (w & 1) } else { w };
+            }
+            return self.top[base + i].clone();
+        }
+
+        let left = self.node(a, level - 1, 2 * i);
+        let right = if 2 * i + 1 < self.width(level - 1) {
+            self.node(a, level - 1, 2 * i + 1)
+        } else {
+            left.clone()
+        };
+
+        a.reset();
+        a.node(left, right)
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    pub fn gen_proof(&self, i: usize) -> Proof<T> {
+        assert!(i < self.leafs.len()); // i in [0 .. self.leafs)
+
+        let mut a = A::default();
+        let mut lemma: Vec<T> = Vec::with_capacity(self.height + 1); // path + root
+        let mut path: Vec<bool> = Vec::with_capacity(self.height - 1); // path - 1
+
+        let mut j = i;
+        lemma.push(self.leafs[j].clone());
+        for level in 0..self.height - 1 {
+            // duplicated last node is its own sibling
+            let sibling = if j ^ 1 < self.width(level) { j ^ 1 } else { j };
+            lemma.push(self.node(&mut a, level, sibling));
+            path.push(j & 1 == 0);
+            j >>= 1;
+        }
+
+        // root is final
+        lemma.push(self.root());
+        Proof::new(lemma, path).with_index(i)
+    }
+
+    /// Returns merkle root
+    pub fn root(&self) -> T {
+        self.root.clone()
+    }
+
+    /// Returns height of the tree
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns number of cached top levels.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns original number of elements the tree was built upon.
+    pub fn leafs(&self) -> usize {
+        self.leafs.len()
+    }
+}
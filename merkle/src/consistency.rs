@@ -0,0 +1,111 @@
+extern crate alloc;
+
+use crate::hash::Algorithm;
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Consistency proof of append-only merkle tree, RFC 6962 section 2.1.2.
+///
+/// Proves that the tree of `old_size` leafs is the prefix of the tree of
+/// `new_size` leafs, so that the newer tree only appended leafs to the
+/// older. Trees are the ones of RFC 6962, see
+/// [`MerkleTree::gen_consistency_proof`].
+///
+/// Nodes are the subtree roots of `SUBPROOF(m, D[n], true)` in the order
+/// the RFC lists them, `O(log n)` hashes. Sizes are not authenticated by
+/// the proof, they have to come along with the roots, e.g. signed together
+/// as a tree head.
+///
+/// [`MerkleTree::gen_consistency_proof`]: crate::merkle::MerkleTree::gen_consistency_proof
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConsistencyProof<T: Eq + Clone + AsRef<[u8]>> {
+    old_size: usize,
+    new_size: usize,
+    nodes: Vec<T>,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> ConsistencyProof<T> {
+    /// Creates new MT consistency proof between the trees of `old_size` and
+    /// `new_size` leafs.
+    pub fn new(old_size: usize, new_size: usize, nodes: Vec<T>) -> ConsistencyProof<T> {
+        assert!(0 < old_size && old_size <= new_size);
+        ConsistencyProof {
+            old_size,
+            new_size,
+            nodes,
+        }
+    }
+
+    /// Verifies that the tree of `old_root` is the prefix of the tree of
+    /// `new_root`, following RFC 9162 section 2.1.4.2.
+    pub fn validate<A: Algorithm<T>>(&self, old_root: &T, new_root: &T) -> bool {
+        if self.old_size == 0 || self.old_size > self.new_size {
+            return false;
+        }
+        if self.old_size == self.new_size {
+            return self.nodes.is_empty() && old_root == new_root;
+        }
+
+        // the old tree is a complete subtree of the new one, its root is
+        // not in the proof
+        let mut path = self.nodes.iter();
+        let first = if self.old_size.is_power_of_two() {
+            old_root
+        } else {
+            match path.next() {
+                Some(n) => n,
+                None => return false,
+            }
+        };
+
+        let mut fnode = self.old_size - 1;
+        let mut snode = self.new_size - 1;
+        while fnode & 1 == 1 {
+            fnode >>= 1;
+            snode >>= 1;
+        }
+
+        let mut a = A::default();
+        let mut fr = first.clone();
+        let mut sr = first.clone();
+        for c in path {
+            if snode == 0 {
+                return false;
+            }
+            if fnode & 1 == 1 || fnode == snode {
+                a.reset();
+                fr = a.node(c.clone(), fr);
+                a.reset();
+                sr = a.node(c.clone(), sr);
+                while fnode & 1 == 0 && fnode != 0 {
+                    fnode >>= 1;
+                    snode >>= 1;
+                }
+            } else {
+                a.reset();
+                sr = a.node(sr, c.clone());
+            }
+            fnode >>= 1;
+            snode >>= 1;
+        }
+
+        snode == 0 && fr == *old_root && sr == *new_root
+    }
+
+    /// Returns the number of leafs of the older tree.
+    pub fn old_size(&self) -> usize {
+        self.old_size
+    }
+
+    /// Returns the number of leafs of the newer tree.
+    pub fn new_size(&self) -> usize {
+        self.new_size
+    }
+
+    /// Returns the subtree roots of this proof.
+    pub fn nodes(&self) -> &[T] {
+        &self.nodes
+    }
+}
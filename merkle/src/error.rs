@@ -0,0 +1,117 @@
+#[cfg(feature = "std")]
+extern crate std;
+
+use crate::hash::to_hex;
+use core::fmt;
+
+/// Merkle tree operation errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MerkleError {
+    /// Leaf index is outside of the `[0 .. leafs)` range of the tree.
+    IndexOutOfBounds(usize),
+    /// Tree has no leafs.
+    EmptyTree,
+    /// Proof does not fold to the expected root.
+    InvalidProof,
+    /// Tree has equal sibling nodes, so a shorter leaf sequence padded by
+    /// duplication has the same root (CVE-2012-2459).
+    DuplicateAmbiguity,
+    /// Encoded proof or tree is truncated or malformed.
+    InvalidEncoding,
+    /// Nodes the operation needs are pruned from the tree.
+    Pruned,
+    /// Trees compared have different number of leafs.
+    LeafsMismatch(usize, usize),
+    /// Proofs merged are of different roots.
+    RootMismatch,
+    /// Number of the tree nodes overflows `usize`.
+    TooLarge,
+    /// Chunks are not subtrees which tile the leafs of the tree.
+    InvalidChunks,
+    /// Build is stopped by the progress callback.
+    Cancelled,
+    /// Operation needs the tree of the positional node order, see
+    /// [`NodeOrder`](crate::merkle::NodeOrder).
+    UnsupportedOrder,
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MerkleError::IndexOutOfBounds(i) => write!(f, "leaf index {} is out of bounds", i),
+            MerkleError::EmptyTree => write!(f, "merkle tree is empty"),
+            MerkleError::InvalidProof => write!(f, "proof is invalid"),
+            MerkleError::DuplicateAmbiguity => {
+                write!(f, "equal sibling nodes make the root ambiguous")
+            }
+            MerkleError::InvalidEncoding => write!(f, "encoding is invalid"),
+            MerkleError::Pruned => write!(f, "tree nodes are pruned"),
+            MerkleError::LeafsMismatch(l, r) => {
+                write!(f, "trees have {} and {} leafs", l, r)
+            }
+            MerkleError::RootMismatch => write!(f, "proofs are of different roots"),
+            MerkleError::TooLarge => write!(f, "tree is too large"),
+            MerkleError::InvalidChunks => write!(f, "chunks do not tile the tree leafs"),
+            MerkleError::Cancelled => write!(f, "tree build is cancelled"),
+            MerkleError::UnsupportedOrder => write!(f, "tree node order is not supported"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleError {}
+
+/// Reasons of an inclusion proof validation failure, see
+/// [`Proof::validate_detailed`](crate::proof::Proof::validate_detailed).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofError<T> {
+    /// Lemma folds to the `computed` root instead of the `expected` one.
+    RootMismatch {
+        /// Root the proof is validated against.
+        expected: T,
+        /// Root the lemma folds to.
+        computed: T,
+    },
+    /// Lemma carries neither the item nor the root.
+    EmptyLemma,
+    /// Path does not have a bit for every sibling of the lemma.
+    LengthMismatch {
+        /// Number of hashes in the lemma, the item and the root included.
+        lemma: usize,
+        /// Number of the path bits.
+        path: usize,
+    },
+    /// Path bits are not the ones of the leaf `index` the proof recorded.
+    IndexMismatch {
+        /// Leaf index the proof was generated for.
+        index: usize,
+    },
+}
+
+impl<T: AsRef<[u8]>> fmt::Display for ProofError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProofError::RootMismatch {
+                ref expected,
+                ref computed,
+            } => write!(
+                f,
+                "proof root {} does not match expected root {}",
+                to_hex(computed.as_ref()),
+                to_hex(expected.as_ref())
+            ),
+            ProofError::EmptyLemma => write!(f, "proof lemma is empty"),
+            ProofError::LengthMismatch { lemma, path } => write!(
+                f,
+                "proof path of {} bits does not match lemma of {} hashes",
+                path, lemma
+            ),
+            ProofError::IndexMismatch { index } => {
+                write!(f, "proof path does not match leaf index {}", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug + AsRef<[u8]>> std::error::Error for ProofError<T> {}
@@ -0,0 +1,213 @@
+extern crate alloc;
+
+use crate::hash::{Algorithm, HashContext, Hashable};
+use crate::proof::Proof;
+use crate::verify::ProofVerifier;
+use alloc::vec::Vec;
+use core::array;
+use core::marker::PhantomData;
+
+/// Merkle tree of `LEAFS = 2^DEPTH` leafs stored in arrays, which is built,
+/// proven and validated without the heap, e.g. for small trees on embedded
+/// targets.
+///
+/// Leafs and nodes are hashed as [`MerkleTree::from_data`] hashes them, so
+/// the trees have the same root. Stable Rust can not size an array by
+/// `2^DEPTH`, so both are parameters, checked to agree at compile time.
+///
+/// ```
+/// # use merkle_light::fixed::FixedMerkleTree;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::Hasher;
+/// # use merkle_light::hash::Algorithm;
+/// # #[derive(Default)]
+/// # struct Sip(DefaultHasher);
+/// # impl Hasher for Sip {
+/// #     fn write(&mut self, bytes: &[u8]) { self.0.write(bytes) }
+/// #     fn finish(&self) -> u64 { self.0.finish() }
+/// # }
+/// # impl Algorithm<[u8; 8]> for Sip {
+/// #     fn hash(&mut self) -> [u8; 8] { self.0.finish().to_le_bytes() }
+/// # }
+/// let mt: FixedMerkleTree<[u8; 8], Sip, 3, 8> =
+///     FixedMerkleTree::from_data(&[1u64, 2, 3, 4, 5, 6, 7, 8]);
+/// assert!(mt.gen_proof(5).validate::<Sip>());
+/// ```
+///
+/// Nodes are stored as a binary heap, node `i` is the parent of nodes
+/// `2i` and `2i + 1`, the root is node `1` and leaf `j` is node
+/// `LEAFS + j`:
+///
+/// ```text
+///     DEPTH = 2:
+///     nodes: [- root h01 h23] leafs: [h0 h1 h2 h3]
+/// ```
+///
+/// [`MerkleTree::from_data`]: crate::merkle::MerkleTree::from_data
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FixedMerkleTree<T, A, const DEPTH: usize, const LEAFS: usize> {
+    nodes: [T; LEAFS],
+    leafs: [T; LEAFS],
+    _a: PhantomData<fn() -> A>,
+}
+
+impl<T, A, const DEPTH: usize, const LEAFS: usize> FixedMerkleTree<T, A, DEPTH, LEAFS>
+where
+    T: Eq + Clone + Default + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    const SHAPE: () = assert!(
+        DEPTH < usize::BITS as usize && LEAFS == 1 << DEPTH,
+        "LEAFS must be 2^DEPTH"
+    );
+
+    /// Creates new merkle tree from the hashes of the leaf data, which are
+    /// hashed as leafs, as [`MerkleTree::new`] does.
+    ///
+    /// [`MerkleTree::new`]: crate::merkle::MerkleTree::new
+    pub fn new(data: [T; LEAFS]) -> FixedMerkleTree<T, A, DEPTH, LEAFS> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::SHAPE;
+
+        let mut a = A::default();
+        let leafs = data.map(|h| {
+            a.reset();
+            a.leaf(h)
+        });
+
+        let mut nodes: [T; LEAFS] = array::from_fn(|_| T::default());
+        for i in (1..LEAFS).rev() {
+            let left = Self::get(&nodes, &leafs, 2 * i);
+            let right = Self::get(&nodes, &leafs, 2 * i + 1);
+            a.reset();
+            nodes[i] = a.node(left, right);
+        }
+
+        FixedMerkleTree {
+            nodes,
+            leafs,
+            _a: PhantomData,
+        }
+    }
+
+    /// Creates new merkle tree from an array of hashable objects.
+    pub fn from_data<O: Hashable<A>>(data: &[O; LEAFS]) -> FixedMerkleTree<T, A, DEPTH, LEAFS> {
+        let mut a = A::default();
+        Self::new(array::from_fn(|i| {
+            a.reset();
+            data[i].hash_with_context(&mut a, HashContext::Leaf);
+            a.hash()
+        }))
+    }
+
+    /// Returns heap node `i` of the tree.
+    fn get(nodes: &[T; LEAFS], leafs: &[T; LEAFS], i: usize) -> T {
+        if i < LEAFS {
+            nodes[i].clone()
+        } else {
+            leafs[i - LEAFS].clone()
+        }
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    pub fn gen_proof(&self, i: usize) -> FixedProof<T, DEPTH> {
+        assert!(i < LEAFS); // i in [0 .. LEAFS)
+
+        let mut node = LEAFS + i;
+        let siblings = array::from_fn(|_| {
+            let sibling = Self::get(&self.nodes, &self.leafs, node ^ 1);
+            node >>= 1;
+            sibling
+        });
+
+        FixedProof {
+            item: self.leafs[i].clone(),
+            siblings,
+            index: i,
+            root: self.root(),
+        }
+    }
+
+    /// Returns merkle root
+    pub fn root(&self) -> T {
+        Self::get(&self.nodes, &self.leafs, 1)
+    }
+
+    /// Returns the leaf nodes of the tree.
+    pub fn leafs(&self) -> &[T; LEAFS] {
+        &self.leafs
+    }
+
+    /// Returns height of the tree
+    pub fn height(&self) -> usize {
+        DEPTH + 1
+    }
+}
+
+/// Inclusion proof of [`FixedMerkleTree`] leaf, stored in an array of the
+/// `DEPTH` siblings from the leaf up.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FixedProof<T, const DEPTH: usize> {
+    item: T,
+    siblings: [T; DEPTH],
+    index: usize,
+    root: T,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, const DEPTH: usize> FixedProof<T, DEPTH> {
+    /// Creates new fixed MT inclusion proof of the leaf node `item` at
+    /// position `index`.
+    pub fn new(item: T, siblings: [T; DEPTH], index: usize, root: T) -> FixedProof<T, DEPTH> {
+        FixedProof {
+            item,
+            siblings,
+            index,
+            root,
+        }
+    }
+
+    /// Verifies fixed MT inclusion proof
+    pub fn validate<A: Algorithm<T>>(&self) -> bool {
+        if DEPTH < usize::BITS as usize && self.index >> DEPTH != 0 {
+            return false;
+        }
+        let mut v = ProofVerifier::<A, T>::new(self.item.clone(), self.index);
+        for sibling in &self.siblings {
+            v.feed_sibling(sibling.clone());
+        }
+        v.finalize() == self.root
+    }
+
+    /// Converts into the [`Proof`] of the leaf, the one
+    /// [`MerkleTree::gen_proof`] generates over the same leafs.
+    ///
+    /// [`MerkleTree::gen_proof`]: crate::merkle::MerkleTree::gen_proof
+    pub fn to_proof(&self) -> Proof<T> {
+        let mut lemma = Vec::with_capacity(DEPTH + 2);
+        lemma.push(self.item.clone());
+        lemma.extend_from_slice(&self.siblings);
+        lemma.push(self.root.clone());
+        let path = (0..DEPTH).map(|l| (self.index >> l) & 1 == 0).collect();
+        Proof::new(lemma, path).with_index(self.index)
+    }
+
+    /// Return proof target leaf
+    pub fn item(&self) -> T {
+        self.item.clone()
+    }
+
+    /// Return tree root
+    pub fn root(&self) -> T {
+        self.root.clone()
+    }
+
+    /// Returns the siblings of the proven node from the leaf up.
+    pub fn siblings(&self) -> &[T; DEPTH] {
+        &self.siblings
+    }
+
+    /// Returns the position of the proven leaf.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
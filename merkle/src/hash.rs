@@ -1,6 +1,17 @@
 //! Hash infrastructure for items in Merkle Tree.
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::array::TryFromSliceError;
+use core::fmt::{self, Write};
 use core::hash::Hasher;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
 
 /// A hashable type.
 ///
@@ -62,10 +73,36 @@ use core::hash::Hasher;
 ///
 ///    let mut hr = DefaultHasher::new();
 ///    foo.hash(&mut hr);
-///    assert_eq!(hr.finish(), 7101638158313343130)
+///    # #[cfg(not(feature = "scheme_v1"))]
+///    assert_eq!(hr.finish(), 16522193108841771551);
 /// }
 /// ```
 ///
+/// ## Encoding
+///
+/// Implementations of the standard types feed the bytes explicitly, so the
+/// hashes do not depend on the target nor on the [`Hasher`] `write_*`
+/// methods:
+///
+/// - integers are fed as little-endian bytes (`to_le_bytes`),
+/// - `usize` and `isize` are fed as `u64` and `i64`,
+/// - floats are fed as the little-endian bytes of their canonical bits,
+/// - `str` and `String` are fed as their bytes terminated by `0xff`, the
+///   byte UTF-8 never contains, as std `Hash` does,
+/// - slices and `Vec` are prefixed with their length as `usize`, arrays
+///   are not,
+/// - `BTreeMap` and `HashMap` are prefixed with their entry count as
+///   `usize` and fed the entries in the order of the keys, so that the
+///   hash does not depend on the insertion order.
+///
+/// Hand written implementations can length-prefix with [`HashExt`] and
+/// [`hash_all`].
+///
+/// With the `scheme_v1` feature the standard types are fed as in 0.4.0
+/// instead, see [`ENCODING_VERSION`].
+///
+/// [`ENCODING_VERSION`]: crate::scheme::ENCODING_VERSION
+///
 /// ## `Hashable` and `Eq`
 ///
 /// When implementing both `Hashable` and [`Eq`], it is important that the following
@@ -76,12 +113,32 @@ use core::hash::Hasher;
 /// ```
 ///
 /// In other words, if two keys are equal, their hashes must also be equal.
+///
+/// Floats break it both ways: `0.0 == -0.0` though their bits differ, and
+/// `NaN != NaN` though their bits may be the same. `f32` and `f64` are
+/// hashed canonicalized, `-0.0` as `0.0` and any `NaN` as the `NAN`
+/// constant of the type, so equal floats hash equal and all `NaN`s hash
+/// the same.
 pub trait Hashable<H: Hasher> {
     /// Feeds this value into the given [`Hasher`].
     ///
     /// [`Hasher`]: trait.Hasher.html
     fn hash(&self, state: &mut H);
 
+    /// Feeds this value into the given [`Hasher`] as the data of the node
+    /// `ctx` tells, plain [`Hashable::hash`] by default.
+    ///
+    /// The trees hash their data with [`HashContext::Leaf`], so types which
+    /// need a domain of their own, e.g. a tag or a salt, override it and
+    /// keep `hash` for the values hashed outside of the trees. Interior
+    /// nodes are hashed by [`Algorithm::node`] from the child hashes, only
+    /// the leaf data gets a context.
+    #[inline]
+    fn hash_with_context(&self, state: &mut H, ctx: HashContext) {
+        let _ = ctx;
+        self.hash(state)
+    }
+
     /// Feeds a slice of this type into the given [`Hasher`].
     ///
     /// [`Hasher`]: trait.Hasher.html
@@ -95,6 +152,58 @@ pub trait Hashable<H: Hasher> {
     }
 }
 
+/// Kind of the tree node a [`Hashable`] value is hashed into, see
+/// [`Hashable::hash_with_context`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HashContext {
+    /// The value is the data of a leaf.
+    Leaf,
+}
+
+/// Length-prefixed hashing of [`Hashable`] values.
+///
+/// `hash_prefixed` feeds variable sized values prefixed with their length,
+/// and is the plain [`Hashable::hash`] for fixed size ones, e.g. to match
+/// the encodings which prefix each field with its length. Plain hashing is
+/// unambiguous as well, strings are terminated and sequences prefixed.
+///
+/// ```
+/// extern crate merkle_light;
+///
+/// use merkle_light::hash::{hash_all, HashExt, Hashable};
+/// use std::hash::Hasher;
+///
+/// struct Person {
+///     name: String,
+///     tags: Vec<String>,
+/// }
+///
+/// impl<H: Hasher> Hashable<H> for Person {
+///     fn hash(&self, state: &mut H) {
+///         self.name.hash_prefixed(state);
+///         hash_all(&self.tags, state);
+///     }
+/// }
+/// # fn main() {}
+/// ```
+pub trait HashExt<H: Hasher>: Hashable<H> {
+    /// Feeds this value into the given [`Hasher`], prefixed with its length
+    /// if it is not of fixed size.
+    fn hash_prefixed(&self, state: &mut H) {
+        self.hash(state)
+    }
+}
+
+/// Feeds `items` into the given [`Hasher`] prefixed with their count, each
+/// item with [`HashExt::hash_prefixed`].
+pub fn hash_all<H: Hasher>(items: &[impl HashExt<H>], state: &mut H) {
+    items.len().hash(state);
+    for item in items {
+        item.hash_prefixed(state);
+    }
+}
+
 /// MT leaf hash prefix
 const LEAF: u8 = 0x00;
 
@@ -107,10 +216,21 @@ const INTERIOR: u8 = 0x01;
 /// T is a hash item must be of known size at compile time, globally ordered, with
 /// default value as a neutral element of the hash space. Neutral element is
 /// interpreted as 0 or nil and required for evaluation of merkle tree.
+/// The tree does not rely on any particular size of T, so digests of any
+/// length fit, e.g. `[u8; 64]` for SHA-512.
 ///
 /// [`Algorithm`] breaks the [`Hasher`] contract at `finish()`, but that is intended.
 /// This trait extends [`Hasher`] with `hash -> T` and `reset` state methods,
 /// plus implements default behavior of evaluation of MT interior nodes.
+///
+/// Default [`leaf`] and [`node`] prepend `0x00` and `0x01` bytes to the
+/// hashed data (RFC 6962 domain separation), so an interior node can not be
+/// presented as a leaf to forge an inclusion proof (second preimage attack).
+/// Implementations may override both to hash raw concatenations when they
+/// must interoperate with such trees (e.g. bitcoin).
+///
+/// [`leaf`]: Algorithm::leaf
+/// [`node`]: Algorithm::node
 pub trait Algorithm<T>: Hasher + Default
 where
     T: Clone + AsRef<[u8]>,
@@ -124,6 +244,29 @@ where
         *self = Self::default();
     }
 
+    /// Returns the hash value of `bytes` alone.
+    ///
+    /// Self-contained: the state is reset before `bytes` are written, so
+    /// whatever was written before does not affect the hash.
+    #[inline]
+    fn hash_bytes(&mut self, bytes: &[u8]) -> T {
+        self.reset();
+        self.write(bytes);
+        self.hash()
+    }
+
+    /// Returns the hashes of `leaves`, one [`Algorithm::hash_bytes`] each.
+    ///
+    /// This is the leaf layer [`MerkleTree::from_byte_slices`] hashes.
+    /// Algorithms with a batched implementation, e.g. SIMD lanes or a GPU,
+    /// override it to hash many leaves at once, the hashes must stay the
+    /// ones of `hash_bytes`.
+    ///
+    /// [`MerkleTree::from_byte_slices`]: crate::merkle::MerkleTree::from_byte_slices
+    fn hash_leaves_batch(&mut self, leaves: &[&[u8]]) -> Vec<T> {
+        leaves.iter().map(|leaf| self.hash_bytes(leaf)).collect()
+    }
+
     /// Returns hash value for MT leaf (prefix 0x00).
     #[inline]
     fn leaf(&mut self, leaf: T) -> T {
@@ -140,4 +283,452 @@ where
         self.write(right.as_ref());
         self.hash()
     }
+
+    /// Returns hash value for n-ary MT interior node of `children` (prefix
+    /// 0x01), see [`NaryMerkleTree`](crate::nary::NaryMerkleTree).
+    ///
+    /// Two children are hashed with [`node`], so implementations overriding
+    /// it need to override this one only for wider nodes.
+    ///
+    /// [`node`]: Algorithm::node
+    #[inline]
+    fn nary_node(&mut self, children: &[T]) -> T {
+        if let [left, right] = children {
+            return self.node(left.clone(), right.clone());
+        }
+        self.write(&[INTERIOR]);
+        for child in children {
+            self.write(child.as_ref());
+        }
+        self.hash()
+    }
+}
+
+/// [`Algorithm`] adapter which hashes leafs and interior nodes without the
+/// domain separation prefixes, i.e. a leaf is passed through and a node is
+/// the hash of the raw concatenation of its children.
+///
+/// Proofs of trees built with
+/// [`MerkleTreeBuilder::domain_separation`](crate::merkle::MerkleTreeBuilder::domain_separation)
+/// disabled are validated with `Unprefixed<A>`.
+#[derive(Debug, Clone, Default)]
+pub struct Unprefixed<A>(pub A);
+
+impl<A: Hasher> Hasher for Unprefixed<A> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl<T, A> Algorithm<T> for Unprefixed<A>
+where
+    T: Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    #[inline]
+    fn hash(&mut self) -> T {
+        self.0.hash()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: T) -> T {
+        leaf
+    }
+
+    #[inline]
+    fn node(&mut self, left: T, right: T) -> T {
+        self.write(left.as_ref());
+        self.write(right.as_ref());
+        self.hash()
+    }
+
+    #[inline]
+    fn nary_node(&mut self, children: &[T]) -> T {
+        for child in children {
+            self.write(child.as_ref());
+        }
+        self.hash()
+    }
+}
+
+/// [`Algorithm`] adapter which keys leafs and interior nodes with distinct
+/// keys, to bind a tree to an application context.
+///
+/// `leaf_key` is written before the leaf and `node_key` before the
+/// children of an interior node, ahead of the domain separation of `A`.
+/// The keys change every node hash, so the root differs from the one of
+/// the unkeyed `A` and of the other keys over the same data. For a keyed
+/// MAC use a keyed `A`, the keys here are plain prefixes.
+///
+/// The keys survive [`Algorithm::reset`], but `Default` has none, so trees
+/// are built with [`MerkleTreeBuilder::hasher`] and proofs are validated
+/// with [`Proof::validate_with_hasher`].
+///
+/// ```
+/// # use merkle_light::hash::{Algorithm, KeyedAlgorithm};
+/// # use merkle_light::merkle::MerkleTreeBuilder;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::Hasher;
+/// # #[derive(Clone, Default)]
+/// # struct Sip(DefaultHasher);
+/// # impl Hasher for Sip {
+/// #     fn write(&mut self, bytes: &[u8]) { self.0.write(bytes) }
+/// #     fn finish(&self) -> u64 { self.0.finish() }
+/// # }
+/// # impl Algorithm<[u8; 8]> for Sip {
+/// #     fn hash(&mut self) -> [u8; 8] { self.0.finish().to_le_bytes() }
+/// # }
+/// let keyed: KeyedAlgorithm<Sip> = KeyedAlgorithm::new(b"app leaf", b"app node");
+/// let mt = MerkleTreeBuilder::new()
+///     .hasher(keyed.clone())
+///     .build(0..7u64);
+/// assert!(mt.gen_proof(3).validate_with_hasher(&keyed));
+/// ```
+///
+/// [`MerkleTreeBuilder::hasher`]: crate::merkle::MerkleTreeBuilder::hasher
+/// [`Proof::validate_with_hasher`]: crate::proof::Proof::validate_with_hasher
+#[derive(Clone, Default)]
+pub struct KeyedAlgorithm<A> {
+    inner: A,
+    leaf_key: Vec<u8>,
+    node_key: Vec<u8>,
+}
+
+impl<A: Default> KeyedAlgorithm<A> {
+    /// Creates new keyed adapter over `A::default()`.
+    pub fn new(leaf_key: &[u8], node_key: &[u8]) -> KeyedAlgorithm<A> {
+        KeyedAlgorithm {
+            inner: A::default(),
+            leaf_key: leaf_key.to_vec(),
+            node_key: node_key.to_vec(),
+        }
+    }
+}
+
+// keys are secrets, not shown
+impl<A: fmt::Debug> fmt::Debug for KeyedAlgorithm<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyedAlgorithm")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Hasher> Hasher for KeyedAlgorithm<A> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.inner.finish()
+    }
+}
+
+impl<T, A> Algorithm<T> for KeyedAlgorithm<A>
+where
+    T: Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    #[inline]
+    fn hash(&mut self) -> T {
+        self.inner.hash()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.inner.reset()
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: T) -> T {
+        self.inner.write(&self.leaf_key);
+        self.inner.leaf(leaf)
+    }
+
+    #[inline]
+    fn node(&mut self, left: T, right: T) -> T {
+        self.inner.write(&self.node_key);
+        self.inner.node(left, right)
+    }
+
+    #[inline]
+    fn nary_node(&mut self, children: &[T]) -> T {
+        self.inner.write(&self.node_key);
+        self.inner.nary_node(children)
+    }
+}
+
+/// Incremental hash of a leaf assembled from several pieces.
+///
+/// Pieces are fed as they become available, so the leaf does not have to be
+/// buffered. The result is the hash of the concatenation of the pieces, the
+/// one [`MerkleTree::from_byte_slices`] computes from the whole leaf bytes,
+/// to be appended with [`MerkleTree::push`]:
+///
+/// ```
+/// # use merkle_light::hash::{Algorithm, LeafBuilder};
+/// # use std::hash::Hasher;
+/// # #[derive(Default)]
+/// # struct Fnv(u64);
+/// # impl Hasher for Fnv {
+/// #     fn write(&mut self, bytes: &[u8]) {
+/// #         for b in bytes {
+/// #             self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3);
+/// #         }
+/// #     }
+/// #     fn finish(&self) -> u64 {
+/// #         self.0
+/// #     }
+/// # }
+/// # impl Algorithm<[u8; 8]> for Fnv {
+/// #     fn hash(&mut self) -> [u8; 8] {
+/// #         self.0.to_le_bytes()
+/// #     }
+/// # }
+/// let mut leaf = LeafBuilder::<Fnv>::new();
+/// leaf.feed(b"header").feed(b"body");
+/// let h: [u8; 8] = leaf.finish();
+/// assert_eq!(h, Fnv::default().hash_bytes(b"headerbody"));
+/// ```
+///
+/// [`MerkleTree::from_byte_slices`]: crate::merkle::MerkleTree::from_byte_slices
+/// [`MerkleTree::push`]: crate::merkle::MerkleTree::push
+#[derive(Debug, Clone, Default)]
+pub struct LeafBuilder<A> {
+    a: A,
+}
+
+impl<A: Hasher + Default> LeafBuilder<A> {
+    /// Creates new leaf builder hashing with `A::default()`.
+    pub fn new() -> LeafBuilder<A> {
+        LeafBuilder { a: A::default() }
+    }
+
+    /// Feeds the next piece of the leaf.
+    pub fn feed(&mut self, bytes: &[u8]) -> &mut Self {
+        self.a.write(bytes);
+        self
+    }
+
+    /// Returns the hash of all the pieces fed so far.
+    pub fn finish<T: Clone + AsRef<[u8]>>(mut self) -> T
+    where
+        A: Algorithm<T>,
+    {
+        self.a.hash()
+    }
+}
+
+/// One-shot hash function over the whole input, see [`FnAlgorithm`].
+pub trait HashFn<T> {
+    /// Returns the hash of `data`.
+    fn hash(data: &[u8]) -> T;
+}
+
+/// [`Algorithm`] of a plain hash function `F`, which buffers the writes
+/// and hashes them all at once in [`Algorithm::hash`].
+///
+/// Trees create algorithms with `Default`, which closures and function
+/// pointers do not implement, so the function is named by a type:
+///
+/// ```
+/// # use merkle_light::hash::{FnAlgorithm, HashFn};
+/// # use merkle_light::merkle::MerkleTree;
+/// struct Xor;
+///
+/// impl HashFn<[u8; 4]> for Xor {
+///     fn hash(data: &[u8]) -> [u8; 4] {
+///         let mut h = [0u8; 4];
+///         data.iter().enumerate().for_each(|(i, b)| h[i % 4] ^= b);
+///         h
+///     }
+/// }
+///
+/// let mt: MerkleTree<[u8; 4], FnAlgorithm<Xor, [u8; 4]>> = MerkleTree::from_data(["a", "b"]);
+/// assert_eq!(mt.leafs(), 2);
+/// ```
+pub struct FnAlgorithm<F, T> {
+    buf: Vec<u8>,
+    _f: PhantomData<fn() -> (F, T)>,
+}
+
+impl<F, T> Default for FnAlgorithm<F, T> {
+    fn default() -> FnAlgorithm<F, T> {
+        FnAlgorithm {
+            buf: Vec::new(),
+            _f: PhantomData,
+        }
+    }
+}
+
+impl<F, T> Clone for FnAlgorithm<F, T> {
+    fn clone(&self) -> FnAlgorithm<F, T> {
+        FnAlgorithm {
+            buf: self.buf.clone(),
+            _f: PhantomData,
+        }
+    }
+}
+
+impl<F, T> fmt::Debug for FnAlgorithm<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FnAlgorithm")
+            .field("buf", &self.buf)
+            .finish()
+    }
+}
+
+impl<F: HashFn<T>, T: AsRef<[u8]>> Hasher for FnAlgorithm<F, T> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Returns the first 8 bytes of the hash, little-endian.
+    fn finish(&self) -> u64 {
+        let h = F::hash(&self.buf);
+        let mut bytes = [0u8; 8];
+        let n = h.as_ref().len().min(8);
+        bytes[..n].copy_from_slice(&h.as_ref()[..n]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+impl<F: HashFn<T>, T: Clone + AsRef<[u8]>> Algorithm<T> for FnAlgorithm<F, T> {
+    fn hash(&mut self) -> T {
+        F::hash(&self.buf)
+    }
+
+    // keeps the buffer allocation
+    fn reset(&mut self) {
+        self.buf.clear();
+    }
+}
+
+macro_rules! hash_type {
+    ($(#[$doc:meta])* $name:ident, $n:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub [u8; $n]);
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name([0u8; $n])
+            }
+        }
+
+        impl From<[u8; $n]> for $name {
+            fn from(bytes: [u8; $n]) -> $name {
+                $name(bytes)
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = TryFromSliceError;
+
+            /// Fails unless `bytes` are exactly the size of the hash.
+            fn try_from(bytes: &[u8]) -> Result<$name, TryFromSliceError> {
+                <[u8; $n]>::try_from(bytes).map($name)
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl AsMut<[u8]> for $name {
+            fn as_mut(&mut self) -> &mut [u8] {
+                &mut self.0
+            }
+        }
+
+        /// Formats the hash as lowercase hex.
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}({})", stringify!($name), self)
+            }
+        }
+
+        // fixed size, fed as is
+        impl<H: Hasher> Hashable<H> for $name {
+            fn hash(&self, state: &mut H) {
+                state.write(&self.0)
+            }
+        }
+
+        impl<H: Hasher> HashExt<H> for $name {}
+    };
+}
+
+hash_type! {
+    /// Hash of 32 bytes, e.g. SHA-256 or BLAKE2b-256, which meets all the
+    /// bounds of the tree nodes.
+    ///
+    /// ```
+    /// # use merkle_light::hash::Hash32;
+    /// let h = Hash32::try_from(&[0xab; 32][..]).unwrap();
+    /// assert!(h.to_string().starts_with("abab"));
+    /// assert!(Hash32::try_from(&[0xab; 31][..]).is_err());
+    /// ```
+    Hash32,
+    32
+}
+
+hash_type! {
+    /// Hash of 64 bytes, e.g. SHA-512 or BLAKE2b-512, see [`Hash32`].
+    Hash64,
+    64
+}
+
+/// Size of the chunks [`hash_reader`] pulls from the source.
+#[cfg(feature = "std")]
+const READ_CHUNK: usize = 4096;
+
+/// Feeds all the bytes of `reader` into the given [`Hasher`], so that large
+/// sources (e.g. files) can be hashed as leafs without loading them into
+/// memory.
+///
+/// The source is read in 4 KiB chunks, the state ends up the same as after
+/// a single `write` of the whole content for hashers that are agnostic to
+/// how the stream is split.
+#[cfg(feature = "std")]
+pub fn hash_reader<H: Hasher, R: Read>(reader: &mut R, state: &mut H) -> io::Result<()> {
+    let mut buf = [0u8; READ_CHUNK];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => state.write(&buf[..n]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Formats hash bytes as lowercase hex.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(2 * bytes.len());
+    for b in bytes {
+        write!(s, "{:02x}", b).unwrap();
+    }
+    s
 }
@@ -1,74 +1,143 @@
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
-use crate::hash::Hashable;
+use crate::hash::{hash_all, HashContext, HashExt, Hashable};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
 use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::hash::Hasher;
-use core::mem;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use core::slice;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Feeds the native bytes of the integers of `data` in one write.
+#[cfg(any(target_endian = "little", feature = "scheme_v1"))]
+#[allow(trivial_casts, unsafe_code)]
+fn write_native<H: Hasher, T: Copy>(data: &[T], state: &mut H) {
+    let newlen = size_of_val(data);
+    let ptr = data.as_ptr() as *const u8;
+    state.write(unsafe { slice::from_raw_parts(ptr, newlen) })
+}
 
 macro_rules! impl_write {
     ($(($ty:ident, $meth:ident),)*) => {$(
         impl<H: Hasher> Hashable<H> for $ty {
+            #[cfg(not(feature = "scheme_v1"))]
+            fn hash(&self, state: &mut H) {
+                state.write(&self.to_le_bytes())
+            }
+
+            // 0.4.0 feeds the native bytes
+            #[cfg(feature = "scheme_v1")]
             fn hash(&self, state: &mut H) {
                 state.$meth(*self)
             }
 
-            #[allow(trivial_casts, unsafe_code)]
+            // native bytes are little-endian already, elsewhere each
+            // element is converted by `hash`
+            #[cfg(any(target_endian = "little", feature = "scheme_v1"))]
             fn hash_slice(data: &[$ty], state: &mut H) {
-                let newlen = data.len() * mem::size_of::<$ty>();
-                let ptr = data.as_ptr() as *const u8;
-                state.write(unsafe { slice::from_raw_parts(ptr, newlen) })
+                write_native(data, state)
             }
         }
+
+        impl<H: Hasher> HashExt<H> for $ty {}
     )*}
 }
 
+// Integers are fed as little-endian bytes, so hashes of the same values
+// are the same on any target.
 impl_write! {
     (u8, write_u8),
     (u16, write_u16),
     (u32, write_u32),
     (u64, write_u64),
-    (usize, write_usize),
     (i8, write_i8),
     (i16, write_i16),
     (i32, write_i32),
     (i64, write_i64),
-    (isize, write_isize),
     // unstable: (u128, write_u128),
     // unstable: (i128, write_i128),
 }
 
-macro_rules! impl_array {
-    ($ty:ident $($N:expr)+) => {$(
-        impl<H: Hasher> Hashable<H> for [$ty; $N] {
-            fn hash(&self, state: &mut H) {
-                state.write(self.as_ref())
-            }
+// usize and isize are fed as 64 bit integers, so lengths hash the same on
+// 32 and 64 bit targets.
+impl<H: Hasher> Hashable<H> for usize {
+    #[cfg(not(feature = "scheme_v1"))]
+    fn hash(&self, state: &mut H) {
+        (*self as u64).hash(state)
+    }
 
-            #[allow(trivial_casts, unsafe_code)]
-            fn hash_slice(data: &[[$ty; $N]], state: &mut H) {
-                let newlen = data.len() * mem::size_of::<[$ty; $N]>();
-                let ptr = data.as_ptr() as *const u8;
-                state.write(unsafe { slice::from_raw_parts(ptr, newlen) })
-            }
-        }
-    )*}
+    // 0.4.0 feeds the native bytes of the target width
+    #[cfg(feature = "scheme_v1")]
+    fn hash(&self, state: &mut H) {
+        state.write_usize(*self)
+    }
+
+    #[cfg(feature = "scheme_v1")]
+    fn hash_slice(data: &[usize], state: &mut H) {
+        write_native(data, state)
+    }
+}
+
+impl<H: Hasher> Hashable<H> for isize {
+    #[cfg(not(feature = "scheme_v1"))]
+    fn hash(&self, state: &mut H) {
+        (*self as i64).hash(state)
+    }
+
+    #[cfg(feature = "scheme_v1")]
+    fn hash(&self, state: &mut H) {
+        state.write_isize(*self)
+    }
+
+    #[cfg(feature = "scheme_v1")]
+    fn hash_slice(data: &[isize], state: &mut H) {
+        write_native(data, state)
+    }
 }
 
-impl_array! { u8
-  1  2  3  4  5  6  7  8  9 10
- 11 12 13 14 15 16 17 18 19 20
- 21 22 23 24 25 26 27 28 29 30
- 31 32 33 34 35 36 37 38 39 40
- 41 42 43 44 45 46 47 48 49 50
- 51 52 53 54 55 56 57 58 59 60
- 61 62 63 64
+impl<H: Hasher> HashExt<H> for usize {}
+impl<H: Hasher> HashExt<H> for isize {}
+
+impl<H: Hasher, T: Hashable<H>, const N: usize> Hashable<H> for [T; N] {
+    // fixed size, no length prefix
+    fn hash(&self, state: &mut H) {
+        Hashable::hash_slice(self, state)
+    }
 }
 
-impl<H: Hasher> Hashable<H> for Vec<u8> {
+impl<H: Hasher, T: Hashable<H>> Hashable<H> for Vec<T> {
+    #[cfg(not(feature = "scheme_v1"))]
     fn hash(&self, state: &mut H) {
-        state.write(self.as_ref());
+        self.as_slice().hash(state)
+    }
+
+    // 0.4.0 feeds `Vec<u8>` as the raw bytes, no length prefix
+    #[cfg(feature = "scheme_v1")]
+    fn hash(&self, state: &mut H) {
+        Hashable::hash_slice(self, state)
+    }
+}
+
+impl<H: Hasher, T: Hashable<H>> Hashable<H> for Option<T> {
+    fn hash(&self, state: &mut H) {
+        match *self {
+            None => 0usize.hash(state),
+            Some(ref v) => {
+                1usize.hash(state);
+                v.hash(state)
+            }
+        }
     }
 }
 
@@ -78,26 +147,217 @@ impl<H: Hasher> Hashable<H> for bool {
     }
 }
 
+macro_rules! impl_float {
+    ($($ty:ident,)*) => {$(
+        // -0.0 is fed as 0.0 and every NaN as the canonical one, so that
+        // equal values hash equal
+        impl<H: Hasher> Hashable<H> for $ty {
+            fn hash(&self, state: &mut H) {
+                let canonical = if self.is_nan() {
+                    $ty::NAN
+                } else if *self == 0.0 {
+                    0.0
+                } else {
+                    *self
+                };
+                canonical.to_bits().hash(state)
+            }
+        }
+
+        impl<H: Hasher> HashExt<H> for $ty {}
+    )*}
+}
+
+impl_float! {
+    f32,
+    f64,
+}
+
 impl<H: Hasher> Hashable<H> for char {
     fn hash(&self, state: &mut H) {
-        state.write_u32(*self as u32)
+        (*self as u32).hash(state)
     }
 }
 
+// Strings are terminated by 0xff as std does, the byte never occurs in
+// UTF-8, so strings of a tuple, slice or struct never run into each other.
 impl<H: Hasher> Hashable<H> for str {
     fn hash(&self, state: &mut H) {
         state.write(self.as_bytes());
-        // empty str nope: state.write_u8(0xff)
+        // 0.4.0 feeds the bytes only
+        #[cfg(not(feature = "scheme_v1"))]
+        state.write(&[0xff]);
     }
 }
 
 impl<H: Hasher> Hashable<H> for String {
     fn hash(&self, state: &mut H) {
-        state.write(self.as_bytes());
-        // empty str nope: state.write_u8(0xff)
+        self.as_str().hash(state)
     }
 }
 
+impl<H: Hasher> HashExt<H> for bool {}
+impl<H: Hasher> HashExt<H> for char {}
+
+impl<H: Hasher> HashExt<H> for str {
+    fn hash_prefixed(&self, state: &mut H) {
+        self.len().hash(state);
+        self.hash(state)
+    }
+}
+
+impl<H: Hasher> HashExt<H> for String {
+    fn hash_prefixed(&self, state: &mut H) {
+        self.as_str().hash_prefixed(state)
+    }
+}
+
+impl<H: Hasher, T: HashExt<H>> HashExt<H> for [T] {
+    fn hash_prefixed(&self, state: &mut H) {
+        hash_all(self, state)
+    }
+}
+
+impl<H: Hasher, T: HashExt<H>> HashExt<H> for Vec<T> {
+    fn hash_prefixed(&self, state: &mut H) {
+        hash_all(self, state)
+    }
+}
+
+impl<H: Hasher, T: HashExt<H>, const N: usize> HashExt<H> for [T; N] {
+    // fixed size, no length prefix
+    fn hash_prefixed(&self, state: &mut H) {
+        for item in self {
+            item.hash_prefixed(state);
+        }
+    }
+}
+
+impl<H: Hasher, T: HashExt<H>> HashExt<H> for Option<T> {
+    fn hash_prefixed(&self, state: &mut H) {
+        match *self {
+            None => 0usize.hash(state),
+            Some(ref v) => {
+                1usize.hash(state);
+                v.hash_prefixed(state)
+            }
+        }
+    }
+}
+
+// Maps are fed as the entry count and the entries in the key order, so
+// equal maps hash equal whatever the order of the insertions, and a
+// `HashMap` hashes as the `BTreeMap` of the same entries.
+impl<H: Hasher, K: Hashable<H>, V: Hashable<H>> Hashable<H> for BTreeMap<K, V> {
+    fn hash(&self, state: &mut H) {
+        self.len().hash(state);
+        for (k, v) in self {
+            k.hash(state);
+            v.hash(state);
+        }
+    }
+}
+
+impl<H: Hasher, K: HashExt<H>, V: HashExt<H>> HashExt<H> for BTreeMap<K, V> {
+    fn hash_prefixed(&self, state: &mut H) {
+        self.len().hash(state);
+        for (k, v) in self {
+            k.hash_prefixed(state);
+            v.hash_prefixed(state);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn sorted<K: Ord, V, S>(map: &HashMap<K, V, S>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = map.iter().collect();
+    entries.sort_unstable_by(|l, r| l.0.cmp(r.0));
+    entries
+}
+
+#[cfg(feature = "std")]
+impl<H: Hasher, K: Ord + Hashable<H>, V: Hashable<H>, S> Hashable<H> for HashMap<K, V, S> {
+    fn hash(&self, state: &mut H) {
+        self.len().hash(state);
+        for (k, v) in sorted(self) {
+            k.hash(state);
+            v.hash(state);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: Hasher, K: Ord + HashExt<H>, V: HashExt<H>, S> HashExt<H> for HashMap<K, V, S> {
+    fn hash_prefixed(&self, state: &mut H) {
+        self.len().hash(state);
+        for (k, v) in sorted(self) {
+            k.hash_prefixed(state);
+            v.hash_prefixed(state);
+        }
+    }
+}
+
+impl<H: Hasher> Hashable<H> for Duration {
+    fn hash(&self, state: &mut H) {
+        self.as_secs().hash(state);
+        self.subsec_nanos().hash(state)
+    }
+}
+
+// Time is fed as the duration since the unix epoch, tagged 0, or the one
+// until the epoch for the older times, tagged 1.
+#[cfg(feature = "std")]
+impl<H: Hasher> Hashable<H> for SystemTime {
+    fn hash(&self, state: &mut H) {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => {
+                0u8.hash(state);
+                since.hash(state)
+            }
+            Err(until) => {
+                1u8.hash(state);
+                until.duration().hash(state)
+            }
+        }
+    }
+}
+
+impl<H: Hasher> Hashable<H> for Ipv4Addr {
+    fn hash(&self, state: &mut H) {
+        self.octets().hash(state)
+    }
+}
+
+impl<H: Hasher> Hashable<H> for Ipv6Addr {
+    fn hash(&self, state: &mut H) {
+        self.octets().hash(state)
+    }
+}
+
+// Address octets are tagged by the version, so that v4 and v6 addresses
+// never feed the same bytes.
+impl<H: Hasher> Hashable<H> for IpAddr {
+    fn hash(&self, state: &mut H) {
+        match *self {
+            IpAddr::V4(ref ip) => {
+                4u8.hash(state);
+                ip.hash(state)
+            }
+            IpAddr::V6(ref ip) => {
+                6u8.hash(state);
+                ip.hash(state)
+            }
+        }
+    }
+}
+
+impl<H: Hasher> HashExt<H> for Duration {}
+#[cfg(feature = "std")]
+impl<H: Hasher> HashExt<H> for SystemTime {}
+impl<H: Hasher> HashExt<H> for Ipv4Addr {}
+impl<H: Hasher> HashExt<H> for Ipv6Addr {}
+impl<H: Hasher> HashExt<H> for IpAddr {}
+
 macro_rules! impl_hash_tuple {
     () => (
         impl<H: Hasher> Hashable<H> for () {
@@ -145,22 +405,92 @@ impl<H: Hasher, T: Hashable<H>> Hashable<H> for [T] {
     }
 }
 
-impl<'a, H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &'a T {
+impl<H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &T {
     fn hash(&self, state: &mut H) {
         (**self).hash(state);
     }
+
+    fn hash_with_context(&self, state: &mut H, ctx: HashContext) {
+        (**self).hash_with_context(state, ctx);
+    }
+}
+
+impl<H: Hasher, T: ?Sized + HashExt<H>> HashExt<H> for &T {
+    fn hash_prefixed(&self, state: &mut H) {
+        (**self).hash_prefixed(state);
+    }
 }
 
-impl<'a, H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &'a mut T {
+impl<H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &mut T {
     fn hash(&self, state: &mut H) {
         (**self).hash(state);
     }
+
+    fn hash_with_context(&self, state: &mut H, ctx: HashContext) {
+        (**self).hash_with_context(state, ctx);
+    }
+}
+
+impl<H: Hasher, T: ?Sized + HashExt<H>> HashExt<H> for &mut T {
+    fn hash_prefixed(&self, state: &mut H) {
+        (**self).hash_prefixed(state);
+    }
+}
+
+impl<H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for Box<T> {
+    fn hash(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+
+    fn hash_with_context(&self, state: &mut H, ctx: HashContext) {
+        (**self).hash_with_context(state, ctx);
+    }
+}
+
+impl<H: Hasher, T: ?Sized + HashExt<H>> HashExt<H> for Box<T> {
+    fn hash_prefixed(&self, state: &mut H) {
+        (**self).hash_prefixed(state);
+    }
+}
+
+impl<H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for Rc<T> {
+    fn hash(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+
+    fn hash_with_context(&self, state: &mut H, ctx: HashContext) {
+        (**self).hash_with_context(state, ctx);
+    }
+}
+
+impl<H: Hasher, T: ?Sized + HashExt<H>> HashExt<H> for Rc<T> {
+    fn hash_prefixed(&self, state: &mut H) {
+        (**self).hash_prefixed(state);
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for Arc<T> {
+    fn hash(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+
+    fn hash_with_context(&self, state: &mut H, ctx: HashContext) {
+        (**self).hash_with_context(state, ctx);
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<H: Hasher, T: ?Sized + HashExt<H>> HashExt<H> for Arc<T> {
+    fn hash_prefixed(&self, state: &mut H) {
+        (**self).hash_prefixed(state);
+    }
 }
 
 impl<H: Hasher, T: ?Sized> Hashable<H> for *const T {
     #[allow(trivial_casts, unsafe_code)]
     fn hash(&self, state: &mut H) {
-        if mem::size_of::<Self>() == mem::size_of::<usize>() {
+        if size_of::<Self>() == size_of::<usize>() {
             // Thin pointer
             state.write_usize(*self as *const () as usize);
         } else {
@@ -175,7 +505,7 @@ impl<H: Hasher, T: ?Sized> Hashable<H> for *const T {
 impl<H: Hasher, T: ?Sized> Hashable<H> for *mut T {
     #[allow(trivial_casts, unsafe_code)]
     fn hash(&self, state: &mut H) {
-        if mem::size_of::<Self>() == mem::size_of::<usize>() {
+        if size_of::<Self>() == size_of::<usize>() {
             // Thin pointer
             state.write_usize(*self as *const () as usize);
         } else {
@@ -0,0 +1,67 @@
+//! Nodes of a tree are packed level by level, the leafs first and the root
+//! last, see [`MerkleTree::as_slice`]. Node `i` of `level` is stored at
+//! `level_offset(leafs, level, padded) + i`, where `padded` is `false` for
+//! the trees of [`PaddingPolicy::PromoteOdd`] and `true` otherwise.
+//!
+//! Nodes of 8 leafs tree:
+//!
+//! ```text
+//!     level 0: [ 0  1  2  3  4  5  6  7 ]
+//!     level 1: [ 8  9 10 11 ]
+//!     level 2: [12 13 ]
+//!     level 3: [14 ]
+//! ```
+//!
+//! [`MerkleTree::as_slice`]: crate::merkle::MerkleTree::as_slice
+//! [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+
+/// Returns index of the parent of node `i` within the level above.
+pub fn parent(i: usize) -> usize {
+    i >> 1
+}
+
+/// Returns index of the sibling of node `i` within its level of
+/// `level_len` nodes.
+///
+/// Returns `None` for the last node of an odd level, which is paired with
+/// the padding node or promoted, and for `i` out of the level.
+pub fn sibling(i: usize, level_len: usize) -> Option<usize> {
+    let j = i ^ 1;
+    if i < level_len && j < level_len {
+        Some(j)
+    } else {
+        None
+    }
+}
+
+/// Returns number of nodes of `level` in the tree of `leafs` leafs, the
+/// padding node excluded.
+pub fn level_len(leafs: usize, level: usize) -> usize {
+    let mut len = leafs;
+    for _ in 0..level {
+        len = (len + 1) >> 1;
+    }
+    len
+}
+
+/// Returns number of nodes stored for the level of `level_len` nodes, which
+/// includes the padding node of odd levels below the root if `padded`.
+pub fn stored_len(level_len: usize, padded: bool) -> usize {
+    if padded && level_len > 1 {
+        level_len + (level_len & 1)
+    } else {
+        level_len
+    }
+}
+
+/// Returns position of the first node of `level` in the packed nodes of the
+/// tree of `leafs` leafs.
+pub fn level_offset(leafs: usize, level: usize, padded: bool) -> usize {
+    let mut offset = 0;
+    let mut len = leafs;
+    for _ in 0..level {
+        offset += stored_len(len, padded);
+        len = (len + 1) >> 1;
+    }
+    offset
+}
@@ -56,13 +56,26 @@
 //! - build_tree (items) -> tree
 //! - get_root -> hash
 //! - gen_proof -> proof
+//! - gen_multi_proof (indices) -> multiproof
+//! - gen_range_proof (range) -> rangeproof
+//! - gen_consistency_proof (old_size) -> consistencyproof
 //! - validate_proof (proof, leaf, root) -> bool
+//! - verify_inclusion (root, leaf, index, lemma) -> bool
+//! - ProofVerifier (leaf, index) -> feed_sibling (hash) -> finalize -> root
 //! ```
 //!
+//! # no_std
+//!
+//! The crate is `no_std` and needs only `alloc`, so proofs can be verified
+//! on embedded targets. The default `std` feature adds `hash_reader` and
+//! `std::error::Error` for `MerkleError`. `merkle_no_std` crate of the
+//! workspace checks the verification path builds for a bare metal target.
+//!
 //! # Examples
 //!
 //! [`test_sip.rs`]: algorithm implementation example for std sip hasher, u64 hash items
 //! [`test_xor128.rs`]: custom hash example xor128
+//! [`test_xor512.rs`]: 64 bytes hash items example xor512
 //! [`test_cmh.rs`]: custom merkle hasher implementation example
 //! [`crypto_bitcoin_mt.rs`]: bitcoin merkle tree using crypto lib
 //! [`crypto_chaincore_mt.rs`]: chain core merkle tree using crypto lib
@@ -157,7 +170,6 @@
     unstable_features,
     unused_import_braces
 )]
-#![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(feature = "nightly", allow(unstable_features))]
 
 #[macro_use]
@@ -172,9 +184,51 @@ mod hash_impl;
 /// Merkle tree inclusion proof
 pub mod proof;
 
+/// Merkle tree batched inclusion proof
+pub mod multiproof;
+
+/// Merkle tree inclusion proof for a range of leafs
+pub mod rangeproof;
+
+/// Merkle tree consistency proof
+pub mod consistency;
+
+/// Merkle tree inclusion proof verification without the proof types.
+pub mod verify;
+
+/// Merkle tree errors.
+pub mod error;
+
+/// Merkle tree with partially cached interior nodes.
+pub mod cached;
+
+/// Sparse merkle tree keyed by fixed-size keys.
+pub mod sparse;
+
+/// Merkle tree of configurable arity.
+pub mod nary;
+
+/// Merkle tree of fixed depth stored in arrays.
+pub mod fixed;
+
+/// Ready to use [`Algorithm`] implementations.
+///
+/// [`Algorithm`]: crate::hash::Algorithm
+#[cfg(any(feature = "blake2b", feature = "keccak", feature = "bitcoin"))]
+pub mod algorithms;
+
 /// Merkle tree abstractions, implementation and algorithms.
 pub mod merkle;
 
+/// Merkle tree nodes storage backends.
+pub mod store;
+
+/// Index arithmetic of the packed nodes of merkle tree.
+pub mod layout;
+
+/// Versioned construction rules of merkle tree.
+pub mod scheme;
+
 /// Tests data.
 #[cfg(test)]
 mod test_item;
@@ -183,6 +237,10 @@ mod test_item;
 #[cfg(test)]
 mod test_xor128;
 
+/// Tests XOR512.
+#[cfg(test)]
+mod test_xor512;
+
 /// Tests SIP.
 #[cfg(test)]
 mod test_sip;
@@ -190,3 +248,55 @@ mod test_sip;
 /// Tests for Merkle Hasher Customization
 #[cfg(test)]
 mod test_cmh;
+
+/// Tests for batched inclusion proofs.
+#[cfg(test)]
+mod test_multiproof;
+
+/// Tests for range inclusion proofs.
+#[cfg(test)]
+mod test_rangeproof;
+
+/// Tests for consistency proofs.
+#[cfg(test)]
+mod test_consistency;
+
+/// Tests for proof verification.
+#[cfg(test)]
+mod test_verify;
+
+/// Tests for cached merkle tree.
+#[cfg(test)]
+mod test_cached;
+
+/// Tests for sparse merkle tree.
+#[cfg(test)]
+mod test_sparse;
+
+/// Tests for n-ary merkle tree.
+#[cfg(test)]
+mod test_nary;
+
+/// Tests for fixed depth merkle tree.
+#[cfg(test)]
+mod test_fixed;
+
+/// Tests for nodes layout.
+#[cfg(test)]
+mod test_layout;
+
+/// Tests for nodes storage backends.
+#[cfg(test)]
+mod test_store;
+
+/// Tests for construction scheme.
+#[cfg(test)]
+mod test_scheme;
+
+/// Tests for algorithms implementations.
+#[cfg(test)]
+mod test_algorithms;
+
+/// Tests for serde support.
+#[cfg(all(test, feature = "serde"))]
+mod test_serde;
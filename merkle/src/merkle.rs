@@ -1,11 +1,119 @@
 extern crate alloc;
 
-use crate::hash::{Algorithm, Hashable};
-use crate::proof::Proof;
+use crate::consistency::ConsistencyProof;
+use crate::error::MerkleError;
+use crate::hash::{to_hex, Algorithm, HashContext, Hashable};
+use crate::layout;
+use crate::multiproof::MultiProof;
+use crate::proof::{path_matches_index, PathElement, Proof, Side};
+use crate::rangeproof::RangeProof;
+use crate::scheme::SchemeId;
+use crate::store::{Store, VecStore};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
 use alloc::vec::Vec;
-use core::iter::FromIterator;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{self, FromIterator};
 use core::marker::PhantomData;
-use core::ops;
+use core::mem;
+use core::ops::{self, ControlFlow, Range};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "rayon")]
+use rayon::{ThreadPoolBuildError, ThreadPoolBuilder};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Policy of filling in odd width levels of the tree.
+///
+/// Merkle tree implementations disagree on what to do with the last node of
+/// a level with odd number of nodes, which results in different roots over
+/// the same leafs. Pick the policy of the system you interoperate with:
+///
+/// - Bitcoin (block transactions tree) duplicates the last node:
+///   [`PaddingPolicy::DuplicateLast`].
+/// - Certificate Transparency ([RFC 6962](https://tools.ietf.org/html/rfc6962))
+///   and Tendermint / CometBFT promote the last node to the next level
+///   unchanged: [`PaddingPolicy::PromoteOdd`].
+/// - Fixed width accumulators pairing the last node with an empty placeholder
+///   node: [`PaddingPolicy::HashWithZero`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PaddingPolicy {
+    /// Last node is hashed together with its own copy. Default.
+    #[default]
+    DuplicateLast,
+    /// Last node is carried up to the next level without hashing.
+    PromoteOdd,
+    /// Last node is hashed together with the zero (`T::default()`) node.
+    HashWithZero,
+}
+
+/// Order of the children in the interior node hash.
+///
+/// Positional trees hash `node(left, right)`, so proofs carry a direction bit
+/// per level. Sorted trees hash `node(min(left, right), max(left, right))`,
+/// as OpenZeppelin `MerkleProof` does for set membership, and their proofs
+/// carry no path bits at all, see [`Proof::validate_sorted`]. Nodes are
+/// compared by their bytes, so the hash type does not need to be `Ord`.
+///
+/// Batched proofs and [`MerkleTree::verify_proof`] are positional only.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NodeOrder {
+    /// Children are hashed left to right. Default.
+    #[default]
+    Positional,
+    /// Children are hashed in ascending order of their bytes.
+    Sorted,
+}
+
+impl NodeOrder {
+    /// Returns interior node hash of `left` and `right` in this order.
+    pub(crate) fn node<T, A>(self, a: &mut A, left: T, right: T) -> T
+    where
+        T: Eq + Clone + AsRef<[u8]>,
+        A: Algorithm<T>,
+    {
+        match self {
+            NodeOrder::Sorted if right.as_ref() < left.as_ref() => a.node(right, left),
+            _ => a.node(left, right),
+        }
+    }
+}
+
+/// Resolved [`PaddingPolicy`] which carries the zero node if needed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Padding<T> {
+    Duplicate,
+    Promote,
+    Zero(T),
+}
+
+impl<T: Default> Padding<T> {
+    fn from_policy(policy: PaddingPolicy) -> Padding<T> {
+        match policy {
+            PaddingPolicy::DuplicateLast => Padding::Duplicate,
+            PaddingPolicy::PromoteOdd => Padding::Promote,
+            PaddingPolicy::HashWithZero => Padding::Zero(T::default()),
+        }
+    }
+}
+
+/// Progress of [`MerkleTree::from_data_with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildProgress {
+    /// Number of the leafs hashed so far.
+    pub leafs: usize,
+    /// Number of the levels completed, the leaf level included, so `0`
+    /// while the leafs are hashed and [`MerkleTree::height`] at the end.
+    pub levels: usize,
+}
+
+/// Number of the leafs hashed between two progress reports.
+const PROGRESS_LEAFS: usize = 1024;
 
 /// Merkle Tree.
 ///
@@ -37,194 +145,2323 @@ use core::ops;
 /// Since this function uses nodes that are pointers to the hashes, empty nodes
 /// will be nil.
 ///
-/// TODO: Ord
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct MerkleTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
-    data: Vec<T>,
+/// The way the single left node is treated is defined by [`PaddingPolicy`].
+/// Padding nodes are stored in the level they complete, promoted nodes are
+/// stored again in the next level.
+///
+/// With `serde` feature the tree is serialized as the full nodes array plus
+/// its shape, so deserialization does not recompute any hash.
+///
+/// Nodes are kept in a [`Store`], in memory [`VecStore`] by default. Trees
+/// built into another store with [`MerkleTree::from_data_with_store`] have
+/// the same nodes, root and proofs, but can not be appended to.
+///
+/// Tree holds no algorithm instance, proofs are generated from `&self`
+/// hashing nothing, so it is `Send` and `Sync` whenever the nodes and the
+/// store are, whatever the algorithm. Many threads can serve proofs of one
+/// `Arc<MerkleTree>` without locking.
+///
+/// Trees are equal if they have the same nodes and the same number of
+/// leafs, regardless of the store and of the algorithm type, which does not
+/// need to implement `PartialEq` nor `Hash`.
+///
+/// Hashes need not be `Ord`, only the leaf index of
+/// [`MerkleTree::build_index`] orders them.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: Serialize, S: Serialize",
+        deserialize = "T: Deserialize<'de>, S: Deserialize<'de>"
+    ))
+)]
+pub struct MerkleTree<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T> = VecStore<T>> {
+    data: S,
     leafs: usize,
     height: usize,
-    _a: PhantomData<A>,
+    padding: Padding<T>,
+    order: NodeOrder,
+    #[cfg_attr(feature = "serde", serde(default))]
+    raw: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pruned: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    index: Option<BTreeMap<T, usize>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _a: PhantomData<fn() -> A>,
 }
 
-impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, A> {
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, A> {
     /// Creates new merkle from a sequence of hashes.
     pub fn new<I: IntoIterator<Item = T>>(data: I) -> MerkleTree<T, A> {
         Self::from_iter(data)
     }
 
     /// Creates new merkle tree from a list of hashable objects.
+    ///
+    /// Objects are hashed one by one through [`Hashable`], which has no
+    /// byte view to batch, see [`MerkleTree::from_byte_slices`].
     pub fn from_data<O: Hashable<A>, I: IntoIterator<Item = O>>(data: I) -> MerkleTree<T, A> {
         let mut a = A::default();
         Self::from_iter(data.into_iter().map(|x| {
             a.reset();
-            x.hash(&mut a);
+            x.hash_with_context(&mut a, HashContext::Leaf);
             a.hash()
         }))
     }
 
-    fn build(&mut self) {
+    /// Creates new merkle tree from a list of hashable objects, as
+    /// [`MerkleTree::from_data`] does, reporting the `progress` of the build.
+    ///
+    /// Progress is reported every 1024 leafs hashed and at every level
+    /// completed. The build stops with [`MerkleError::Cancelled`] as soon as
+    /// `progress` breaks.
+    pub fn from_data_with_progress<O, I, P>(
+        data: I,
+        mut progress: P,
+    ) -> Result<MerkleTree<T, A>, MerkleError>
+    where
+        O: Hashable<A>,
+        I: IntoIterator<Item = O>,
+        P: FnMut(BuildProgress) -> ControlFlow<()>,
+    {
         let mut a = A::default();
-        let mut width = self.leafs;
+        let iter = data.into_iter();
+        let (lower, upper) = iter.size_hint();
+        let mut data: Vec<T> =
+            Vec::with_capacity(upper.and_then(|e| tree_size(e).ok()).unwrap_or(lower));
 
-        // build tree
-        let mut i: usize = 0;
-        let mut j: usize = width;
-        while width > 1 {
-            // if there is odd num of elements, fill in to the even
-            if width & 1 == 1 {
-                let he = self.data[self.len() - 1].clone();
-                self.data.push(he);
-                width += 1;
-                j += 1;
+        for x in iter {
+            a.reset();
+            x.hash_with_context(&mut a, HashContext::Leaf);
+            let h = a.hash();
+            a.reset();
+            data.push(a.leaf(h));
+
+            let leafs = data.len();
+            if leafs & (PROGRESS_LEAFS - 1) == 0
+                && progress(BuildProgress { leafs, levels: 0 }).is_break()
+            {
+                return Err(MerkleError::Cancelled);
             }
+        }
 
-            // next shift
-            while i < j {
-                a.reset();
-                let h = a.node(self.data[i].clone(), self.data[i + 1].clone());
-                self.data.push(h);
-                i += 2;
+        let leafs = data.len();
+        if leafs == 0 {
+            return Ok(Self::empty());
+        }
+        let size = tree_size(leafs)?;
+        data.reserve(size - leafs);
+
+        let mut mt: MerkleTree<T, A> = MerkleTree {
+            data,
+            leafs,
+            height: log2_pow2(size + 1),
+            padding: Padding::Duplicate,
+            order: NodeOrder::Positional,
+            raw: false,
+            pruned: 0,
+            index: None,
+            _a: PhantomData,
+        };
+
+        let report = |levels| progress(BuildProgress { leafs, levels });
+        match mt.build_levels(&[], 0, a, |a: &mut A| a.reset(), report) {
+            ControlFlow::Continue(()) => Ok(mt),
+            ControlFlow::Break(()) => Err(MerkleError::Cancelled),
+        }
+    }
+
+    /// Creates new merkle tree from a list of hashable objects, rejecting
+    /// the trees of ambiguous root.
+    ///
+    /// Odd levels are padded with the duplicate of the last node, so a tree
+    /// which has equal siblings at any level, e.g. leafs `[a b c c]`, has
+    /// the same root as the one without the duplicate, `[a b c]`
+    /// (CVE-2012-2459). Consensus code which identifies leaf sets by the
+    /// root must not accept such trees.
+    pub fn from_data_checked<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+    ) -> Result<MerkleTree<T, A>, MerkleError> {
+        let mt = Self::from_data(data);
+
+        let mut width = mt.leafs;
+        for level in mt.levels() {
+            // padding node is the twin of the last node by construction
+            if level[..width]
+                .chunks_exact(2)
+                .any(|pair| pair[0] == pair[1])
+            {
+                return Err(MerkleError::DuplicateAmbiguity);
             }
+            width = (width + 1) >> 1;
+        }
+        Ok(mt)
+    }
 
-            width >>= 1;
-            j += width;
+    /// Creates new merkle tree from raw leaf bytes.
+    ///
+    /// Each slice is hashed as is, without a length prefix, i.e. the leaf is
+    /// `leaf(hash(bytes))`. The slices are hashed in one
+    /// [`Algorithm::hash_leaves_batch`] call.
+    pub fn from_byte_slices<B: AsRef<[u8]>, I: IntoIterator<Item = B>>(
+        data: I,
+    ) -> MerkleTree<T, A> {
+        let data: Vec<B> = data.into_iter().collect();
+        let leaves: Vec<&[u8]> = data.iter().map(AsRef::as_ref).collect();
+        Self::from_iter(A::default().hash_leaves_batch(&leaves))
+    }
+
+    /// Creates new merkle tree from a list of hashable objects padding odd
+    /// levels according to `policy`.
+    pub fn from_data_with_policy<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        policy: PaddingPolicy,
+    ) -> MerkleTree<T, A>
+    where
+        T: Default,
+    {
+        let mut a = A::default();
+        Self::from_leafs(
+            Vec::new(),
+            data.into_iter().map(|x| {
+                a.reset();
+                x.hash_with_context(&mut a, HashContext::Leaf);
+                a.hash()
+            }),
+            Padding::from_policy(policy),
+            NodeOrder::Positional,
+            false,
+            A::default(),
+            |a: &mut A| a.reset(),
+        )
+    }
+
+    /// Creates new merkle tree from a list of hashable objects hashing the
+    /// children of interior nodes in `order`.
+    pub fn from_data_with_order<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        order: NodeOrder,
+    ) -> MerkleTree<T, A> {
+        let mut a = A::default();
+        Self::from_leafs(
+            Vec::new(),
+            data.into_iter().map(|x| {
+                a.reset();
+                x.hash_with_context(&mut a, HashContext::Leaf);
+                a.hash()
+            }),
+            Padding::Duplicate,
+            order,
+            false,
+            A::default(),
+            |a: &mut A| a.reset(),
+        )
+    }
+
+    /// Creates new merkle tree from a list of hashable objects hashing with
+    /// a preconfigured `algo` instance, e.g. a keyed or personalized one
+    /// which can not be expressed by `Default`.
+    ///
+    /// Every hash starts from a fresh clone of `algo` instead of a reset
+    /// one. Proofs of such trees are validated with
+    /// [`Proof::validate_with_hasher`]. Appending to the tree with
+    /// [`MerkleTree::push`] or [`MerkleTree::extend`] hashes with
+    /// `A::default()`.
+    pub fn from_data_with_hasher<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        algo: A,
+    ) -> MerkleTree<T, A>
+    where
+        A: Clone,
+    {
+        let mut a = algo.clone();
+        Self::from_leafs(
+            Vec::new(),
+            data.into_iter().map(|x| {
+                a.clone_from(&algo);
+                x.hash_with_context(&mut a, HashContext::Leaf);
+                a.hash()
+            }),
+            Padding::Duplicate,
+            NodeOrder::Positional,
+            false,
+            algo.clone(),
+            |a: &mut A| a.clone_from(&algo),
+        )
+    }
+
+    /// Creates new merkle tree from a list of leaf values, which are kept
+    /// along with the nodes, so the tree can be rebuilt under another
+    /// algorithm with [`RetainedMerkleTree::rebuild_with`].
+    ///
+    /// The tree is the one [`MerkleTree::from_data`] builds from the values.
+    pub fn retain_leaves<L: Hashable<A>, I: IntoIterator<Item = L>>(
+        values: I,
+    ) -> RetainedMerkleTree<T, A, L> {
+        let values: Vec<L> = values.into_iter().collect();
+        RetainedMerkleTree {
+            tree: Self::from_data(&values),
+            values,
         }
     }
 
-    /// Generate merkle tree inclusion proof for leaf `i`
-    pub fn gen_proof(&self, i: usize) -> Proof<T> {
-        assert!(i < self.leafs); // i in [0 .. self.leafs)
+    /// Loads the tree of `leafs` leafs from the nodes packed by
+    /// [`MerkleTree::to_packed_bytes`], hashes are of the size of
+    /// `T::default()`.
+    ///
+    /// The tree is assumed to be built by the [`MerkleTree::from_data`]
+    /// rules. Fails on an empty tree and if the length of `bytes` is not the
+    /// one of the tree nodes. The nodes are not rehashed, see
+    /// [`MerkleTree::verify_self`].
+    pub fn from_packed_bytes(bytes: &[u8], leafs: usize) -> Result<MerkleTree<T, A>, MerkleError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        if leafs == 0 {
+            return Err(MerkleError::EmptyTree);
+        }
+        let size = tree_size(leafs)?;
+        let height = log2_pow2(size + 1);
+        let nodes = layout::level_offset(leafs, height - 1, true) + 1;
 
-        let mut lemma: Vec<T> = Vec::with_capacity(self.height + 1); // path + root
-        let mut path: Vec<bool> = Vec::with_capacity(self.height - 1); // path - 1
+        let hash = T::default().as_ref().len();
+        if hash == 0 || nodes.checked_mul(hash) != Some(bytes.len()) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let data: Vec<T> = bytes
+            .chunks_exact(hash)
+            .map(|c| {
+                let mut h = T::default();
+                h.as_mut().copy_from_slice(c);
+                h
+            })
+            .collect();
 
-        let mut base = 0;
-        let mut j = i;
+        Ok(MerkleTree {
+            data,
+            leafs,
+            height,
+            padding: Padding::Duplicate,
+            order: NodeOrder::Positional,
+            raw: false,
+            pruned: 0,
+            index: None,
+            _a: PhantomData,
+        })
+    }
 
-        // level 1 width
-        let mut width = self.leafs;
-        if width & 1 == 1 {
-            width += 1;
+    /// Creates new merkle tree over leaf nodes hashed elsewhere.
+    ///
+    /// No leaf hashing is applied: `hashes` are the leafs of the tree as
+    /// they are stored and proven, see [`Proof::item`], and only the
+    /// interior nodes are hashed, with domain separation. To get the tree
+    /// [`MerkleTree::from_data`] builds, the hashes have to be the leaf
+    /// hashes [`Algorithm::leaf`] returns, not the plain data hashes which
+    /// [`MerkleTree::new`] takes.
+    pub fn from_leaf_hashes(hashes: Vec<T>) -> MerkleTree<T, A> {
+        let leafs = hashes.len();
+        if leafs == 0 {
+            return Self::empty();
         }
 
-        lemma.push(self.data[j].clone());
-        while base + 1 < self.len() {
-            lemma.push(if j & 1 == 0 {
-                // j is left
-                self.data[base + j + 1].clone()
-            } else {
-                // j is right
-                self.data[base + j - 1].clone()
-            });
-            path.push(j & 1 == 0);
+        let size = tree_size(leafs).expect("tree is too large");
+        let mut data = hashes;
+        data.reserve_exact(size - leafs);
 
-            base += width;
-            width >>= 1;
-            if width & 1 == 1 {
-                width += 1;
+        let mut mt: MerkleTree<T, A> = MerkleTree {
+            data,
+            leafs,
+            height: log2_pow2(size + 1),
+            padding: Padding::Duplicate,
+            order: NodeOrder::Positional,
+            raw: false,
+            pruned: 0,
+            index: None,
+            _a: PhantomData,
+        };
+        mt.rebuild(&[], 0, A::default(), |a: &mut A| a.reset());
+        mt
+    }
+
+    /// Returns the root of the subtree of [`MerkleTree::new`] over `data`
+    /// which spans the leafs `chunk`, so that the chunks of the tree can be
+    /// hashed apart, e.g. by distributed workers, and put together with
+    /// [`MerkleTree::combine_partials`].
+    ///
+    /// A chunk has to be a subtree of the tree: its length is a power of
+    /// two and its start a multiple of it, or it is the last chunk, which
+    /// may be shorter.
+    ///
+    /// Panics if `chunk` is not a subtree of the tree over `data`.
+    pub fn partial_root(data: &[T], chunk: Range<usize>) -> T {
+        assert!(
+            chunk_level(&chunk, data.len()).is_some(),
+            "chunk {:?} is not a subtree of {} leafs",
+            chunk,
+            data.len()
+        );
+        Self::new(data[chunk].iter().cloned()).root()
+    }
+
+    /// Returns the root of the tree of [`MerkleTree::new`] from the roots
+    /// of the chunks of its leafs, see [`MerkleTree::partial_root`].
+    ///
+    /// Partials may come in any order. Fails with
+    /// [`MerkleError::InvalidChunks`] unless their chunks are subtrees of
+    /// the tree which tile all of its leafs exactly, and with
+    /// [`MerkleError::EmptyTree`] if there are none.
+    pub fn combine_partials(partials: &[(Range<usize>, T)]) -> Result<T, MerkleError> {
+        let mut sorted: Vec<&(Range<usize>, T)> = partials.iter().collect();
+        sorted.sort_by_key(|(chunk, _)| chunk.start);
+        let leafs = match sorted.last() {
+            Some((chunk, _)) => chunk.end,
+            None => return Err(MerkleError::EmptyTree),
+        };
+
+        // right edge of the subtrees combined so far, as (level, index, root)
+        let mut a = A::default();
+        let mut stack: Vec<(usize, usize, T)> = Vec::new();
+        let mut next = 0;
+        for (chunk, root) in sorted {
+            if chunk.start != next {
+                return Err(MerkleError::InvalidChunks);
             }
-            j >>= 1;
+            let level = chunk_level(chunk, leafs).ok_or(MerkleError::InvalidChunks)?;
+            next = chunk.end;
+
+            let (mut level, mut index, mut h) = (level, chunk.start >> level, root.clone());
+            // merge with the left siblings
+            while let Some(&(l, i, _)) = stack.last() {
+                if l != level || index & 1 == 0 || i + 1 != index {
+                    break;
+                }
+                let (_, _, left) = stack.pop().expect("checked above");
+                a.reset();
+                h = a.node(left, h);
+                level += 1;
+                index >>= 1;
+            }
+            stack.push((level, index, h));
         }
 
-        // root is final
-        lemma.push(self.root());
-        Proof::new(lemma, path)
+        // last nodes of odd levels are hashed with themselves
+        let (mut level, mut index, mut h) = stack.pop().expect("chunks are not empty");
+        while !stack.is_empty() {
+            a.reset();
+            if index & 1 == 0 {
+                h = a.node(h.clone(), h);
+            } else {
+                let (l, i, left) = stack.pop().expect("checked above");
+                if l != level || i + 1 != index {
+                    return Err(MerkleError::InvalidChunks);
+                }
+                h = a.node(left, h);
+            }
+            level += 1;
+            index >>= 1;
+        }
+        Ok(h)
     }
 
-    /// Returns merkle root
-    pub fn root(&self) -> T {
-        self.data[self.data.len() - 1].clone()
+    /// Creates new empty merkle tree.
+    ///
+    /// Root of the empty tree is the hash of the empty input, as defined by
+    /// RFC 6962 (`MTH({}) = HASH()`). The tree has no leafs and no levels,
+    /// so no proof can be generated for it.
+    pub fn empty() -> MerkleTree<T, A> {
+        Self::from_empty(
+            Vec::new(),
+            Padding::Duplicate,
+            NodeOrder::Positional,
+            false,
+            A::default(),
+        )
     }
 
-    /// Returns number of elements in the tree.
-    pub fn len(&self) -> usize {
-        self.data.len()
+    /// Creates new empty tree with the storage for the nodes of the tree of
+    /// `n` leafs, so that up to `n` leafs can be [`extend`]ed into it
+    /// without reallocation.
+    ///
+    /// Panics if the tree of `n` leafs does not fit the address space, see
+    /// [`MerkleTree::try_with_capacity`].
+    ///
+    /// [`extend`]: MerkleTree::extend
+    pub fn with_capacity(n: usize) -> MerkleTree<T, A> {
+        Self::try_with_capacity(n).expect("tree is too large")
     }
 
-    /// Returns `true` if the vector contains no elements.
-    pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+    /// Creates new empty tree with the storage for the nodes of the tree of
+    /// `n` leafs, failing with [`MerkleError::TooLarge`] if the number of
+    /// the nodes overflows `usize`, see [`MerkleTree::with_capacity`].
+    pub fn try_with_capacity(n: usize) -> Result<MerkleTree<T, A>, MerkleError> {
+        Ok(Self::from_empty(
+            Vec::with_capacity(tree_size(n)?),
+            Padding::Duplicate,
+            NodeOrder::Positional,
+            false,
+            A::default(),
+        ))
     }
 
-    /// Returns height of the tree
-    pub fn height(&self) -> usize {
-        self.height
+    /// Appends a leaf to the tree.
+    ///
+    /// Only the nodes on the right spine of the tree are rehashed, which
+    /// takes `O(log n)` hashes per append. Stored nodes are moved to the new
+    /// layout, which is a plain copy of the nodes array.
+    pub fn push(&mut self, item: T) {
+        self.extend(Some(item));
     }
 
-    /// Returns original number of elements the tree was built upon.
-    pub fn leafs(&self) -> usize {
-        self.leafs
+    /// Appends leafs to the tree.
+    ///
+    /// Interior nodes of complete subtrees are reused, so that appending `k`
+    /// leafs takes `O(k + log n)` hashes instead of rebuilding the tree.
+    ///
+    /// Panics if the tree is pruned, see [`MerkleTree::prune_to_depth`].
+    pub fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        let raw = self.raw;
+        let mut a = A::default();
+        self.append_leafs(items.into_iter().map(move |item| {
+            a.reset();
+            leaf(raw, &mut a, item)
+        }));
+    }
+
+    /// Returns the tree of the leafs of `self` followed by the leafs of
+    /// `other`, the tree [`MerkleTree::from_data`] builds over the data of
+    /// both.
+    ///
+    /// Leafs of `other` are not rehashed and the interior nodes of complete
+    /// subtrees of `self` are reused, as [`MerkleTree::extend`] does. If
+    /// `self` has a power of two leafs, its whole tree is the left subtree
+    /// of the result, and only the nodes over the leafs of `other` are
+    /// hashed.
+    ///
+    /// Panics if the trees are built by different rules, see
+    /// [`MerkleTree::scheme`], or are pruned.
+    pub fn merge(mut self, mut other: Self) -> Self {
+        assert_eq!(
+            self.scheme(),
+            other.scheme(),
+            "trees are built by different rules"
+        );
+        assert!(other.pruned == 0, "tree is pruned");
+
+        other.data.truncate(other.leafs);
+        self.append_leafs(other.data.into_iter());
+        self
+    }
+
+    /// Appends leaf nodes, rehashing only the interior nodes which are not
+    /// the ones of complete subtrees of the tree.
+    fn append_leafs<I: Iterator<Item = T>>(&mut self, iter: I) {
+        assert!(self.pruned == 0, "tree is pruned");
+
+        self.index = None;
+
+        let mut iter = iter.peekable();
+        if iter.peek().is_none() {
+            return;
+        }
+
+        let complete = self.leafs;
+        let (old, mut data) = if complete == 0 {
+            // nothing to reuse, nodes go to the storage of the empty tree,
+            // e.g. the one of `with_capacity`
+            let mut data = mem::take(&mut self.data);
+            data.clear();
+            data.reserve(iter.size_hint().0);
+            (Vec::new(), data)
+        } else {
+            let old = mem::take(&mut self.data);
+            let mut data: Vec<T> = Vec::with_capacity(complete + iter.size_hint().0);
+            data.extend_from_slice(&old[..complete]);
+            (old, data)
+        };
+
+        data.extend(iter);
+
+        let leafs = data.len();
+        let size = tree_size(leafs).expect("tree is too large");
+        data.reserve_exact(size - leafs);
+
+        self.data = data;
+        self.leafs = leafs;
+        self.height = log2_pow2(size + 1);
+        self.rebuild(&old, complete, A::default(), |a: &mut A| a.reset());
+    }
+
+    /// Verifies that `proof` proves `leaf` at position `index` of the tree
+    /// of `root`.
+    ///
+    /// Unlike [`Proof::validate`], which only checks that the lemma folds
+    /// to its root, the path bits are also required to be the ones of
+    /// `index`, so a proof can not be replayed for another position. `leaf`
+    /// is the leaf node as stored in the tree, see [`Proof::item`]. Proofs
+    /// recording another [`Proof::index`] are rejected too.
+    ///
+    /// [`PaddingPolicy::PromoteOdd`] proofs have no path bits for the levels
+    /// the node is promoted through, so the promoted ones do not verify
+    /// here, see [`MerkleTree::verify_proof_with_policy`].
+    pub fn verify_proof(root: &T, proof: &Proof<T>, leaf: &T, index: usize) -> bool {
+        proof.item() == *leaf
+            && proof.root() == *root
+            && proof.index().is_none_or(|i| i == index)
+            && path_matches_index(proof.path(), index, None)
+            && proof.validate::<A>()
+    }
+
+    /// Verifies that `proof` proves `leaf` at position `index` of the tree
+    /// of `leafs` leafs built by `policy` and of `root`, see
+    /// [`MerkleTree::verify_proof`].
+    ///
+    /// The levels the node of a [`PaddingPolicy::PromoteOdd`] tree is
+    /// promoted through are the ones of `index` and `leafs`, the path bits
+    /// are required for all the other levels.
+    pub fn verify_proof_with_policy(
+        root: &T,
+        proof: &Proof<T>,
+        leaf: &T,
+        index: usize,
+        leafs: usize,
+        policy: PaddingPolicy,
+    ) -> bool {
+        let promote_leafs = match policy {
+            PaddingPolicy::PromoteOdd => Some(leafs),
+            _ => None,
+        };
+        proof.item() == *leaf
+            && proof.root() == *root
+            && proof.index().is_none_or(|i| i == index)
+            && index < leafs
+            && path_matches_index(proof.path(), index, promote_leafs)
+            && proof.validate::<A>()
+    }
+
+    /// Verifies a batch of `(index, leaf, proof)` proofs claiming the same
+    /// `expected_root`, see [`MerkleTree::verify_proof`].
+    ///
+    /// Returns `Err(i)` with the position in `proofs` of the first proof
+    /// which fails or proves another root.
+    pub fn verify_batch(expected_root: &T, proofs: &[(usize, T, Proof<T>)]) -> Result<(), usize> {
+        match proofs.iter().position(|(index, leaf, proof)| {
+            !Self::verify_proof(expected_root, proof, leaf, *index)
+        }) {
+            Some(i) => Err(i),
+            None => Ok(()),
+        }
     }
 
     /// Extracts a slice containing the entire vector.
     ///
+    /// Nodes are laid out level by level: the `leafs()` leafs first, then
+    /// each level up, the root last. Odd levels are followed by their padding
+    /// node, see [`PaddingPolicy`]:
+    ///
+    /// ```text
+    ///     [h1 h2 h3 h3 h12 h33 root]
+    /// ```
+    ///
+    /// Nodes of pruned trees start at the first kept level, see
+    /// [`MerkleTree::prune_to_depth`].
+    ///
     /// Equivalent to `&s[..]`.
     pub fn as_slice(&self) -> &[T] {
         self
     }
-}
 
-impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> FromIterator<T> for MerkleTree<T, A> {
-    /// Creates new merkle tree from an iterator over hashable objects.
-    fn from_iter<I: IntoIterator<Item = T>>(into: I) -> Self {
-        let iter = into.into_iter();
-        let mut data: Vec<T> = match iter.size_hint().1 {
-            Some(e) => {
-                let pow = next_pow2(e);
-                let size = 2 * pow - 1;
-                Vec::with_capacity(size)
+    /// Returns merkle root without cloning it, see [`MerkleTree::root`].
+    ///
+    /// Every tree has a root, the empty one the hash of the empty input, so
+    /// this never panics.
+    pub fn root_ref(&self) -> &T {
+        &self.data[self.data.len() - 1]
+    }
+
+    /// Returns leaf `index` as stored in the tree, see [`Proof::item`].
+    ///
+    /// Returns `None` if the index is out of range or the leafs are pruned.
+    pub fn get_leaf(&self, index: usize) -> Option<&T> {
+        self.get_node(0, index)
+    }
+
+    /// Returns the leaf level of the tree, the leaf hashes as stored and
+    /// proven, see [`Proof::item`], not the data the tree is built from.
+    ///
+    /// Panics if the leafs are pruned, see [`MerkleTree::prune_to_depth`].
+    pub fn leaves_slice(&self) -> &[T] {
+        assert!(self.pruned == 0, "tree is pruned");
+        &self.data[..self.leafs]
+    }
+
+    /// Consumes the tree returning its leaf level, see
+    /// [`MerkleTree::leaves_slice`].
+    ///
+    /// The leafs are leaf hashes, so the tree is rebuilt from them with
+    /// [`MerkleTree::from_leaf_hashes`], not [`MerkleTree::new`].
+    ///
+    /// Panics if the leafs are pruned, see [`MerkleTree::prune_to_depth`].
+    pub fn into_leaves(self) -> Vec<T> {
+        assert!(self.pruned == 0, "tree is pruned");
+        let mut data = self.data;
+        data.truncate(self.leafs);
+        data
+    }
+
+    /// Returns node `index` of `level`, the coordinates of
+    /// [`MerkleTree::subtree_root`]: level 0 is the leafs, level
+    /// `height() - 1` the root, and nodes are indexed from the left within
+    /// the level.
+    ///
+    /// Returns `None` if there is no such node, or it is pruned.
+    pub fn get_node(&self, level: usize, index: usize) -> Option<&T> {
+        self.position(level, index).map(|i| &self.data[i])
+    }
+
+    /// Returns an iterator over `(index, leaf, proof)` of every leaf of the
+    /// tree, in the order of the leafs.
+    ///
+    /// Proofs are generated lazily as the iterator advances, so only one of
+    /// them is held at a time.
+    ///
+    /// Panics if the tree is pruned, see [`MerkleTree::prune_to_depth`].
+    pub fn leaf_proofs(&self) -> impl Iterator<Item = (usize, &T, Proof<T>)> {
+        assert!(self.pruned == 0, "tree is pruned");
+        self.data[..self.leafs]
+            .iter()
+            .enumerate()
+            .map(move |(i, leaf)| (i, leaf, self.gen_proof(i)))
+    }
+
+    /// Returns an iterator over the levels of the tree, from the leafs up
+    /// to the root, each level as a slice of its stored nodes including the
+    /// padding node, see [`MerkleTree::as_slice`].
+    ///
+    /// Empty tree has no levels, pruned tree only the kept ones.
+    pub fn levels(&self) -> impl Iterator<Item = &[T]> {
+        let (_, mut width) = self.level(self.pruned);
+        let mut base = 0;
+        iter::from_fn(move || {
+            if width == 0 || base == self.data.len() {
+                return None;
             }
-            None => Vec::new(),
-        };
 
-        // leafs
+            let stored = self.stored_width(width);
+            let level = &self.data[base..base + stored];
+            base += stored;
+            width = (width + 1) >> 1;
+            Some(level)
+        })
+    }
+
+    /// Drops all the levels but the root and the `depth` levels below it,
+    /// turning the tree into a verification stub of the same root.
+    ///
+    /// Proofs of the pruned leafs fail with [`MerkleError::Pruned`], nodes
+    /// of the kept levels are still available with
+    /// [`MerkleTree::subtree_root`]. The tree can not be appended to
+    /// anymore. The leaf index is dropped.
+    pub fn prune_to_depth(&mut self, depth: usize) {
+        let keep = self.height.min(depth + 1);
+        let pruned = self.height - keep;
+        if pruned <= self.pruned {
+            return;
+        }
+
+        let (from, _) = self.level(self.pruned);
+        let (to, _) = self.level(pruned);
+        self.data.drain(..to - from);
+        self.data.shrink_to_fit();
+        self.pruned = pruned;
+        self.index = None;
+    }
+
+    /// Discards the tree keeping only its root.
+    pub fn into_root_only(self) -> T {
+        self.root()
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> MerkleTree<T, A, S> {
+    /// Creates new merkle tree from a list of hashable objects storing its
+    /// nodes into the empty `store`, e.g. a [`DiskStore`] for trees larger
+    /// than memory.
+    ///
+    /// [`DiskStore`]: crate::store::DiskStore
+    pub fn from_data_with_store<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        store: S,
+    ) -> MerkleTree<T, A, S> {
+        assert!(store.is_empty());
+
         let mut a = A::default();
+        Self::from_leafs(
+            store,
+            data.into_iter().map(|x| {
+                a.reset();
+                x.hash_with_context(&mut a, HashContext::Leaf);
+                a.hash()
+            }),
+            Padding::Duplicate,
+            NodeOrder::Positional,
+            false,
+            A::default(),
+            |a: &mut A| a.reset(),
+        )
+    }
+
+    fn from_empty(
+        mut data: S,
+        padding: Padding<T>,
+        order: NodeOrder,
+        raw: bool,
+        mut a: A,
+    ) -> MerkleTree<T, A, S> {
+        data.push(a.hash());
+        MerkleTree {
+            data,
+            leafs: 0,
+            height: 0,
+            padding,
+            order,
+            raw,
+            pruned: 0,
+            index: None,
+            _a: PhantomData,
+        }
+    }
+
+    /// Builds the tree hashing with `a`, which `reset` brings back to the
+    /// initial state before every hash. `raw` trees hash leafs and nodes
+    /// without domain separation.
+    fn from_leafs<I: IntoIterator<Item = T>, R: Fn(&mut A)>(
+        mut data: S,
+        into: I,
+        padding: Padding<T>,
+        order: NodeOrder,
+        raw: bool,
+        mut a: A,
+        reset: R,
+    ) -> MerkleTree<T, A, S> {
+        let iter = into.into_iter();
+        // upper bound of too large a tree is no hint
+        let (lower, upper) = iter.size_hint();
+        data.reserve(upper.and_then(|e| tree_size(e).ok()).unwrap_or(lower));
+
+        // leafs are hashed right into the tree storage as they are consumed
         for item in iter {
-            a.reset();
-            data.push(a.leaf(item));
+            reset(&mut a);
+            data.push(leaf(raw, &mut a, item));
         }
 
         let leafs = data.len();
-        let pow = next_pow2(leafs);
-        let size = 2 * pow - 1;
+        if leafs == 0 {
+            reset(&mut a);
+            return Self::from_empty(data, padding, order, raw, a);
+        }
 
-        assert!(leafs > 1);
+        let size = tree_size(leafs).expect("tree is too large");
 
-        let mut mt: MerkleTree<T, A> = MerkleTree {
+        // interior nodes, a no-op if the size hint was exact
+        data.reserve(size - leafs);
+
+        let mut mt: MerkleTree<T, A, S> = MerkleTree {
             data,
             leafs,
             height: log2_pow2(size + 1),
+            padding,
+            order,
+            raw,
+            pruned: 0,
+            index: None,
             _a: PhantomData,
         };
 
-        mt.build();
+        mt.rebuild(&[], 0, a, reset);
         mt
     }
-}
-
-impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> ops::Deref for MerkleTree<T, A> {
-    type Target = [T];
 
-    fn deref(&self) -> &[T] {
-        self.data.deref()
+    /// Builds interior nodes on top of the leafs reusing the nodes of the
+    /// `old` tree of `complete` leafs. Only the nodes whose subtrees were
+    /// not complete in the old tree are hashed.
+    fn rebuild<R: Fn(&mut A)>(&mut self, old: &[T], complete: usize, a: A, reset: R) {
+        let _ = self.build_levels(old, complete, a, reset, |_| ControlFlow::Continue(()));
     }
-}
 
-/// `next_pow2` returns next highest power of two from a given number if
-/// it is not already a power of two.
-///
-/// [](http://locklessinc.com/articles/next_pow2/)
-/// [](https://stackoverflow.com/questions/466204/rounding-up-to-next-power-of-2/466242#466242)
-pub fn next_pow2(mut n: usize) -> usize {
-    n -= 1;
-    n |= n >> 1;
-    n |= n >> 2;
-    n |= n >> 4;
-    n |= n >> 8;
-    n |= n >> 16;
-    #[cfg(target_pointer_width = "64")]
+    /// Builds interior nodes as [`MerkleTree::rebuild`] does, reporting the
+    /// number of the levels completed to `progress`, the leaf level first,
+    /// and stopping as soon as it breaks.
+    fn build_levels<R, P>(
+        &mut self,
+        old: &[T],
+        complete: usize,
+        mut a: A,
+        reset: R,
+        mut progress: P,
+    ) -> ControlFlow<()>
+    where
+        R: Fn(&mut A),
+        P: FnMut(usize) -> ControlFlow<()>,
     {
-        n |= n >> 32;
+        let mut width = self.leafs;
+        let mut levels: usize = 1;
+        if progress(levels).is_break() {
+            return ControlFlow::Break(());
+        }
+
+        // reusable nodes of the old tree
+        let mut old_base: usize = 0;
+        let mut old_width = complete;
+        let mut valid = complete;
+
+        // build tree
+        let mut i: usize = 0;
+        let mut j: usize = width;
+        while width > 1 {
+            // if there is odd num of elements, fill in to the even
+            if width & 1 == 1 {
+                match self.padding {
+                    Padding::Duplicate => {
+                        let he = self.data.get(self.len() - 1);
+                        self.data.push(he);
+                    }
+                    Padding::Zero(ref z) => {
+                        let he = z.clone();
+                        self.data.push(he);
+                    }
+                    Padding::Promote => {}
+                }
+                j = i + self.stored_width(width);
+            }
+
+            // complete subtrees of the old tree
+            old_base += self.stored_width(old_width);
+            old_width = (old_width + 1) >> 1;
+            valid >>= 1;
+            for k in 0..valid {
+                self.data.push(old[old_base + k].clone());
+                i += 2;
+            }
+
+            // next shift
+            while i + 1 < j {
+                reset(&mut a);
+                let h = node(
+                    self.order,
+                    self.raw,
+                    &mut a,
+                    self.data.get(i),
+                    self.data.get(i + 1),
+                );
+                self.data.push(h);
+                i += 2;
+            }
+
+            // promote the single left node
+            if i < j {
+                let he = self.data.get(i);
+                self.data.push(he);
+                i += 1;
+            }
+
+            width = (width + 1) >> 1;
+            j += width;
+
+            levels += 1;
+            if progress(levels).is_break() {
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Replaces leaf `index` with the leaf of hash `item`, as
+    /// [`MerkleTree::push`] hashes it, rehashing only the `O(log n)` nodes
+    /// on the path to the root.
+    ///
+    /// The tree is the one built from scratch with the leaf replaced. Nodes
+    /// are hashed with `A::default()`. The leaf index is dropped.
+    pub fn update_leaf(&mut self, index: usize, item: T) -> Result<(), MerkleError> {
+        if index >= self.leafs {
+            return Err(MerkleError::IndexOutOfBounds(index));
+        }
+        if self.pruned > 0 {
+            return Err(MerkleError::Pruned);
+        }
+        self.index = None;
+
+        let mut a = A::default();
+        let mut h = leaf(self.raw, &mut a, item);
+
+        let mut base = 0;
+        let mut width = self.leafs;
+        let mut j = index;
+        loop {
+            self.data.set(base + j, h.clone());
+            if width == 1 {
+                return Ok(());
+            }
+
+            let stored = self.stored_width(width);
+            if j + 1 == width && stored > width {
+                if let Padding::Duplicate = self.padding {
+                    self.data.set(base + width, h.clone());
+                }
+            }
+
+            // a lone last node is promoted
+            if j ^ 1 < stored {
+                let sibling = self.data.get(base + (j ^ 1));
+                let (left, right) = match j & 1 {
+                    0 => (h, sibling),
+                    _ => (sibling, h),
+                };
+                a.reset();
+                h = node(self.order, self.raw, &mut a, left, right);
+            }
+
+            base += stored;
+            width = (width + 1) >> 1;
+            j >>= 1;
+        }
+    }
+
+    /// Returns number of nodes stored for the level of `width` nodes.
+    fn stored_width(&self, width: usize) -> usize {
+        layout::stored_len(width, self.padding != Padding::Promote)
+    }
+
+    /// Returns offset of the first node of `level` in the unpruned tree and
+    /// the width of the level.
+    fn level(&self, level: usize) -> (usize, usize) {
+        let padded = self.padding != Padding::Promote;
+        (
+            layout::level_offset(self.leafs, level, padded),
+            layout::level_len(self.leafs, level),
+        )
+    }
+
+    /// Returns `true` if the tree is pruned, see
+    /// [`MerkleTree::prune_to_depth`].
+    pub fn is_pruned(&self) -> bool {
+        self.pruned > 0
+    }
+
+    /// Returns node order of the tree.
+    pub fn order(&self) -> NodeOrder {
+        self.order
+    }
+
+    /// Returns `true` if leafs and nodes are hashed with the [`Algorithm`]
+    /// domain separation, see [`MerkleTreeBuilder::domain_separation`].
+    pub fn domain_separation(&self) -> bool {
+        !self.raw
+    }
+
+    /// Returns padding policy of the tree.
+    pub fn policy(&self) -> PaddingPolicy {
+        match self.padding {
+            Padding::Duplicate => PaddingPolicy::DuplicateLast,
+            Padding::Promote => PaddingPolicy::PromoteOdd,
+            Padding::Zero(_) => PaddingPolicy::HashWithZero,
+        }
+    }
+
+    /// Returns the construction rules the root of the tree is computed by,
+    /// see [`SchemeId`].
+    pub fn scheme(&self) -> SchemeId {
+        SchemeId::new(self.policy(), self.order, !self.raw)
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`, failing cleanly on
+    /// an empty tree or an out of range index.
+    pub fn try_gen_proof(&self, i: usize) -> Result<Proof<T>, MerkleError> {
+        if self.leafs == 0 {
+            return Err(MerkleError::EmptyTree);
+        }
+        if i >= self.leafs {
+            return Err(MerkleError::IndexOutOfBounds(i));
+        }
+        if self.pruned > 0 {
+            return Err(MerkleError::Pruned);
+        }
+
+        let mut lemma: Vec<T> = Vec::with_capacity(self.height + 1); // path + root
+        let mut path: Vec<bool> = Vec::with_capacity(self.height - 1); // path - 1
+
+        let mut base = 0;
+        let mut j = i;
+
+        // level 1 width
+        let mut width = self.leafs;
+
+        lemma.push(self.data.get(j));
+        while width > 1 {
+            let stored = self.stored_width(width);
+
+            // promoted node has no sibling
+            if j ^ 1 < stored {
+                lemma.push(self.data.get(base + (j ^ 1)));
+                path.push(j & 1 == 0);
+            }
+
+            base += stored;
+            width = (width + 1) >> 1;
+            j >>= 1;
+        }
+
+        // root is final
+        lemma.push(self.root());
+        let proof = match self.order {
+            NodeOrder::Positional => Proof::new(lemma, path).with_index(i),
+            NodeOrder::Sorted => Proof::new_sorted(lemma).with_index(i),
+        };
+        match self.policy() {
+            PaddingPolicy::PromoteOdd => Ok(proof.with_promote_odd(self.leafs)),
+            _ => Ok(proof),
+        }
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    ///
+    /// Panics if the tree is empty or `i` is out of range, see
+    /// [`MerkleTree::try_gen_proof`].
+    pub fn gen_proof(&self, i: usize) -> Proof<T> {
+        self.try_gen_proof(i).expect("invalid leaf index")
+    }
+
+    /// Returns the authentication path of leaf `index`, the siblings from
+    /// the leaf level up along with their sides.
+    ///
+    /// It is the lemma of [`MerkleTree::gen_proof`] without the item and the
+    /// root, with the path bits spelled out. Sides are the positions in the
+    /// tree, so children of [`NodeOrder::Sorted`] trees may be hashed the
+    /// other way around. Promoted levels have no element.
+    ///
+    /// Panics if the tree is empty, pruned or `index` is out of range.
+    pub fn witness(&self, index: usize) -> Vec<PathElement<T>> {
+        assert!(index < self.leafs); // index in [0 .. self.leafs)
+        assert!(self.pruned == 0, "tree is pruned");
+
+        let mut witness = Vec::with_capacity(self.height - 1);
+        let mut base = 0;
+        let mut j = index;
+        let mut width = self.leafs;
+        while width > 1 {
+            let stored = self.stored_width(width);
+
+            // promoted node has no sibling
+            if j ^ 1 < stored {
+                witness.push(PathElement {
+                    sibling: self.data.get(base + (j ^ 1)),
+                    side: if j & 1 == 0 { Side::Right } else { Side::Left },
+                });
+            }
+
+            base += stored;
+            width = (width + 1) >> 1;
+            j >>= 1;
+        }
+        witness
+    }
+
+    /// Returns number of hashes in the lemma of the proof for leaf `index`,
+    /// i.e. the sibling hashes plus the item and the root, without
+    /// generating it.
+    ///
+    /// It is `height + 1` unless the tree is [`PaddingPolicy::PromoteOdd`],
+    /// where promoted levels carry no sibling.
+    pub fn proof_len(&self, index: usize) -> usize {
+        assert!(index < self.leafs); // index in [0 .. self.leafs)
+
+        let mut len = 2;
+        let mut j = index;
+        let mut width = self.leafs;
+        while width > 1 {
+            if j ^ 1 < self.stored_width(width) {
+                len += 1;
+            }
+            width = (width + 1) >> 1;
+            j >>= 1;
+        }
+        len
+    }
+
+    /// Returns node `index` of `level`, the root of the subtree it spans.
+    ///
+    /// Levels are counted from the leafs up, level 0 is the leafs and level
+    /// `height() - 1` is the root. Nodes are indexed from the left within
+    /// the level, node `index` of `level` spans leafs
+    /// `[index << level .. (index + 1) << level)`. Padding nodes are not
+    /// addressable.
+    ///
+    /// Returns `None` if there is no such node.
+    pub fn subtree_root(&self, level: usize, index: usize) -> Option<T> {
+        self.position(level, index).map(|i| self.data.get(i))
+    }
+
+    /// Returns the indices of the leafs which differ from the ones of
+    /// `other`, in ascending order.
+    ///
+    /// Trees are compared from the root down, descending only into the
+    /// subtrees whose roots differ, so mostly identical trees are compared
+    /// in `O(d log n)` for `d` differing leafs. Fails if the trees have
+    /// different number of leafs, or are pruned.
+    pub fn diff(&self, other: &Self) -> Result<Vec<usize>, MerkleError> {
+        if self.leafs != other.leafs {
+            return Err(MerkleError::LeafsMismatch(self.leafs, other.leafs));
+        }
+        if self.pruned > 0 || other.pruned > 0 {
+            return Err(MerkleError::Pruned);
+        }
+
+        let mut changed = Vec::new();
+        let mut stack = Vec::new();
+        if self.height > 0 {
+            stack.push((self.height - 1, 0));
+        }
+        while let Some((level, index)) = stack.pop() {
+            if self.subtree_root(level, index) == other.subtree_root(level, index) {
+                continue;
+            }
+            if level == 0 {
+                changed.push(index);
+            } else {
+                // right first, so that the left is popped first
+                stack.push((level - 1, 2 * index + 1));
+                stack.push((level - 1, 2 * index));
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Packs the stored nodes into one buffer, e.g. to persist the tree.
+    ///
+    /// Layout, the nodes as they are stored, see [`MerkleTree`]:
+    ///
+    /// ```text
+    /// leafs | padding | level 1 | ... | root, every node hash size bytes
+    /// ```
+    ///
+    /// Hashes are stored as is, one after another. Neither the number of
+    /// the leafs nor the rules the tree is built by are encoded, the tree is
+    /// loaded with [`MerkleTree::from_packed_bytes`].
+    ///
+    /// Panics if the tree is pruned, see [`MerkleTree::prune_to_depth`].
+    pub fn to_packed_bytes(&self) -> Vec<u8> {
+        assert!(self.pruned == 0, "tree is pruned");
+
+        let size = if !self.data.is_empty() {
+            self.data.get(0).as_ref().len()
+        } else {
+            0
+        };
+        let mut bytes = Vec::with_capacity(self.data.len() * size);
+        for i in 0..self.data.len() {
+            bytes.extend_from_slice(self.data.get(i).as_ref());
+        }
+        bytes
+    }
+
+    /// Returns `true` if the stored interior nodes are the ones the stored
+    /// leafs hash to, e.g. to catch the corruption of a tree loaded from
+    /// disk.
+    ///
+    /// Every interior node is recomputed with `A::default()`, so the trees
+    /// built with a keyed [`MerkleTreeBuilder::hasher`] do not verify.
+    ///
+    /// Panics if the tree is pruned, see [`MerkleTree::prune_to_depth`].
+    pub fn verify_self(&self) -> bool {
+        assert!(self.pruned == 0, "tree is pruned");
+
+        match self.recomputed() {
+            Ok(expected) => {
+                expected.height == self.height
+                    && expected.data.len() == self.data.len()
+                    && self.first_mismatch_of(&expected).is_none()
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the `(level, index)` of the first stored node, from the
+    /// leafs up, which is not the one the stored nodes below hash to, or
+    /// `None` if every node is, see [`MerkleTree::verify_self`].
+    ///
+    /// Coordinates are the ones of [`MerkleTree::subtree_root`], the
+    /// padding node of a level is the one past its last node. A node which
+    /// is missing from the store is the first mismatch, more nodes stored
+    /// than the tree has are not one, [`MerkleTree::verify_self`] tells
+    /// them.
+    ///
+    /// Panics if the tree is pruned, see [`MerkleTree::prune_to_depth`].
+    pub fn first_mismatch(&self) -> Option<(usize, usize)> {
+        assert!(self.pruned == 0, "tree is pruned");
+
+        match self.recomputed() {
+            Ok(expected) => self.first_mismatch_of(&expected),
+            Err(missing) => Some(missing),
+        }
+    }
+
+    /// Returns the tree the stored leafs build by the rules of this one, or
+    /// the coordinate of the first node missing for that.
+    fn recomputed(&self) -> Result<MerkleTree<T, A>, (usize, usize)> {
+        if self.leafs > self.data.len() {
+            return Err((0, self.data.len()));
+        }
+        let data: Vec<T> = (0..self.leafs).map(|i| self.data.get(i)).collect();
+        if self.leafs == 0 {
+            return Ok(MerkleTree::from_empty(
+                data,
+                self.padding.clone(),
+                self.order,
+                self.raw,
+                A::default(),
+            ));
+        }
+
+        // the nodes of so large a tree can not be stored
+        let size = tree_size(self.leafs).map_err(|_| (1, 0))?;
+        let mut mt = MerkleTree {
+            data,
+            leafs: self.leafs,
+            height: log2_pow2(size + 1),
+            padding: self.padding.clone(),
+            order: self.order,
+            raw: self.raw,
+            pruned: 0,
+            index: None,
+            _a: PhantomData,
+        };
+        mt.rebuild(&[], 0, A::default(), |a: &mut A| a.reset());
+        Ok(mt)
+    }
+
+    /// Returns the coordinate of the first node stored unlike in the
+    /// `expected` tree.
+    fn first_mismatch_of(&self, expected: &MerkleTree<T, A>) -> Option<(usize, usize)> {
+        for level in 0..expected.height {
+            let (base, width) = expected.level(level);
+            let stored = if level + 1 == expected.height {
+                1
+            } else {
+                expected.stored_width(width)
+            };
+            let mismatch = (0..stored).find(|&k| {
+                base + k >= self.data.len() || self.data.get(base + k) != expected.data[base + k]
+            });
+            if let Some(k) = mismatch {
+                return Some((level, k));
+            }
+        }
+        None
+    }
+
+    /// Returns the range of the leafs node `index` of `level` spans, in the
+    /// coordinates of [`MerkleTree::subtree_root`].
+    ///
+    /// The last node of a level may span past the last leaf, over the
+    /// padding, the range is clamped to the leafs of the tree.
+    ///
+    /// Panics if there is no such node.
+    pub fn leaf_range(&self, level: usize, index: usize) -> Range<usize> {
+        assert!(
+            level < self.height && index < self.level(level).1,
+            "no node {} at level {}",
+            index,
+            level
+        );
+        let start = index << level;
+        start..((index + 1) << level).min(self.leafs)
+    }
+
+    /// Returns the subtree of node `index` of `level` as a tree of its own,
+    /// in the coordinates of [`MerkleTree::subtree_root`], e.g. to shard the
+    /// tree.
+    ///
+    /// The subtree has the leafs of [`MerkleTree::leaf_range`] and the root
+    /// of the node, so proofs of its leafs validate against the node. Nodes
+    /// are copied, not rehashed.
+    ///
+    /// Returns `None` if there is no such node, the tree is pruned, or the
+    /// node is padded over more than half of its span, i.e. it is not the
+    /// root its leafs build. [`PaddingPolicy::PromoteOdd`] trees give the
+    /// subtree of the promoted node then.
+    pub fn subtree(&self, level: usize, index: usize) -> Option<MerkleTree<T, A>> {
+        if self.pruned > 0 || level >= self.height || index >= self.level(level).1 {
+            return None;
+        }
+        let range = self.leaf_range(level, index);
+        let leafs = range.len();
+        if level > 0 && leafs <= 1 << (level - 1) {
+            return match self.padding {
+                Padding::Promote => self.subtree(level - 1, index << 1),
+                _ => None,
+            };
+        }
+
+        let mut mt: MerkleTree<T, A> = MerkleTree {
+            data: Vec::new(),
+            leafs,
+            height: level + 1,
+            padding: self.padding.clone(),
+            order: self.order,
+            raw: self.raw,
+            pruned: 0,
+            index: None,
+            _a: PhantomData,
+        };
+        for k in 0..=level {
+            let base = self.level(k).0 + (index << (level - k));
+            let stored = if k == level {
+                1
+            } else {
+                mt.stored_width(mt.level(k).1)
+            };
+            mt.data
+                .extend((base..base + stored).map(|i| self.data.get(i)));
+        }
+        Some(mt)
+    }
+
+    /// Returns storage position of node `index` of `level`, see
+    /// [`MerkleTree::subtree_root`].
+    fn position(&self, level: usize, index: usize) -> Option<usize> {
+        if level >= self.height || level < self.pruned {
+            return None;
+        }
+
+        let (base, width) = self.level(level);
+        let (first, _) = self.level(self.pruned);
+        if index < width {
+            Some(base - first + index)
+        } else {
+            None
+        }
+    }
+
+    /// Builds the leaf index of the tree, so that [`MerkleTree::index_of`]
+    /// and [`MerkleTree::gen_proof_for`] look leafs up in `O(log n)`.
+    ///
+    /// The index is dropped when the tree is appended to.
+    pub fn build_index(&mut self)
+    where
+        T: Ord,
+    {
+        let mut index = BTreeMap::new();
+        let leafs = if self.pruned > 0 { 0 } else { self.leafs };
+        for i in 0..leafs {
+            // the first of the duplicates wins
+            index.entry(self.data.get(i)).or_insert(i);
+        }
+        self.index = Some(index);
+    }
+
+    /// Returns position of the first `leaf`, the leaf node as stored in the
+    /// tree, see [`Proof::item`].
+    ///
+    /// Leafs are scanned if the index is not built, see
+    /// [`MerkleTree::build_index`].
+    pub fn index_of(&self, leaf: &T) -> Option<usize>
+    where
+        T: Ord,
+    {
+        match self.index {
+            Some(ref index) => index.get(leaf).copied(),
+            None if self.pruned > 0 => None,
+            None => (0..self.leafs).find(|&i| self.data.get(i) == *leaf),
+        }
+    }
+
+    /// Generate merkle tree inclusion proof for the first `leaf`, building
+    /// the leaf index if it is not built yet, see [`MerkleTree::index_of`].
+    pub fn gen_proof_for(&mut self, leaf: &T) -> Option<Proof<T>>
+    where
+        T: Ord,
+    {
+        if self.index.is_none() {
+            self.build_index();
+        }
+        let i = self.index_of(leaf)?;
+        Some(self.gen_proof(i))
+    }
+
+    /// Generate merkle tree batched inclusion proof for leafs `indices`.
+    ///
+    /// Indices are sorted and deduplicated, sibling hashes shared by several
+    /// paths are included only once.
+    ///
+    /// Multi proofs fold the nodes in their positions, so the trees of
+    /// [`NodeOrder::Sorted`] are rejected with
+    /// [`MerkleError::UnsupportedOrder`].
+    pub fn gen_multi_proof(&self, indices: &[usize]) -> Result<MultiProof<T>, MerkleError> {
+        if self.order != NodeOrder::Positional {
+            return Err(MerkleError::UnsupportedOrder);
+        }
+
+        let mut layer = indices.to_vec();
+        layer.sort_unstable();
+        layer.dedup();
+
+        if let Some(&i) = layer.last() {
+            if i >= self.leafs {
+                return Err(MerkleError::IndexOutOfBounds(i));
+            }
+            if self.pruned > 0 {
+                return Err(MerkleError::Pruned);
+            }
+        }
+
+        let proven = layer.clone();
+        let mut nodes: Vec<Option<T>> = vec![];
+        if layer.is_empty() {
+            return Ok(MultiProof::new(proven, nodes, self.height));
+        }
+
+        let mut base = 0;
+
+        // level 1 width
+        let mut width = self.leafs;
+
+        while width > 1 {
+            let stored = self.stored_width(width);
+            let mut next = Vec::with_capacity(layer.len());
+            let mut k = 0;
+            while k < layer.len() {
+                let j = layer[k];
+                if j & 1 == 0 && k + 1 < layer.len() && layer[k + 1] == j + 1 {
+                    // sibling is proven itself
+                    k += 1;
+                } else if j ^ 1 < stored {
+                    nodes.push(Some(self.data.get(base + (j ^ 1))));
+                } else {
+                    // promoted node has no sibling
+                    nodes.push(None);
+                }
+                next.push(j >> 1);
+                k += 1;
+            }
+            layer = next;
+
+            base += stored;
+            width = (width + 1) >> 1;
+        }
+
+        Ok(MultiProof::new(proven, nodes, self.height))
+    }
+
+    /// Generate merkle tree inclusion proof for the contiguous leafs
+    /// `range`, which carries only the boundary siblings of the range.
+    ///
+    /// Panics if the range is out of the leafs bounds, the tree is pruned or
+    /// is of [`NodeOrder::Sorted`], range proofs fold the nodes in their
+    /// positions.
+    pub fn gen_range_proof(&self, range: Range<usize>) -> RangeProof<T> {
+        assert!(range.start <= range.end && range.end <= self.leafs); // range in [0 .. self.leafs]
+        assert!(self.pruned == 0, "tree is pruned");
+        assert!(
+            self.order == NodeOrder::Positional,
+            "range proofs need positional tree"
+        );
+
+        let mut nodes: Vec<Option<T>> = vec![];
+        if range.is_empty() {
+            return RangeProof::new(range, nodes, self.height);
+        }
+
+        let mut base = 0;
+        let (mut lo, mut hi) = (range.start, range.end);
+
+        // level 1 width
+        let mut width = self.leafs;
+
+        while width > 1 {
+            let stored = self.stored_width(width);
+            if lo & 1 == 1 {
+                nodes.push(Some(self.data.get(base + lo - 1)));
+            }
+            if hi & 1 == 1 {
+                if hi < stored {
+                    nodes.push(Some(self.data.get(base + hi)));
+                } else {
+                    // promoted node has no sibling
+                    nodes.push(None);
+                }
+            }
+
+            base += stored;
+            width = (width + 1) >> 1;
+            lo >>= 1;
+            hi = (hi + 1) >> 1;
+        }
+
+        RangeProof::new(range, nodes, self.height)
+    }
+
+    /// Returns the root of the tree of the first `k` leafs, the root this
+    /// tree had before the rest of the leafs were appended.
+    ///
+    /// Complete subtrees of the prefix are shared with this tree, so only
+    /// the `O(log n)` nodes on the right edge of the prefix are hashed, with
+    /// `A::default()`.
+    ///
+    /// Returns `None` if `k` is not in `1 ..= leafs()` or the tree is
+    /// pruned.
+    pub fn root_at_size(&self, k: usize) -> Option<T> {
+        if k == 0 || k > self.leafs || self.pruned > 0 {
+            return None;
+        }
+
+        let mut a = A::default();
+        let mut last = self.data.get(k - 1);
+        let mut level = 0;
+        let mut width = k;
+        while width > 1 {
+            let i = width - 1;
+            if i & 1 == 1 {
+                // left sibling is a complete subtree
+                let left = self.data.get(self.position(level, i - 1)?);
+                a.reset();
+                last = node(self.order, self.raw, &mut a, left, last);
+            } else {
+                match self.padding {
+                    Padding::Duplicate => {
+                        a.reset();
+                        last = node(self.order, self.raw, &mut a, last.clone(), last);
+                    }
+                    Padding::Zero(ref z) => {
+                        a.reset();
+                        last = node(self.order, self.raw, &mut a, last, z.clone());
+                    }
+                    Padding::Promote => {}
+                }
+            }
+            level += 1;
+            width = (width + 1) >> 1;
+        }
+        Some(last)
+    }
+
+    /// Generate RFC 6962 consistency proof of the tree of the first
+    /// `old_size` leafs and this tree.
+    ///
+    /// RFC 6962 trees are the positional trees which promote odd nodes, see
+    /// [`PaddingPolicy::PromoteOdd`]. Appending leafs to such a tree with
+    /// [`MerkleTree::extend`], an append-only log, keeps its older roots
+    /// provably consistent with the newer ones.
+    ///
+    /// Panics if `old_size` is not in `1 ..= leafs()`, the tree is pruned or
+    /// is not an RFC 6962 tree.
+    pub fn gen_consistency_proof(&self, old_size: usize) -> ConsistencyProof<T> {
+        assert!(0 < old_size && old_size <= self.leafs); // old_size in [1 .. self.leafs]
+        assert!(self.pruned == 0, "tree is pruned");
+        assert!(
+            self.padding == Padding::Promote && self.order == NodeOrder::Positional,
+            "consistency proofs need RFC 6962 tree"
+        );
+
+        let mut nodes = Vec::new();
+        self.subproof(old_size, 0, self.leafs, true, &mut nodes);
+        ConsistencyProof::new(old_size, self.leafs, nodes)
+    }
+
+    /// Pushes the nodes of RFC 6962 `SUBPROOF(m, D[start:end], b)`.
+    fn subproof(&self, m: usize, start: usize, end: usize, b: bool, nodes: &mut Vec<T>) {
+        let n = end - start;
+        if m == n {
+            if !b {
+                nodes.push(self.span_root(start, end));
+            }
+            return;
+        }
+
+        // largest power of two smaller than n
+        let k = next_pow2(n) >> 1;
+        if m <= k {
+            self.subproof(m, start, start + k, b, nodes);
+            nodes.push(self.span_root(start + k, end));
+        } else {
+            self.subproof(m - k, start + k, end, false, nodes);
+            nodes.push(self.span_root(start, start + k));
+        }
+    }
+
+    /// Returns the root of the subtree of leafs `[start .. end)`, `start`
+    /// aligned to the subtree size.
+    fn span_root(&self, start: usize, end: usize) -> T {
+        let level = log2_pow2(next_pow2(end - start));
+        self.subtree_root(level, start >> level).unwrap()
+    }
+
+    /// Returns merkle root
+    pub fn root(&self) -> T {
+        self.data.get(self.data.len() - 1)
+    }
+
+    /// Returns merkle root as lowercase hex string.
+    pub fn root_hex(&self) -> String {
+        to_hex(self.root().as_ref())
+    }
+
+    /// Renders the tree as ASCII art, each node as its truncated hex hash
+    /// under its parent, the root first:
+    ///
+    /// ```text
+    /// e7f0835d
+    /// +-- 83bb3e35
+    /// |   +-- 8f1f8d76
+    /// |   `-- 17ebb5ce
+    /// `-- 319ed0fc
+    ///     `-- 60d53b46
+    /// ```
+    ///
+    /// Padding nodes are not rendered, pruned tree is rendered down to the
+    /// kept levels.
+    pub fn fmt_tree(&self) -> String {
+        let mut out = short_hex(self.root().as_ref());
+        out.push('\n');
+        if self.height > 0 {
+            self.fmt_subtree(&mut out, self.height - 1, 0, "");
+        }
+        out
+    }
+
+    /// Renders the children of node `index` of `level`.
+    fn fmt_subtree(&self, out: &mut String, level: usize, index: usize, prefix: &str) {
+        if level == self.pruned {
+            return;
+        }
+        let children: Vec<usize> = (2 * index..2 * index + 2)
+            .filter(|&i| self.position(level - 1, i).is_some())
+            .collect();
+        for (k, &child) in children.iter().enumerate() {
+            let last = k + 1 == children.len();
+            let h = self.data.get(self.position(level - 1, child).unwrap());
+            out.push_str(prefix);
+            out.push_str(if last { "`-- " } else { "+-- " });
+            out.push_str(&short_hex(h.as_ref()));
+            out.push('\n');
+
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "|   " });
+            self.fmt_subtree(out, level - 1, child, &prefix);
+        }
+    }
+
+    /// Returns number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns height of the tree, the number of levels including the
+    /// leafs and the root. Single leaf tree is of height 1.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns original number of elements the tree was built upon.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+}
+
+/// Levels narrower than that are hashed sequentially, as threading overhead
+/// dominates there.
+#[cfg(feature = "rayon")]
+const PAR_MIN_WIDTH: usize = 1024;
+
+#[cfg(feature = "rayon")]
+impl<T, A> MerkleTree<T, A>
+where
+    T: Eq + Clone + AsRef<[u8]> + Send + Sync,
+    A: Algorithm<T> + Send,
+{
+    /// Creates new merkle tree from a list of hashable objects in parallel.
+    ///
+    /// Leafs and every level of the tree are hashed on the rayon global
+    /// thread pool. The tree is identical to the one [`MerkleTree::from_data`]
+    /// builds from the same data.
+    pub fn par_from_data<O, I>(data: I) -> MerkleTree<T, A>
+    where
+        O: Hashable<A> + Send,
+        I: IntoParallelIterator<Item = O>,
+        I::Iter: IndexedParallelIterator,
+    {
+        let mut mt = Self::par_leafs(data);
+        if mt.leafs > 0 {
+            let _ = mt.par_build(|_| ControlFlow::Continue(()));
+        }
+        mt
+    }
+
+    /// Creates new merkle tree from a list of hashable objects in parallel,
+    /// as [`MerkleTree::par_from_data`] does, on a pool of its own of
+    /// `num_threads` threads instead of the rayon global one.
+    ///
+    /// The tree is the one of the sequential build whatever the number of
+    /// threads. Zero threads are the rayon default number. Fails if the
+    /// pool threads can not be spawned.
+    pub fn par_from_data_with_threads<O, I>(
+        data: I,
+        num_threads: usize,
+    ) -> Result<MerkleTree<T, A>, ThreadPoolBuildError>
+    where
+        O: Hashable<A> + Send,
+        I: IntoParallelIterator<Item = O> + Send,
+        I::Iter: IndexedParallelIterator,
+    {
+        let pool = ThreadPoolBuilder::new().num_threads(num_threads).build()?;
+        Ok(pool.install(|| Self::par_from_data(data)))
+    }
+
+    /// Hashes the leafs of the tree in parallel, leaving the interior nodes
+    /// to [`MerkleTree::par_build`].
+    fn par_leafs<O, I>(data: I) -> MerkleTree<T, A>
+    where
+        O: Hashable<A> + Send,
+        I: IntoParallelIterator<Item = O>,
+        I::Iter: IndexedParallelIterator,
+    {
+        let data: Vec<T> = data
+            .into_par_iter()
+            .with_min_len(PAR_MIN_WIDTH)
+            .map_init(A::default, |a, x| {
+                a.reset();
+                x.hash_with_context(a, HashContext::Leaf);
+                let h = a.hash();
+                a.reset();
+                a.leaf(h)
+            })
+            .collect();
+
+        let leafs = data.len();
+        if leafs == 0 {
+            return Self::empty();
+        }
+
+        let size = tree_size(leafs).expect("tree is too large");
+
+        let mut mt: MerkleTree<T, A> = MerkleTree {
+            data,
+            leafs,
+            height: log2_pow2(size + 1),
+            padding: Padding::Duplicate,
+            order: NodeOrder::Positional,
+            raw: false,
+            pruned: 0,
+            index: None,
+            _a: PhantomData,
+        };
+
+        mt.data.reserve_exact(size - leafs);
+        mt
+    }
+
+    /// Creates new merkle tree from a list of hashable objects in parallel,
+    /// as [`MerkleTree::par_from_data`] does, reporting the `progress` of the
+    /// build.
+    ///
+    /// Progress is reported on the calling thread at every level completed.
+    /// The build stops with [`MerkleError::Cancelled`] as soon as `progress`
+    /// breaks, once the level being hashed is complete.
+    pub fn par_from_data_with_progress<O, I, P>(
+        data: I,
+        mut progress: P,
+    ) -> Result<MerkleTree<T, A>, MerkleError>
+    where
+        O: Hashable<A> + Send,
+        I: IntoParallelIterator<Item = O>,
+        I::Iter: IndexedParallelIterator,
+        P: FnMut(BuildProgress) -> ControlFlow<()>,
+    {
+        let mut mt = Self::par_leafs(data);
+        let leafs = mt.leafs;
+        if leafs == 0 {
+            return Ok(mt);
+        }
+        let report = |levels| progress(BuildProgress { leafs, levels });
+        match mt.par_build(report) {
+            ControlFlow::Continue(()) => Ok(mt),
+            ControlFlow::Break(()) => Err(MerkleError::Cancelled),
+        }
+    }
+
+    /// Generates inclusion proofs of leafs `indices` in parallel.
+    ///
+    /// Proofs are in the order of `indices` and identical to the ones
+    /// [`MerkleTree::gen_proof`] generates, which panics the same way on an
+    /// index out of bounds.
+    pub fn par_gen_proofs(&self, indices: &[usize]) -> Vec<Proof<T>> {
+        indices.par_iter().map(|&i| self.gen_proof(i)).collect()
+    }
+
+    fn par_build<P: FnMut(usize) -> ControlFlow<()>>(
+        &mut self,
+        mut progress: P,
+    ) -> ControlFlow<()> {
+        let mut width = self.leafs;
+        let mut levels: usize = 1;
+        if progress(levels).is_break() {
+            return ControlFlow::Break(());
+        }
+
+        let mut i: usize = 0;
+        while width > 1 {
+            // if there is odd num of elements, fill in to the even
+            if width & 1 == 1 {
+                match self.padding {
+                    Padding::Duplicate => {
+                        let he = self.data[self.len() - 1].clone();
+                        self.data.push(he);
+                    }
+                    Padding::Zero(ref z) => {
+                        let he = z.clone();
+                        self.data.push(he);
+                    }
+                    Padding::Promote => {}
+                }
+            }
+            let j = i + self.stored_width(width);
+
+            // a lone last node is promoted
+            let (order, raw) = (self.order, self.raw);
+            let level: Vec<T> = self.data[i..j]
+                .par_chunks(2)
+                .with_min_len(PAR_MIN_WIDTH / 2)
+                .map_init(A::default, |a, pair| {
+                    if pair.len() == 1 {
+                        return pair[0].clone();
+                    }
+                    a.reset();
+                    node(order, raw, a, pair[0].clone(), pair[1].clone())
+                })
+                .collect();
+            self.data.extend(level);
+
+            i = j;
+            width = (width + 1) >> 1;
+
+            levels += 1;
+            if progress(levels).is_break() {
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> FromIterator<T> for MerkleTree<T, A> {
+    /// Creates new merkle tree from an iterator over hashable objects.
+    ///
+    /// Leafs are streamed into the tree storage without an intermediate
+    /// collection, iterators of unknown length are supported too.
+    fn from_iter<I: IntoIterator<Item = T>>(into: I) -> Self {
+        Self::from_leafs(
+            Vec::new(),
+            into,
+            Padding::Duplicate,
+            NodeOrder::Positional,
+            false,
+            A::default(),
+            |a: &mut A| a.reset(),
+        )
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> From<Vec<T>> for MerkleTree<T, A> {
+    /// Creates new merkle tree over leaf nodes hashed elsewhere, see
+    /// [`MerkleTree::from_leaf_hashes`].
+    ///
+    /// Unlike collecting the hashes, which hashes them as leafs, no leaf
+    /// hashing is applied.
+    fn from(hashes: Vec<T>) -> Self {
+        Self::from_leaf_hashes(hashes)
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> Extend<T> for MerkleTree<T, A> {
+    /// Appends leafs to the tree, see [`MerkleTree::extend`].
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        MerkleTree::extend(self, iter)
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> fmt::Debug for MerkleTree<T, A, S> {
+    /// Renders the tree level by level, the root first, each node as its
+    /// truncated hex hash:
+    ///
+    /// ```text
+    /// MerkleTree { leafs: 3, height: 3 }
+    ///   2: e7f0835d
+    ///   1: 83bb3e35 319ed0fc
+    ///   0: 8f1f8d76 17ebb5ce 60d53b46
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MerkleTree {{ leafs: {}, height: {} }}",
+            self.leafs, self.height
+        )?;
+        if self.height == 0 {
+            return write!(f, "\n  root: {}", short_hex(self.root().as_ref()));
+        }
+        for level in (self.pruned..self.height).rev() {
+            write!(f, "\n  {}:", level)?;
+            let mut index = 0;
+            while let Some(i) = self.position(level, index) {
+                write!(f, " {}", short_hex(self.data.get(i).as_ref()))?;
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> PartialEq for MerkleTree<T, A, S> {
+    fn eq(&self, other: &Self) -> bool {
+        // equal nodes of `[a b c]` and `[a b c c]`, see `from_data_checked`
+        self.leafs == other.leafs
+            && self.pruned == other.pruned
+            && self.data.len() == other.data.len()
+            && (0..self.data.len()).all(|i| self.data.get(i) == other.data.get(i))
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> Eq for MerkleTree<T, A, S> {}
+
+impl<T, A, S> Hash for MerkleTree<T, A, S>
+where
+    T: Eq + Clone + AsRef<[u8]> + Hash,
+    A: Algorithm<T>,
+    S: Store<T>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&self.leafs, state);
+        Hash::hash(&self.pruned, state);
+        Hash::hash(&self.data.len(), state);
+        for i in 0..self.data.len() {
+            Hash::hash(&self.data.get(i), state);
+        }
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> ops::Deref for MerkleTree<T, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.data.deref()
+    }
+}
+
+/// Merkle tree along with the original leaf values `L` it is built from,
+/// see [`MerkleTree::retain_leaves`] and [`MerkleTreeBuilder::build_values`].
+///
+/// The tree is only lent out, see [`RetainedMerkleTree::tree`], so the
+/// values stay the ones of its leafs.
+#[derive(Clone)]
+pub struct RetainedMerkleTree<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, L> {
+    tree: MerkleTree<T, A>,
+    values: Vec<L>,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, L> RetainedMerkleTree<T, A, L> {
+    /// Generate merkle tree inclusion proof for leaf `i` along with the
+    /// original value of the leaf.
+    ///
+    /// Returns `None` if the leaf values are not retained, see
+    /// [`MerkleTreeBuilder::retain_leaves`], or `i` is not a leaf.
+    pub fn gen_proof_with_value(&self, i: usize) -> Option<(&L, Proof<T>)> {
+        let value = self.value(i)?;
+        Some((value, self.tree.gen_proof(i)))
+    }
+
+    /// Returns the original value of leaf `i`, `None` if the leaf values
+    /// are not retained or `i` is not a leaf.
+    pub fn value(&self, i: usize) -> Option<&L> {
+        self.values.get(i)
+    }
+
+    /// Returns the tree.
+    pub fn tree(&self) -> &MerkleTree<T, A> {
+        &self.tree
+    }
+
+    /// Returns the retained leaf values, empty if they are not retained.
+    pub fn values(&self) -> &[L] {
+        &self.values
+    }
+
+    /// Returns the tree, dropping the leaf values.
+    pub fn into_tree(self) -> MerkleTree<T, A> {
+        self.tree
+    }
+
+    /// Builds the tree of the retained leaf values under algorithm `B`,
+    /// as [`MerkleTree::from_data`] does.
+    ///
+    /// Panics if the leaf values are not retained.
+    pub fn rebuild_with<B: Algorithm<T>>(&self) -> MerkleTree<T, B>
+    where
+        L: Hashable<B>,
+    {
+        assert!(
+            self.tree.leafs() == self.values.len(),
+            "leaf values are not retained"
+        );
+        MerkleTree::from_data(&self.values)
+    }
+
+    /// Returns the subtree of node `index` of `level` along with the values
+    /// of its leafs, see [`MerkleTree::subtree`].
+    pub fn subtree(&self, level: usize, index: usize) -> Option<RetainedMerkleTree<T, A, L>>
+    where
+        L: Clone,
+    {
+        let tree = self.tree.subtree(level, index)?;
+        let values = if self.values.is_empty() {
+            Vec::new()
+        } else {
+            self.values[self.tree.leaf_range(level, index)].to_vec()
+        };
+        Some(RetainedMerkleTree { tree, values })
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, L: fmt::Debug> fmt::Debug
+    for RetainedMerkleTree<T, A, L>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetainedMerkleTree")
+            .field("tree", &self.tree)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+/// Builder of [`MerkleTree`] which combines the options of the
+/// `from_data_with_*` constructors.
+///
+/// Defaults build the same tree as [`MerkleTree::from_data`]:
+///
+/// ```
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::{MerkleTree, MerkleTreeBuilder, NodeOrder, PaddingPolicy};
+/// # use std::hash::Hasher;
+/// # #[derive(Clone, Default)]
+/// # struct Xor([u8; 8]);
+/// # impl Hasher for Xor {
+/// #     fn write(&mut self, bytes: &[u8]) {
+/// #         for (i, b) in bytes.iter().enumerate() {
+/// #             self.0[i % 8] ^= b;
+/// #         }
+/// #     }
+/// #     fn finish(&self) -> u64 {
+/// #         u64::from_le_bytes(self.0)
+/// #     }
+/// # }
+/// # impl Algorithm<[u8; 8]> for Xor {
+/// #     fn hash(&mut self) -> [u8; 8] {
+/// #         self.0
+/// #     }
+/// # }
+///
+/// let mt: MerkleTree<[u8; 8], Xor> = MerkleTreeBuilder::new()
+///     .padding(PaddingPolicy::PromoteOdd)
+///     .ordering(NodeOrder::Sorted)
+///     .build([1u64, 2, 3]);
+/// assert_eq!(mt.policy(), PaddingPolicy::PromoteOdd);
+/// assert_eq!(mt.order(), NodeOrder::Sorted);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MerkleTreeBuilder<T, A> {
+    padding: Padding<T>,
+    order: NodeOrder,
+    separation: bool,
+    hasher: A,
+    capacity: usize,
+    retain: bool,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTreeBuilder<T, A> {
+    /// Creates new builder with the [`MerkleTree::from_data`] options.
+    pub fn new() -> MerkleTreeBuilder<T, A> {
+        MerkleTreeBuilder {
+            padding: Padding::Duplicate,
+            order: NodeOrder::Positional,
+            separation: true,
+            hasher: A::default(),
+            capacity: 0,
+            retain: false,
+        }
+    }
+
+    /// Pads odd levels according to `policy`, see
+    /// [`MerkleTree::from_data_with_policy`].
+    pub fn padding(mut self, policy: PaddingPolicy) -> Self
+    where
+        T: Default,
+    {
+        self.padding = Padding::from_policy(policy);
+        self
+    }
+
+    /// Hashes the children of interior nodes in `order`, see
+    /// [`MerkleTree::from_data_with_order`].
+    pub fn ordering(mut self, order: NodeOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Enables or disables the domain separation of leafs and interior nodes
+    /// done by [`Algorithm::leaf`] and [`Algorithm::node`], enabled by
+    /// default.
+    ///
+    /// Without it a leaf is the hash of the item and a node is the hash of
+    /// the raw concatenation of its children, as in the trees of bitcoin.
+    /// Such trees are open to the second preimage attack, use it only to
+    /// interoperate with them. Proofs are validated with
+    /// [`Unprefixed`](crate::hash::Unprefixed) algorithm.
+    pub fn domain_separation(mut self, enabled: bool) -> Self {
+        self.separation = enabled;
+        self
+    }
+
+    /// Hashes with a preconfigured `hasher` instance, see
+    /// [`MerkleTree::from_data_with_hasher`].
+    pub fn hasher(mut self, hasher: A) -> Self {
+        self.hasher = hasher;
+        self
+    }
+
+    /// Sizes the storage for the nodes of the tree of `n` leafs up front,
+    /// for the data iterators which do not know their length.
+    pub fn capacity(mut self, n: usize) -> Self {
+        self.capacity = n;
+        self
+    }
+
+    /// Keeps the leaf values [`MerkleTreeBuilder::build_values`] builds the
+    /// tree from, see [`RetainedMerkleTree::gen_proof_with_value`],
+    /// disabled by default.
+    pub fn retain_leaves(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    /// Builds the tree from a list of leaf values, which are kept along with
+    /// the nodes if [`MerkleTreeBuilder::retain_leaves`] is enabled.
+    pub fn build_values<L: Hashable<A>, I: IntoIterator<Item = L>>(
+        self,
+        values: I,
+    ) -> RetainedMerkleTree<T, A, L>
+    where
+        A: Clone,
+    {
+        if !self.retain {
+            return RetainedMerkleTree {
+                tree: self.build(values),
+                values: Vec::new(),
+            };
+        }
+        let values: Vec<L> = values.into_iter().collect();
+        RetainedMerkleTree {
+            tree: self.build(&values),
+            values,
+        }
+    }
+
+    /// Builds the tree from a list of hashable objects.
+    pub fn build<O: Hashable<A>, I: IntoIterator<Item = O>>(self, data: I) -> MerkleTree<T, A>
+    where
+        A: Clone,
+    {
+        let algo = self.hasher;
+        let mut a = algo.clone();
+        // too large a hint is ignored as too small a one
+        let capacity = match self.capacity {
+            0 => 0,
+            n => tree_size(n).unwrap_or(0),
+        };
+        MerkleTree::from_leafs(
+            Vec::with_capacity(capacity),
+            data.into_iter().map(|x| {
+                a.clone_from(&algo);
+                x.hash_with_context(&mut a, HashContext::Leaf);
+                a.hash()
+            }),
+            self.padding,
+            self.order,
+            !self.separation,
+            algo.clone(),
+            |a: &mut A| a.clone_from(&algo),
+        )
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>> Default for MerkleTreeBuilder<T, A> {
+    fn default() -> MerkleTreeBuilder<T, A> {
+        Self::new()
+    }
+}
+
+/// Formats the first 4 bytes of hash as lowercase hex, for the tree
+/// renderings.
+fn short_hex(bytes: &[u8]) -> String {
+    to_hex(&bytes[..bytes.len().min(4)])
+}
+
+/// Number of nodes stored by the tree of `leafs` leafs, the storage size
+/// reserved up front by the constructors.
+///
+/// Fails if the number overflows `usize`, e.g. for more than `2^31` leafs
+/// on 32-bit targets.
+fn tree_size(leafs: usize) -> Result<usize, MerkleError> {
+    leafs
+        .max(1)
+        .checked_next_power_of_two()
+        .and_then(|pow| pow.checked_mul(2))
+        .map(|n| n - 1)
+        .ok_or(MerkleError::TooLarge)
+}
+
+/// Returns the level of the node which spans leafs `chunk` of the tree of
+/// `leafs` leafs, if there is such a node, the lowest one of the last
+/// chunk.
+fn chunk_level(chunk: &Range<usize>, leafs: usize) -> Option<usize> {
+    if chunk.start >= chunk.end || chunk.end > leafs {
+        return None;
+    }
+    let size = (chunk.end - chunk.start).checked_next_power_of_two()?;
+    let fits = chunk.end - chunk.start == size || chunk.end == leafs;
+    if fits && chunk.start & (size - 1) == 0 {
+        Some(log2_pow2(size))
+    } else {
+        None
+    }
+}
+
+/// `next_pow2` returns next highest power of two from a given number if
+/// it is not already a power of two.
+///
+/// [](http://locklessinc.com/articles/next_pow2/)
+/// [](https://stackoverflow.com/questions/466204/rounding-up-to-next-power-of-2/466242#466242)
+pub fn next_pow2(mut n: usize) -> usize {
+    n -= 1;
+    n |= n >> 1;
+    n |= n >> 2;
+    n |= n >> 4;
+    n |= n >> 8;
+    n |= n >> 16;
+    #[cfg(target_pointer_width = "64")]
+    {
+        n |= n >> 32;
     }
     n + 1
 }
@@ -233,3 +2470,37 @@ pub fn next_pow2(mut n: usize) -> usize {
 pub fn log2_pow2(n: usize) -> usize {
     n.trailing_zeros() as usize
 }
+
+/// Returns leaf hash of `item`, which is `item` itself without domain
+/// separation.
+fn leaf<T, A>(raw: bool, a: &mut A, item: T) -> T
+where
+    T: Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    if raw {
+        item
+    } else {
+        a.leaf(item)
+    }
+}
+
+/// Returns interior node hash of `left` and `right` in `order`, which is
+/// the hash of their raw concatenation without domain separation.
+fn node<T, A>(order: NodeOrder, raw: bool, a: &mut A, left: T, right: T) -> T
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    if raw {
+        let (left, right) = match order {
+            NodeOrder::Sorted if right.as_ref() < left.as_ref() => (right, left),
+            _ => (left, right),
+        };
+        a.write(left.as_ref());
+        a.write(right.as_ref());
+        a.hash()
+    } else {
+        order.node(a, left, right)
+    }
+}
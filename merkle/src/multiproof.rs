@@ -0,0 +1,182 @@
+extern crate alloc;
+
+use crate::error::MerkleError;
+use crate::hash::Algorithm;
+use crate::proof::Proof;
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Merkle tree batched inclusion proof for a set of leafs.
+///
+/// Instead of carrying one lemma per leaf, multiproof carries only the
+/// sibling hashes which can not be computed from the proven leafs themselves,
+/// so interior nodes shared by several paths are never repeated.
+///
+/// Nodes layout is the order in which validation consumes them: level by
+/// level from the leafs up, and left to right within each level.
+///
+/// ```text
+/// [ level 1 siblings ... level 2 siblings ... level N siblings ]
+/// ```
+///
+/// Node without a sibling (promoted by [`PaddingPolicy::PromoteOdd`]) is
+/// marked with `None`.
+///
+/// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiProof<T: Eq + Clone + AsRef<[u8]>> {
+    indices: Vec<usize>,
+    nodes: Vec<Option<T>>,
+    height: usize,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> MultiProof<T> {
+    /// Creates new MT batched inclusion proof.
+    ///
+    /// `indices` must be sorted and deduplicated leaf indices, `nodes` the
+    /// sibling hashes in validation order and `height` the height of the tree.
+    pub fn new(indices: Vec<usize>, nodes: Vec<Option<T>>, height: usize) -> MultiProof<T> {
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        MultiProof {
+            indices,
+            nodes,
+            height,
+        }
+    }
+
+    /// Merges single inclusion proofs into one batched proof, the one
+    /// [`MerkleTree::gen_multi_proof`] generates for their leafs.
+    ///
+    /// Every proof is validated, all of them must have the same root and
+    /// record their leaf index, see [`Proof::index`]. Proofs of the same
+    /// leaf are merged into one. Proofs of [`NodeOrder::Sorted`] trees and
+    /// of the leafs promoted by [`PaddingPolicy::PromoteOdd`] can not be
+    /// merged, their paths do not give the sibling positions.
+    ///
+    /// Fails with [`MerkleError::RootMismatch`] if the roots disagree and
+    /// with [`MerkleError::InvalidProof`] if a proof does not validate or
+    /// does not carry the full path of its index.
+    ///
+    /// [`MerkleTree::gen_multi_proof`]: crate::merkle::MerkleTree::gen_multi_proof
+    /// [`NodeOrder::Sorted`]: crate::merkle::NodeOrder::Sorted
+    /// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+    pub fn from_proofs<A: Algorithm<T>>(proofs: &[Proof<T>]) -> Result<MultiProof<T>, MerkleError> {
+        let first = match proofs.first() {
+            Some(p) => p,
+            None => return Ok(MultiProof::new(Vec::new(), Vec::new(), 0)),
+        };
+        let depth = first.path().len();
+
+        let mut layer = Vec::with_capacity(proofs.len());
+        for p in proofs {
+            if p.root() != first.root() {
+                return Err(MerkleError::RootMismatch);
+            }
+            let i = p.index().ok_or(MerkleError::InvalidProof)?;
+            let full = p.path().len() == depth
+                && i.checked_shr(depth as u32).unwrap_or(0) == 0
+                && p.path()
+                    .iter()
+                    .enumerate()
+                    .all(|(l, &left)| left == (i >> l & 1 == 0));
+            if !full || !p.validate::<A>() {
+                return Err(MerkleError::InvalidProof);
+            }
+            layer.push((i, p));
+        }
+        layer.sort_by_key(|&(i, _)| i);
+        layer.dedup_by_key(|&mut (i, _)| i);
+
+        let indices = layer.iter().map(|&(i, _)| i).collect();
+        let mut nodes = Vec::new();
+        for level in 0..depth {
+            let mut next = Vec::with_capacity(layer.len());
+            let mut k = 0;
+            while k < layer.len() {
+                let (j, p) = layer[k];
+                if j & 1 == 0 && k + 1 < layer.len() && layer[k + 1].0 == j + 1 {
+                    // sibling is proven itself
+                    k += 1;
+                } else {
+                    nodes.push(Some(p.lemma()[level + 1].clone()));
+                }
+                next.push((j >> 1, p));
+                k += 1;
+            }
+            layer = next;
+        }
+
+        Ok(MultiProof::new(indices, nodes, depth + 1))
+    }
+
+    /// Verifies MT batched inclusion proof for `leaves` against `root`.
+    ///
+    /// `leaves` are `(index, leaf)` pairs in any order, which must cover
+    /// exactly the indices the proof was generated for. An empty proof
+    /// validates against any root for an empty set of leaves.
+    pub fn validate<A: Algorithm<T>>(&self, root: &T, leaves: &[(usize, T)]) -> bool {
+        let mut layer = leaves.to_vec();
+        layer.sort_by_key(|&(i, _)| i);
+        if layer.len() != self.indices.len()
+            || layer.iter().zip(&self.indices).any(|(&(i, _), &j)| i != j)
+        {
+            return false;
+        }
+        if layer.is_empty() {
+            return true;
+        }
+
+        let mut a = A::default();
+        let mut nodes = self.nodes.iter();
+
+        for _ in 1..self.height {
+            let mut next = Vec::with_capacity(layer.len());
+            let mut k = 0;
+            while k < layer.len() {
+                let (j, ref h) = layer[k];
+                let paired = j & 1 == 0 && k + 1 < layer.len() && layer[k + 1].0 == j + 1;
+                let sibling = if paired {
+                    k += 1;
+                    layer[k].1.clone()
+                } else {
+                    match nodes.next() {
+                        Some(Some(n)) => n.clone(),
+                        // only the last left node of a level can be promoted
+                        Some(None) if j & 1 == 0 && k + 1 == layer.len() => {
+                            next.push((j >> 1, h.clone()));
+                            k += 1;
+                            continue;
+                        }
+                        _ => return false,
+                    }
+                };
+
+                a.reset();
+                next.push((
+                    j >> 1,
+                    if j & 1 == 0 {
+                        a.node(h.clone(), sibling)
+                    } else {
+                        a.node(sibling, h.clone())
+                    },
+                ));
+                k += 1;
+            }
+            layer = next;
+        }
+
+        nodes.next().is_none() && layer.len() == 1 && layer[0].0 == 0 && layer[0].1 == *root
+    }
+
+    /// Returns the leaf indices this proof was generated for.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns the sibling hashes of this proof.
+    pub fn nodes(&self) -> &[Option<T>] {
+        &self.nodes
+    }
+}
@@ -0,0 +1,272 @@
+extern crate alloc;
+
+use crate::hash::{Algorithm, HashContext, Hashable};
+use crate::proof::Proof;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Merkle tree which hashes `ARITY` children per interior node.
+///
+/// Wider nodes make the tree shallower: proofs have `log_ARITY(n)` levels
+/// of `ARITY - 1` siblings each, and building the tree takes fewer hash
+/// calls over longer inputs. Nodes are hashed with [`Algorithm::nary_node`].
+///
+/// ```text
+///     ARITY = 4, 16 leafs:
+///     [h0 .. h15] [h0-3 h4-7 h8-11 h12-15] [root]
+/// ```
+///
+/// Levels which are not a multiple of `ARITY` are padded with duplicates of
+/// the last node, so the binary tree (`ARITY = 2`) is the one
+/// [`MerkleTree::from_data`] builds and has the same root.
+///
+/// [`MerkleTree::from_data`]: crate::merkle::MerkleTree::from_data
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NaryMerkleTree<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, const ARITY: usize> {
+    data: Vec<T>,
+    leafs: usize,
+    height: usize,
+    _a: PhantomData<fn() -> A>,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>, const ARITY: usize> NaryMerkleTree<T, A, ARITY> {
+    /// Creates new n-ary merkle tree from a sequence of hashes.
+    ///
+    /// Root of the empty tree is the hash of the empty input, the one of the
+    /// empty [`MerkleTree`], and the tree has no leafs and no levels.
+    ///
+    /// [`MerkleTree`]: crate::merkle::MerkleTree
+    pub fn new<I: IntoIterator<Item = T>>(data: I) -> NaryMerkleTree<T, A, ARITY> {
+        assert!(ARITY >= 2, "arity must be at least 2");
+
+        let mut a = A::default();
+        let mut data: Vec<T> = data
+            .into_iter()
+            .map(|item| {
+                a.reset();
+                a.leaf(item)
+            })
+            .collect();
+
+        let leafs = data.len();
+        if leafs == 0 {
+            a.reset();
+            return NaryMerkleTree {
+                data: vec![a.hash()],
+                leafs: 0,
+                height: 0,
+                _a: PhantomData,
+            };
+        }
+
+        let mut height = 1;
+        let mut base = 0;
+        let mut width = leafs;
+        while width > 1 {
+            // fill in to the multiple of arity with the last node
+            let he = data[data.len() - 1].clone();
+            let stored = width.div_ceil(ARITY) * ARITY;
+            data.resize(base + stored, he);
+
+            for k in (base..base + stored).step_by(ARITY) {
+                a.reset();
+                let h = a.nary_node(&data[k..k + ARITY]);
+                data.push(h);
+            }
+
+            base += stored;
+            width = stored / ARITY;
+            height += 1;
+        }
+
+        NaryMerkleTree {
+            data,
+            leafs,
+            height,
+            _a: PhantomData,
+        }
+    }
+
+    /// Creates new n-ary merkle tree from a list of hashable objects.
+    pub fn from_data<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+    ) -> NaryMerkleTree<T, A, ARITY> {
+        let mut a = A::default();
+        Self::new(data.into_iter().map(|x| {
+            a.reset();
+            x.hash_with_context(&mut a, HashContext::Leaf);
+            a.hash()
+        }))
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    pub fn gen_proof(&self, i: usize) -> NaryProof<T> {
+        assert!(i < self.leafs); // i in [0 .. self.leafs)
+
+        let mut lemma: Vec<T> = Vec::with_capacity((self.height - 1) * (ARITY - 1) + 2);
+        let mut path: Vec<usize> = Vec::with_capacity(self.height - 1);
+
+        let mut base = 0;
+        let mut width = self.leafs;
+        let mut j = i;
+        lemma.push(self.data[j].clone());
+        while width > 1 {
+            let group = base + j / ARITY * ARITY;
+            let pos = j % ARITY;
+            lemma.extend_from_slice(&self.data[group..group + pos]);
+            lemma.extend_from_slice(&self.data[group + pos + 1..group + ARITY]);
+            path.push(pos);
+
+            let stored = width.div_ceil(ARITY) * ARITY;
+            base += stored;
+            width = stored / ARITY;
+            j /= ARITY;
+        }
+
+        // root is final
+        lemma.push(self.root());
+        NaryProof::new(lemma, path, ARITY)
+    }
+
+    /// Returns merkle root
+    pub fn root(&self) -> T {
+        self.data[self.data.len() - 1].clone()
+    }
+
+    /// Returns height of the tree
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns original number of elements the tree was built upon.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns the number of children per interior node.
+    pub fn arity(&self) -> usize {
+        ARITY
+    }
+}
+
+/// Inclusion proof of [`NaryMerkleTree`] leaf.
+///
+/// Lemma layout, with `arity - 1` siblings per level in positional order:
+///
+/// ```text
+/// [ item s1.. s2.. s3.. ... root ]
+/// ```
+///
+/// Path is the position of the proven node among its siblings at each level
+/// from the leaf up.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NaryProof<T: Eq + Clone + AsRef<[u8]>> {
+    lemma: Vec<T>,
+    path: Vec<usize>,
+    arity: usize,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> NaryProof<T> {
+    /// Creates new n-ary MT inclusion proof
+    pub fn new(hash: Vec<T>, path: Vec<usize>, arity: usize) -> NaryProof<T> {
+        assert!(arity >= 2);
+        assert!(hash.len() > 1);
+        assert_eq!(hash.len() - 2, path.len() * (arity - 1));
+        assert!(path.iter().all(|&pos| pos < arity));
+        NaryProof {
+            lemma: hash,
+            path,
+            arity,
+        }
+    }
+
+    /// Converts the binary [`Proof`] into the proof of arity 2, which
+    /// validates the same.
+    ///
+    /// Returns `None` for the proofs without a path bit per sibling, i.e.
+    /// the ones of [`NodeOrder::Sorted`] trees.
+    ///
+    /// [`NodeOrder::Sorted`]: crate::merkle::NodeOrder::Sorted
+    pub fn from_proof(proof: &Proof<T>) -> Option<NaryProof<T>> {
+        let lemma = proof.lemma();
+        if lemma.len() < 2 || lemma.len() - 2 != proof.path().len() {
+            return None;
+        }
+        // a left child is at position 0
+        let path = proof
+            .path()
+            .iter()
+            .map(|&left| usize::from(!left))
+            .collect();
+        Some(NaryProof::new(lemma.to_vec(), path, 2))
+    }
+
+    /// Returns the position of the proven leaf, decoded from the path.
+    ///
+    /// Exact for the trees [`NaryMerkleTree`] builds, which pad every level.
+    pub fn index(&self) -> usize {
+        self.path
+            .iter()
+            .rev()
+            .fold(0, |index, &pos| index * self.arity + pos)
+    }
+
+    /// Return proof target leaf
+    pub fn item(&self) -> T {
+        self.lemma.first().unwrap().clone()
+    }
+
+    /// Return tree root
+    pub fn root(&self) -> T {
+        self.lemma.last().unwrap().clone()
+    }
+
+    /// Verifies n-ary MT inclusion proof
+    pub fn validate<A: Algorithm<T>>(&self) -> bool {
+        let size = self.lemma.len();
+        if self.arity < 2 || size < 2 {
+            return false;
+        }
+        let siblings = self.arity - 1;
+        if self.path.len().checked_mul(siblings) != Some(size - 2) {
+            return false;
+        }
+
+        let mut a = A::default();
+        let mut h = self.item();
+        let mut children: Vec<T> = Vec::with_capacity(self.arity);
+
+        for (level, &pos) in self.path.iter().enumerate() {
+            if pos >= self.arity {
+                return false;
+            }
+            let first = 1 + level * siblings;
+            children.clear();
+            children.extend_from_slice(&self.lemma[first..first + siblings]);
+            children.insert(pos, h);
+
+            a.reset();
+            h = a.nary_node(&children);
+        }
+
+        h == self.root()
+    }
+
+    /// Returns the number of children per interior node.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the path of this proof.
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// Returns the lemma of this proof.
+    pub fn lemma(&self) -> &[T] {
+        &self.lemma
+    }
+}
@@ -1,7 +1,12 @@
 extern crate alloc;
 
-use crate::hash::Algorithm;
+use crate::error::{MerkleError, ProofError};
+use crate::hash::{to_hex, Algorithm};
+use crate::merkle::NodeOrder;
+use alloc::string::String;
 use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Merkle tree inclusion proof for data element, for which item = Leaf(Hash(Data Item)).
 ///
@@ -12,18 +17,115 @@ use alloc::vec::Vec;
 /// ```
 ///
 /// Proof validation is positioned hash against lemma path to match root hash.
+///
+/// Proofs generated by the tree record the leaf index, and validate only if
+/// the path bits are the ones of that index, so that a proof edited to
+/// prove another position is rejected, see [`Proof::index`].
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Proof<T: Eq + Clone + AsRef<[u8]>> {
     lemma: Vec<T>,
     path: Vec<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    index: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    promote_leafs: Option<usize>,
 }
 
 impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
     /// Creates new MT inclusion proof
     pub fn new(hash: Vec<T>, path: Vec<bool>) -> Proof<T> {
-        assert!(hash.len() > 2);
+        assert!(hash.len() > 1);
         assert_eq!(hash.len() - 2, path.len());
-        Proof { lemma: hash, path }
+        Proof {
+            lemma: hash,
+            path,
+            index: None,
+            promote_leafs: None,
+        }
+    }
+
+    /// Creates new MT inclusion proof of a [`NodeOrder::Sorted`] tree, which
+    /// carries no path bits.
+    ///
+    /// [`NodeOrder::Sorted`]: crate::merkle::NodeOrder::Sorted
+    pub fn new_sorted(hash: Vec<T>) -> Proof<T> {
+        assert!(hash.len() > 1);
+        Proof {
+            lemma: hash,
+            path: Vec::new(),
+            index: None,
+            promote_leafs: None,
+        }
+    }
+
+    /// Records the leaf `index` this proof is for, binding the path bits to
+    /// it, see [`Proof::index`].
+    pub fn with_index(mut self, index: usize) -> Proof<T> {
+        self.index = Some(index);
+        self
+    }
+
+    /// Records that the proof is of a [`PaddingPolicy::PromoteOdd`] tree of
+    /// `leafs` leafs, which tells the levels the proven node is promoted
+    /// through and has no path bit for, see [`Proof::index`].
+    ///
+    /// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+    pub fn with_promote_odd(mut self, leafs: usize) -> Proof<T> {
+        self.promote_leafs = Some(leafs);
+        self
+    }
+
+    /// Returns the leaf index recorded when the proof was generated.
+    ///
+    /// Proofs of a recorded index validate only if their path bits are the
+    /// ones of the index, bit for bit. Proofs of
+    /// [`PaddingPolicy::PromoteOdd`] trees skip the levels the node is
+    /// promoted through only if they record the leaf count of the tree, see
+    /// [`Proof::with_promote_odd`]. Proofs of [`NodeOrder::Sorted`] trees
+    /// carry no path bits, so nothing binds them to the index.
+    ///
+    /// [`NodeOrder::Sorted`]: crate::merkle::NodeOrder::Sorted
+    /// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Returns the position of the proven leaf, counted from 0, e.g. to
+    /// display the leaf as the `leaf_position() + 1`th of
+    /// [`Proof::tree_size_hint`].
+    ///
+    /// This is the recorded [`Proof::index`], or the index the path bits
+    /// spell for the proofs which have none. Paths of promoted nodes of
+    /// [`PaddingPolicy::PromoteOdd`] trees skip the levels without a
+    /// sibling, and sorted proofs have no path, so their index is only
+    /// known if recorded.
+    ///
+    /// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+    pub fn leaf_position(&self) -> usize {
+        self.index.unwrap_or_else(|| self.path_index())
+    }
+
+    /// Returns the leaf index the path bits spell, see [`Proof::indices`].
+    fn path_index(&self) -> usize {
+        self.path
+            .iter()
+            .rev()
+            .fold(0, |i, &left| (i << 1) | usize::from(!left))
+    }
+
+    /// Returns `2^h` for a proof of `h` siblings, the number of leafs of the
+    /// full tree of the proof height.
+    ///
+    /// This is an upper bound of the leafs of the tree, exact only for the
+    /// trees of a power of two leafs. Paths of the promoted nodes of
+    /// [`PaddingPolicy::PromoteOdd`] trees are shorter than the tree
+    /// height, so the hint of such a proof can be below the tree size.
+    ///
+    /// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+    pub fn tree_size_hint(&self) -> usize {
+        let siblings = self.lemma.len().saturating_sub(2);
+        1usize.checked_shl(siblings as u32).unwrap_or(usize::MAX)
     }
 
     /// Return proof target leaf
@@ -36,8 +138,117 @@ impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
         self.lemma.last().unwrap().clone()
     }
 
+    /// Return tree root as lowercase hex string
+    pub fn root_hex(&self) -> String {
+        to_hex(self.lemma.last().unwrap().as_ref())
+    }
+
     /// Verifies MT inclusion proof
     pub fn validate<A: Algorithm<T>>(&self) -> bool {
+        self.computed_root::<A>().is_some_and(|h| h == self.root())
+    }
+
+    /// Returns the root the item and the siblings of the lemma fold to, the
+    /// one [`Proof::validate`] compares to the root of the lemma.
+    ///
+    /// Returns `None` if the path does not have a bit for every sibling, or
+    /// is not the one of the recorded leaf index.
+    pub fn computed_root<A: Algorithm<T>>(&self) -> Option<T> {
+        self.fold(&mut A::default(), |a: &mut A| a.reset())
+    }
+
+    /// Verifies MT inclusion proof hashing with a preconfigured `algo`
+    /// instance, see [`MerkleTree::from_data_with_hasher`].
+    ///
+    /// [`MerkleTree::from_data_with_hasher`]: crate::merkle::MerkleTree::from_data_with_hasher
+    pub fn validate_with_hasher<A: Algorithm<T> + Clone>(&self, algo: &A) -> bool {
+        match self.fold(&mut algo.clone(), |a: &mut A| a.clone_from(algo)) {
+            Some(h) => h == self.root(),
+            None => false,
+        }
+    }
+
+    /// Verifies MT inclusion proof against `root`, telling why it fails.
+    ///
+    /// Same as [`Proof::validate_ct`], the root the lemma carries is not
+    /// checked, only the one it folds to.
+    pub fn validate_detailed<A: Algorithm<T>>(&self, root: &T) -> Result<(), ProofError<T>> {
+        if self.lemma.is_empty() {
+            return Err(ProofError::EmptyLemma);
+        }
+        if self.lemma.len() != self.path.len() + 2 {
+            return Err(ProofError::LengthMismatch {
+                lemma: self.lemma.len(),
+                path: self.path.len(),
+            });
+        }
+
+        if let Some(index) = self.index {
+            if !path_matches_index(&self.path, index, self.promote_leafs) {
+                return Err(ProofError::IndexMismatch { index });
+            }
+        }
+
+        let computed = self
+            .computed_root::<A>()
+            .expect("lengths and index are checked");
+        if computed != *root {
+            return Err(ProofError::RootMismatch {
+                expected: root.clone(),
+                computed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the position of the first of the candidate `roots` the proof
+    /// folds to, e.g. to tell the fork a proof belongs to.
+    ///
+    /// The lemma is folded once for all the candidates. Same as
+    /// [`Proof::validate_detailed`], the root the lemma carries is not
+    /// checked, only the one it folds to.
+    pub fn matches_any<A: Algorithm<T>>(&self, roots: &[T]) -> Option<usize> {
+        let computed = self.computed_root::<A>()?;
+        roots.iter().position(|root| *root == computed)
+    }
+
+    /// Verifies MT inclusion proof against `root` hashing with the caller
+    /// supplied `algo` instance, which is reset before every hash.
+    ///
+    /// Unlike [`Proof::validate`] the algorithm is not default constructed,
+    /// so keyed or personalized algorithms which restore their initial
+    /// state on [`Algorithm::reset`] validate their own proofs.
+    pub fn validate_with<A: Algorithm<T>>(&self, algo: &mut A, root: &T) -> bool {
+        match self.fold(algo, |a: &mut A| a.reset()) {
+            Some(h) => h == *root && h == self.root(),
+            None => false,
+        }
+    }
+
+    /// Verifies MT inclusion proof of `raw_leaf` bytes against `root`.
+    ///
+    /// The leaf is hashed here as [`MerkleTree::from_byte_slices`] does,
+    /// `leaf(hash(raw_leaf))`, and required to be the proof item, so an
+    /// interior node can not be passed off as a leaf by the caller.
+    ///
+    /// [`MerkleTree::from_byte_slices`]: crate::merkle::MerkleTree::from_byte_slices
+    pub fn validate_leaf<A: Algorithm<T>>(&self, root: &T, raw_leaf: &[u8]) -> bool {
+        let mut a = A::default();
+        a.write(raw_leaf);
+        let h = a.hash();
+        a.reset();
+        let leaf = a.leaf(h);
+
+        leaf == self.item() && self.root() == *root && self.validate::<A>()
+    }
+
+    /// Verifies MT inclusion proof of a [`NodeOrder::Sorted`] tree.
+    ///
+    /// Children of every node are sorted as the lemma is folded, so no path
+    /// bits nor leaf index are needed and the path is ignored.
+    ///
+    /// [`NodeOrder::Sorted`]: crate::merkle::NodeOrder::Sorted
+    pub fn validate_sorted<A: Algorithm<T>>(&self) -> bool {
         let size = self.lemma.len();
         if size < 2 {
             return false;
@@ -48,23 +259,498 @@ impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
 
         for i in 1..size - 1 {
             a.reset();
-            h = if self.path[i - 1] {
-                a.node(h, self.lemma[i].clone())
-            } else {
-                a.node(self.lemma[i].clone(), h)
-            };
+            h = NodeOrder::Sorted.node(&mut a, h, self.lemma[i].clone());
         }
 
         h == self.root()
     }
 
+    /// Verifies MT inclusion proof against `expected_root`, comparing the
+    /// roots in constant time.
+    ///
+    /// Only the final comparison is constant time, so that the time it takes
+    /// does not tell how many leading bytes of a guessed root are right.
+    /// Hashing itself is not, and neither are the length of the proof and
+    /// the lemma it carries secret.
+    pub fn validate_ct<A: Algorithm<T>>(&self, expected_root: &T) -> bool {
+        match self.computed_root::<A>() {
+            Some(h) => ct_eq(h.as_ref(), expected_root.as_ref()),
+            None => false,
+        }
+    }
+
+    /// Returns a borrowed view of this proof, bound to the same leaf index.
+    pub fn as_ref(&self) -> ProofRef<'_, T> {
+        ProofRef {
+            lemma: &self.lemma,
+            path: &self.path,
+            index: self.index,
+            promote_leafs: self.promote_leafs,
+        }
+    }
+
+    fn fold<A: Algorithm<T>, R: Fn(&mut A)>(&self, a: &mut A, reset: R) -> Option<T> {
+        self.as_ref().fold(a, reset)
+    }
+
     /// Returns the path of this proof.
+    ///
+    /// There is one direction bit per level from the leaf up. `true` means
+    /// the proven node is the left child, so the sibling `lemma[i + 1]` is
+    /// on the right and the parent is `node(h, lemma[i + 1])`; `false` means
+    /// the sibling is on the left and the parent is `node(lemma[i + 1], h)`.
+    ///
+    /// Proofs of [`NodeOrder::Sorted`] trees have an empty path.
+    ///
+    /// [`NodeOrder::Sorted`]: crate::merkle::NodeOrder::Sorted
     pub fn path(&self) -> &[bool] {
         &self.path
     }
 
     /// Returns the lemma of this proof.
+    ///
+    /// First element is the proven leaf, last element is the root and the
+    /// siblings are in between from the leaf level up.
     pub fn lemma(&self) -> &[T] {
         &self.lemma
     }
+
+    /// Returns the positions of the proven node at each level of the path,
+    /// from the leaf up, decoded from the path bits.
+    ///
+    /// First element is the leaf index. Positions are exact for trees which
+    /// pad every odd level, [`PaddingPolicy::PromoteOdd`] trees have no
+    /// path bits for the levels the node is promoted through.
+    ///
+    /// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+    pub fn indices(&self) -> Vec<usize> {
+        let index = self.path_index();
+        (0..self.path.len()).map(|level| index >> level).collect()
+    }
+}
+
+/// Side of a child within its parent node.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Side {
+    /// Left child, the first one hashed.
+    Left,
+    /// Right child, the second one hashed.
+    Right,
+}
+
+/// Element of the authentication path of a leaf: a sibling hash and its
+/// side, see [`MerkleTree::witness`].
+///
+/// [`MerkleTree::witness`]: crate::merkle::MerkleTree::witness
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PathElement<T> {
+    /// Sibling hash.
+    pub sibling: T,
+    /// Side of the sibling, the proven node is on the other one.
+    pub side: Side,
+}
+
+/// Merkle tree inclusion proof over a borrowed lemma and path.
+///
+/// Same as [`Proof`], but validation does not need the proof to own its
+/// hashes, so proofs can be checked right from the buffers they are stored
+/// or received in without allocating. Views of a proof recording its leaf
+/// index check the path against it as the proof does, see [`Proof::index`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ProofRef<'a, T: Eq + Clone + AsRef<[u8]>> {
+    lemma: &'a [T],
+    path: &'a [bool],
+    index: Option<usize>,
+    promote_leafs: Option<usize>,
+}
+
+impl<'a, T: Eq + Clone + AsRef<[u8]>> ProofRef<'a, T> {
+    /// Creates new borrowed MT inclusion proof, see [`Proof::new`].
+    pub fn new(hash: &'a [T], path: &'a [bool]) -> ProofRef<'a, T> {
+        assert!(hash.len() > 1);
+        assert_eq!(hash.len() - 2, path.len());
+        ProofRef {
+            lemma: hash,
+            path,
+            index: None,
+            promote_leafs: None,
+        }
+    }
+
+    /// Records the leaf `index` this proof is for, see [`Proof::with_index`].
+    pub fn with_index(mut self, index: usize) -> ProofRef<'a, T> {
+        self.index = Some(index);
+        self
+    }
+
+    /// Returns the recorded leaf index, see [`Proof::index`].
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Return proof target leaf
+    pub fn item(&self) -> &'a T {
+        self.lemma.first().unwrap()
+    }
+
+    /// Return tree root
+    pub fn root(&self) -> &'a T {
+        self.lemma.last().unwrap()
+    }
+
+    /// Returns the lemma of this proof, see [`Proof::lemma`].
+    pub fn lemma(&self) -> &'a [T] {
+        self.lemma
+    }
+
+    /// Returns the path of this proof, see [`Proof::path`].
+    pub fn path(&self) -> &'a [bool] {
+        self.path
+    }
+
+    /// Verifies MT inclusion proof
+    pub fn validate<A: Algorithm<T>>(&self) -> bool {
+        match self.fold(&mut A::default(), |a: &mut A| a.reset()) {
+            Some(h) => h == *self.root(),
+            None => false,
+        }
+    }
+
+    /// Returns an owned copy of this proof, bound to the same leaf index.
+    pub fn to_proof(&self) -> Proof<T> {
+        Proof {
+            lemma: self.lemma.to_vec(),
+            path: self.path.to_vec(),
+            index: self.index,
+            promote_leafs: self.promote_leafs,
+        }
+    }
+
+    /// Folds the item with the lemma siblings up to the root hashing with
+    /// `a`, which `reset` brings back to the initial state before every hash.
+    ///
+    /// Returns `None` if the path does not have a bit for every sibling, or
+    /// is not the one of the recorded leaf index.
+    fn fold<A: Algorithm<T>, R: Fn(&mut A)>(&self, a: &mut A, reset: R) -> Option<T> {
+        let size = self.lemma.len();
+        if size < 2 || self.path.len() != size - 2 {
+            return None;
+        }
+        if let Some(index) = self.index {
+            if !path_matches_index(self.path, index, self.promote_leafs) {
+                return None;
+            }
+        }
+
+        let mut h = self.item().clone();
+
+        for i in 1..size - 1 {
+            reset(a);
+            h = if self.path[i - 1] {
+                a.node(h, self.lemma[i].clone())
+            } else {
+                a.node(self.lemma[i].clone(), h)
+            };
+        }
+
+        Some(h)
+    }
+}
+
+impl<'a, T: Eq + Clone + AsRef<[u8]>> From<&'a Proof<T>> for ProofRef<'a, T> {
+    fn from(p: &'a Proof<T>) -> ProofRef<'a, T> {
+        p.as_ref()
+    }
+}
+
+/// Checks that proof `path` is the one of leaf `index`, bit for bit.
+///
+/// Nodes of [`PaddingPolicy::PromoteOdd`] trees have no path bit for the
+/// levels they are promoted through. Those levels are only known from the
+/// number of leafs of the tree, `promote_leafs`, which is `None` for trees
+/// padding every odd level.
+///
+/// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+pub(crate) fn path_matches_index(
+    path: &[bool],
+    index: usize,
+    promote_leafs: Option<usize>,
+) -> bool {
+    let mut bits = path.iter();
+    let mut j = index;
+    match promote_leafs {
+        None => {
+            for &left in bits {
+                if left != (j & 1 == 0) {
+                    return false;
+                }
+                j >>= 1;
+            }
+            j == 0
+        }
+        Some(mut width) => {
+            if index >= width {
+                return false;
+            }
+            while width > 1 {
+                // last node of an odd level is promoted
+                if width & 1 == 0 || j != width - 1 {
+                    match bits.next() {
+                        Some(&left) if left == (j & 1 == 0) => {}
+                        _ => return false,
+                    }
+                }
+                width = (width + 1) >> 1;
+                j >>= 1;
+            }
+            bits.next().is_none()
+        }
+    }
+}
+
+/// Compares byte slices of equal length without an early exit.
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let acc = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    core::hint::black_box(acc) == 0
+}
+
+/// Merkle tree inclusion proof with runs of default siblings collapsed.
+///
+/// Proofs of sparse trees, where most of the subtrees are empty, carry long
+/// runs of the same padding hash. Compact proof keeps only the run length of
+/// every run of `default` siblings:
+///
+/// ```text
+/// [ item h1x default default default h5y ... root ] -> [ item h1x (3) h5y ... root ]
+/// ```
+///
+/// Compact proof is created by [`Proof::compact`] and has to be expanded
+/// back with [`CompactProof::expand`] before validation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompactProof<T: Eq + Clone + AsRef<[u8]>> {
+    item: T,
+    siblings: Vec<Sibling<T>>,
+    root: T,
+    path: Vec<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    index: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    promote_leafs: Option<usize>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Sibling<T> {
+    Node(T),
+    Default(usize),
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
+    /// Collapses runs of `default` siblings of this proof.
+    pub fn compact(&self, default: &T) -> CompactProof<T> {
+        let size = self.lemma.len();
+        let mut siblings: Vec<Sibling<T>> = Vec::new();
+        for h in &self.lemma[1..size - 1] {
+            match siblings.last_mut() {
+                Some(Sibling::Default(n)) if h == default => *n += 1,
+                _ if h == default => siblings.push(Sibling::Default(1)),
+                _ => siblings.push(Sibling::Node(h.clone())),
+            }
+        }
+
+        CompactProof {
+            item: self.item(),
+            siblings,
+            root: self.root(),
+            path: self.path.clone(),
+            index: self.index,
+            promote_leafs: self.promote_leafs,
+        }
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> CompactProof<T> {
+    /// Restores the full proof, `default` must be the one the proof was
+    /// compacted with.
+    ///
+    /// Compact proofs are decoded from untrusted input, so the runs are
+    /// checked before any sibling is restored. Fails with
+    /// [`MerkleError::InvalidEncoding`] unless the siblings are one per path
+    /// bit, or at most `usize::BITS` for the proofs of [`NodeOrder::Sorted`]
+    /// trees, which have no path bits. The proof is not validated.
+    pub fn expand(&self, default: &T) -> Result<Proof<T>, MerkleError> {
+        let mut siblings: usize = 0;
+        for s in &self.siblings {
+            let n = match *s {
+                Sibling::Node(_) => 1,
+                Sibling::Default(0) => return Err(MerkleError::InvalidEncoding),
+                Sibling::Default(n) => n,
+            };
+            siblings = siblings
+                .checked_add(n)
+                .ok_or(MerkleError::InvalidEncoding)?;
+        }
+        // proofs of sorted trees have no path bits
+        let sorted = self.path.is_empty() && siblings > 0;
+        if (sorted && siblings > usize::BITS as usize) || (!sorted && siblings != self.path.len()) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+
+        let mut lemma: Vec<T> = Vec::with_capacity(siblings + 2);
+        lemma.push(self.item.clone());
+        for s in &self.siblings {
+            match *s {
+                Sibling::Node(ref h) => lemma.push(h.clone()),
+                Sibling::Default(n) => lemma.extend((0..n).map(|_| default.clone())),
+            }
+        }
+        lemma.push(self.root.clone());
+        Ok(Proof {
+            lemma,
+            path: self.path.clone(),
+            index: self.index,
+            promote_leafs: self.promote_leafs,
+        })
+    }
+
+    /// Returns number of hashes stored in this proof, item and root included.
+    pub fn hashes(&self) -> usize {
+        self.siblings
+            .iter()
+            .filter(|s| matches!(s, Sibling::Node(_)))
+            .count()
+            + 2
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
+    /// Encodes this proof into the binary wire format.
+    ///
+    /// Layout, integers are unsigned LEB128 varints:
+    ///
+    /// ```text
+    /// hashes | path bits | path, ceil(bits / 8) bytes | lemma, hashes * size bytes
+    /// ```
+    ///
+    /// Path bit `i` is the bit `i % 8` (least significant first) of path
+    /// byte `i / 8`, unused bits are zero. There are `hashes - 2` path bits,
+    /// or none for [`NodeOrder::Sorted`] proofs. Lemma hashes are stored as
+    /// is, one after another, in the lemma order. The leaf index and the
+    /// leaf count of [`Proof::with_promote_odd`] are not encoded, decoded
+    /// proofs have none.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let size = self.lemma.first().map_or(0, |h| h.as_ref().len());
+        let mut bytes = Vec::with_capacity(20 + self.path.len() / 8 + self.lemma.len() * size);
+
+        put_varint(&mut bytes, self.lemma.len());
+        put_varint(&mut bytes, self.path.len());
+
+        let mut packed = vec![0u8; self.path.len().div_ceil(8)];
+        for (i, &left) in self.path.iter().enumerate() {
+            if left {
+                packed[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bytes.extend_from_slice(&packed);
+
+        for h in &self.lemma {
+            bytes.extend_from_slice(h.as_ref());
+        }
+        bytes
+    }
+
+    /// Decodes a proof encoded by [`Proof::to_bytes`], hashes are of the
+    /// size of `T::default()`.
+    ///
+    /// Fails on truncated input, trailing bytes, non zero unused path bits,
+    /// inconsistent lengths and hashes of no bytes. The proof is not
+    /// validated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Proof<T>, MerkleError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let mut rest = bytes;
+        let hashes = get_varint(&mut rest)?;
+        let bits = get_varint(&mut rest)?;
+        if hashes < 2 || (bits != 0 && bits != hashes - 2) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+
+        let packed = take(&mut rest, bits.div_ceil(8))?;
+        if bits % 8 != 0 && packed[bits / 8] >> (bits % 8) != 0 {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let path: Vec<bool> = (0..bits)
+            .map(|i| packed[i / 8] & (1 << (i % 8)) != 0)
+            .collect();
+
+        let size = T::default().as_ref().len();
+        if size == 0 {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let total = hashes
+            .checked_mul(size)
+            .ok_or(MerkleError::InvalidEncoding)?;
+        if rest.len() != total {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let lemma: Vec<T> = rest
+            .chunks_exact(size)
+            .map(|c| {
+                let mut h = T::default();
+                h.as_mut().copy_from_slice(c);
+                h
+            })
+            .collect();
+
+        Ok(Proof {
+            lemma,
+            path,
+            index: None,
+            promote_leafs: None,
+        })
+    }
+}
+
+/// Appends `n` as unsigned LEB128.
+fn put_varint(bytes: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+/// Reads unsigned LEB128 from the front of `bytes`, rejecting overlong and
+/// overflowing encodings.
+fn get_varint(bytes: &mut &[u8]) -> Result<usize, MerkleError> {
+    let mut n: usize = 0;
+    for shift in (0..usize::BITS).step_by(7) {
+        let b = *take(bytes, 1)?.first().unwrap();
+        let v = usize::from(b & 0x7f);
+        if v << shift >> shift != v || (shift > 0 && b == 0) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        n |= v << shift;
+        if b & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(MerkleError::InvalidEncoding)
+}
+
+/// Splits `n` bytes off the front of `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], MerkleError> {
+    if bytes.len() < n {
+        return Err(MerkleError::InvalidEncoding);
+    }
+    let (head, tail) = bytes.split_at(n);
+    *bytes = tail;
+    Ok(head)
 }
@@ -0,0 +1,117 @@
+extern crate alloc;
+
+use crate::hash::Algorithm;
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Merkle tree inclusion proof for a contiguous range of leafs.
+///
+/// Interior nodes of the range are computed from the proven leafs, so the
+/// proof carries at most the two boundary siblings per level, `O(log n)`
+/// hashes for a range of any length.
+///
+/// Nodes layout is the order in which validation consumes them: level by
+/// level from the leafs up, the left boundary sibling before the right one.
+///
+/// ```text
+/// [ level 1 left, level 1 right ... level N left, level N right ]
+/// ```
+///
+/// Right boundary node without a sibling (promoted by
+/// [`PaddingPolicy::PromoteOdd`]) is marked with `None`.
+///
+/// [`PaddingPolicy::PromoteOdd`]: crate::merkle::PaddingPolicy::PromoteOdd
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RangeProof<T: Eq + Clone + AsRef<[u8]>> {
+    range: Range<usize>,
+    nodes: Vec<Option<T>>,
+    height: usize,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> RangeProof<T> {
+    /// Creates new MT range inclusion proof.
+    ///
+    /// `range` is the range of the proven leafs, `nodes` the boundary
+    /// sibling hashes in validation order and `height` the height of the
+    /// tree.
+    pub fn new(range: Range<usize>, nodes: Vec<Option<T>>, height: usize) -> RangeProof<T> {
+        assert!(range.start <= range.end);
+        RangeProof {
+            range,
+            nodes,
+            height,
+        }
+    }
+
+    /// Verifies that `leaves` are exactly the leafs of the range in the tree
+    /// of `root`.
+    ///
+    /// `leaves` are the leaf nodes as stored in the tree, see
+    /// [`Proof::item`](crate::proof::Proof::item), in the order of the
+    /// range. An empty proof validates against any root for an empty set of
+    /// leaves.
+    pub fn validate<A: Algorithm<T>>(&self, root: &T, leaves: &[T]) -> bool {
+        if leaves.len() != self.range.len() {
+            return false;
+        }
+        if leaves.is_empty() {
+            return true;
+        }
+
+        let mut a = A::default();
+        let mut nodes = self.nodes.iter();
+        let mut layer = leaves.to_vec();
+        let (mut lo, mut hi) = (self.range.start, self.range.end);
+
+        for _ in 1..self.height {
+            if lo & 1 == 1 {
+                match nodes.next() {
+                    Some(Some(n)) => layer.insert(0, n.clone()),
+                    _ => return false,
+                }
+                lo -= 1;
+            }
+            // a lone last node is promoted
+            let promoted = if hi & 1 == 1 {
+                match nodes.next() {
+                    Some(Some(n)) => {
+                        layer.push(n.clone());
+                        None
+                    }
+                    Some(None) => layer.pop(),
+                    None => return false,
+                }
+            } else {
+                None
+            };
+
+            let mut next: Vec<T> = layer
+                .chunks_exact(2)
+                .map(|pair| {
+                    a.reset();
+                    a.node(pair[0].clone(), pair[1].clone())
+                })
+                .collect();
+            next.extend(promoted);
+
+            layer = next;
+            lo >>= 1;
+            hi = (hi + 1) >> 1;
+        }
+
+        nodes.next().is_none() && layer.len() == 1 && lo == 0 && layer[0] == *root
+    }
+
+    /// Returns the range of the leafs this proof was generated for.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the boundary sibling hashes of this proof.
+    pub fn nodes(&self) -> &[Option<T>] {
+        &self.nodes
+    }
+}
@@ -0,0 +1,124 @@
+//! Versioned construction rules of the tree root.
+//!
+//! The root of a tree depends on the algorithm and on the rules the tree
+//! is built by: the odd levels padding, the children order and the leaf
+//! and node domain separation. [`SchemeId`] names these rules, so that a
+//! persisted root can be stored along with the rules it is computed by.
+//!
+//! Roots are stable within a scheme id: the same leafs hashed by the same
+//! algorithm under equal scheme ids have the same root in any version of
+//! the crate. A change of the rules bumps [`SCHEME_VERSION`], a change of
+//! the [`Hashable`] encoding of the leaf data bumps [`ENCODING_VERSION`].
+//!
+//! With the `scheme_v1` feature the crate builds the trees of 0.4.0: the
+//! leaf data is hashed by the encoding version 1, and the crate fails to
+//! build unless it constructs the trees of scheme version 1, so a
+//! dependency upgrade can not silently change the pinned roots.
+//!
+//! [`Hashable`]: crate::hash::Hashable
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::merkle::{NodeOrder, PaddingPolicy};
+
+/// Version of the construction rules of this crate.
+pub const SCHEME_VERSION: u32 = 1;
+
+#[cfg(feature = "scheme_v1")]
+const _: () = assert!(SCHEME_VERSION == 1, "scheme_v1 is pinned, but not built");
+
+/// Version of the [`Hashable`] encoding of the leaf data of this crate.
+///
+/// 1. Encoding of 0.4.0, built with the `scheme_v1` feature: integers are
+///    fed in the native byte order, `usize` of the target width, `Vec<T>`
+///    with no length prefix, so `Vec<u8>` as the raw bytes, and `str` as
+///    the raw bytes.
+/// 2. Integers are fed as little-endian bytes, `usize` and `isize` as 64
+///    bit integers, `Vec<T>` of any `T` prefixed with its length as the
+///    slices are, `str` terminated by `0xff`.
+///
+/// [`Hashable`]: crate::hash::Hashable
+#[cfg(not(feature = "scheme_v1"))]
+pub const ENCODING_VERSION: u32 = 2;
+
+/// Version of the [`Hashable`] encoding of the leaf data of this crate, the
+/// one of 0.4.0 pinned by the `scheme_v1` feature.
+///
+/// [`Hashable`]: crate::hash::Hashable
+#[cfg(feature = "scheme_v1")]
+pub const ENCODING_VERSION: u32 = 1;
+
+/// Returns the version of the construction rules of this crate, see
+/// [`SCHEME_VERSION`].
+pub const fn scheme_version() -> u32 {
+    SCHEME_VERSION
+}
+
+/// Construction rules of a tree, see [`MerkleTree::scheme`].
+///
+/// Only the rules of the crate are described, the algorithm is not, and
+/// neither are its [`Algorithm::leaf`] and [`Algorithm::node`] prefixes,
+/// which an algorithm may override.
+///
+/// [`MerkleTree::scheme`]: crate::merkle::MerkleTree::scheme
+/// [`Algorithm::leaf`]: crate::hash::Algorithm::leaf
+/// [`Algorithm::node`]: crate::hash::Algorithm::node
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SchemeId {
+    version: u32,
+    encoding: u32,
+    policy: PaddingPolicy,
+    order: NodeOrder,
+    domain_separation: bool,
+}
+
+impl SchemeId {
+    /// Creates the id of the current version rules.
+    pub fn new(policy: PaddingPolicy, order: NodeOrder, domain_separation: bool) -> SchemeId {
+        SchemeId {
+            version: SCHEME_VERSION,
+            encoding: ENCODING_VERSION,
+            policy,
+            order,
+            domain_separation,
+        }
+    }
+
+    /// Returns the version of the rules.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the version of the leaf data encoding, see
+    /// [`ENCODING_VERSION`].
+    pub fn encoding(&self) -> u32 {
+        self.encoding
+    }
+
+    /// Returns the padding policy of odd levels.
+    pub fn policy(&self) -> PaddingPolicy {
+        self.policy
+    }
+
+    /// Returns the order of the children in the node hash.
+    pub fn order(&self) -> NodeOrder {
+        self.order
+    }
+
+    /// Returns `true` if leafs and nodes are hashed with the domain
+    /// separation prefixes.
+    pub fn domain_separation(&self) -> bool {
+        self.domain_separation
+    }
+}
+
+impl Default for SchemeId {
+    /// Returns the id of the trees [`MerkleTree::from_data`] builds.
+    ///
+    /// [`MerkleTree::from_data`]: crate::merkle::MerkleTree::from_data
+    fn default() -> SchemeId {
+        SchemeId::new(PaddingPolicy::default(), NodeOrder::default(), true)
+    }
+}
@@ -0,0 +1,240 @@
+extern crate alloc;
+
+use crate::hash::{Algorithm, HashContext, Hashable};
+use crate::proof::Proof;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+/// Sparse merkle tree, an authenticated key-value map.
+///
+/// Every possible key of `K` has its own leaf, so the tree is of depth
+/// equal to the key bit-length and the position of a leaf is its key, most
+/// significant bit first from the root:
+///
+/// ```text
+///          root
+///         /    \
+///       0..     1..
+///      /  \    /  \
+///    00.. 01.. 10.. 11..
+/// ```
+///
+/// Keys are hashes as well, so `K` is both the key and the hash item type.
+/// Empty leaf is `K::default()`, and empty subtrees collapse to precomputed
+/// default hashes, so only the nodes on the paths of present keys are
+/// stored.
+///
+/// Leaf of a present key is `leaf(hash(value))`.
+#[derive(Debug, Clone)]
+pub struct SparseMerkleTree<K, V, A>
+where
+    K: Ord + Clone + Default + AsRef<[u8]> + AsMut<[u8]>,
+    V: Hashable<A>,
+    A: Algorithm<K>,
+{
+    values: BTreeMap<K, V>,
+    nodes: BTreeMap<(usize, K), K>,
+    defaults: Vec<K>,
+    _a: PhantomData<fn() -> A>,
+}
+
+impl<K, V, A> SparseMerkleTree<K, V, A>
+where
+    K: Ord + Clone + Default + AsRef<[u8]> + AsMut<[u8]>,
+    V: Hashable<A>,
+    A: Algorithm<K>,
+{
+    /// Creates new empty sparse merkle tree.
+    pub fn new() -> SparseMerkleTree<K, V, A> {
+        let depth = 8 * K::default().as_ref().len();
+
+        let mut a = A::default();
+        let mut defaults = Vec::with_capacity(depth + 1);
+        defaults.push(K::default());
+        for h in 0..depth {
+            a.reset();
+            let d = a.node(defaults[h].clone(), defaults[h].clone());
+            defaults.push(d);
+        }
+
+        SparseMerkleTree {
+            values: BTreeMap::new(),
+            nodes: BTreeMap::new(),
+            defaults,
+            _a: PhantomData,
+        }
+    }
+
+    /// Returns depth of the tree, the bit-length of the keys.
+    pub fn depth(&self) -> usize {
+        self.defaults.len() - 1
+    }
+
+    /// Returns hash of the empty subtree of `height` (leaf is of height 0).
+    pub fn default_hash(&self, height: usize) -> K {
+        self.defaults[height].clone()
+    }
+
+    /// Returns number of present keys.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the tree contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns leaf hash of `value`.
+    pub fn leaf(value: &V) -> K {
+        let mut a = A::default();
+        value.hash_with_context(&mut a, HashContext::Leaf);
+        let h = a.hash();
+        a.reset();
+        a.leaf(h)
+    }
+
+    /// Inserts `value` at `key`, returning the previous value.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let leaf = Self::leaf(&value);
+        self.update(&key, leaf);
+        self.values.insert(key, value)
+    }
+
+    /// Returns value at `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.values.get(key)
+    }
+
+    /// Removes value at `key`, returning it.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.values.remove(key)?;
+        self.update(key, K::default());
+        Some(value)
+    }
+
+    /// Returns merkle root
+    pub fn root(&self) -> K {
+        self.node(self.depth(), &K::default())
+    }
+
+    /// Generate inclusion proof for `key`, which is a non-inclusion proof
+    /// if the key is absent.
+    ///
+    /// Proof is of [`depth`] levels. Item of an inclusion proof is the leaf
+    /// of the value, see [`leaf`], item of a non-inclusion proof is the
+    /// empty leaf `K::default()`.
+    ///
+    /// [`depth`]: SparseMerkleTree::depth
+    /// [`leaf`]: SparseMerkleTree::leaf
+    pub fn gen_proof(&self, key: &K) -> Proof<K> {
+        let depth = self.depth();
+        let mut lemma: Vec<K> = Vec::with_capacity(depth + 2); // path + item + root
+        let mut path: Vec<bool> = Vec::with_capacity(depth);
+
+        lemma.push(self.node(0, key));
+        for h in 0..depth {
+            let bit = depth - 1 - h;
+            let mut sibling = prefix(key, bit + 1);
+            flip(&mut sibling, bit);
+            lemma.push(self.node(h, &sibling));
+            path.push(!get_bit(key, bit));
+        }
+
+        // root is final
+        lemma.push(self.root());
+        Proof::new(lemma, path)
+    }
+
+    /// Verifies that `proof` proves `value` at `key` of the tree of `root`,
+    /// or that `key` is absent if `value` is `None`.
+    pub fn verify_proof(root: &K, proof: &Proof<K>, key: &K, value: Option<&V>) -> bool {
+        let item = match value {
+            Some(v) => Self::leaf(v),
+            None => K::default(),
+        };
+
+        let depth = 8 * key.as_ref().len();
+        let path = proof.path();
+        path.len() == depth
+            && (0..depth).all(|h| path[h] != get_bit(key, depth - 1 - h))
+            && proof.item() == item
+            && proof.root() == *root
+            && proof.validate::<A>()
+    }
+
+    /// Returns node of `height` on the path to `key`.
+    fn node(&self, height: usize, key: &K) -> K {
+        let p = prefix(key, self.depth() - height);
+        match self.nodes.get(&(height, p)) {
+            Some(h) => h.clone(),
+            None => self.defaults[height].clone(),
+        }
+    }
+
+    /// Sets leaf of `key` and rehashes its path up to the root.
+    fn update(&mut self, key: &K, leaf: K) {
+        let depth = self.depth();
+        let mut a = A::default();
+
+        let mut h = leaf;
+        for height in 0..=depth {
+            let p = prefix(key, depth - height);
+            if h == self.defaults[height] {
+                self.nodes.remove(&(height, p.clone()));
+            } else {
+                self.nodes.insert((height, p.clone()), h.clone());
+            }
+            if height == depth {
+                break;
+            }
+
+            let bit = depth - 1 - height;
+            let mut sibling = p;
+            flip(&mut sibling, bit);
+            let s = self.node(height, &sibling);
+
+            a.reset();
+            h = if get_bit(key, bit) {
+                a.node(s, h)
+            } else {
+                a.node(h, s)
+            };
+        }
+    }
+}
+
+impl<K, V, A> Default for SparseMerkleTree<K, V, A>
+where
+    K: Ord + Clone + Default + AsRef<[u8]> + AsMut<[u8]>,
+    V: Hashable<A>,
+    A: Algorithm<K>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns bit `i` of `key`, most significant bit first.
+fn get_bit<K: AsRef<[u8]>>(key: &K, i: usize) -> bool {
+    key.as_ref()[i / 8] & (0x80 >> (i % 8)) != 0
+}
+
+/// Flips bit `i` of `key`, most significant bit first.
+fn flip<K: AsMut<[u8]>>(key: &mut K, i: usize) {
+    key.as_mut()[i / 8] ^= 0x80 >> (i % 8);
+}
+
+/// Returns first `bits` bits of `key`, the rest zeroed.
+fn prefix<K: Clone + AsMut<[u8]>>(key: &K, bits: usize) -> K {
+    let mut p = key.clone();
+    for (i, b) in p.as_mut().iter_mut().enumerate() {
+        if 8 * i >= bits {
+            *b = 0;
+        } else if 8 * i + 8 > bits {
+            *b &= 0xff << (8 * i + 8 - bits);
+        }
+    }
+    p
+}
@@ -0,0 +1,211 @@
+extern crate alloc;
+#[cfg(feature = "mmap")]
+extern crate std;
+
+use alloc::vec::Vec;
+#[cfg(feature = "mmap")]
+use core::fmt;
+#[cfg(feature = "mmap")]
+use core::marker::PhantomData;
+#[cfg(feature = "mmap")]
+use memmap2::MmapMut;
+#[cfg(feature = "mmap")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "mmap")]
+use std::io;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+
+/// Backend storage of the nodes of a [`MerkleTree`].
+///
+/// Nodes are stored in the linear layout the tree describes, the store only
+/// has to keep them by position. Items are returned by value, so the nodes
+/// do not have to live in memory.
+///
+/// [`MerkleTree`]: crate::merkle::MerkleTree
+pub trait Store<T> {
+    /// Returns node at position `i`.
+    ///
+    /// Panics if `i` is out of bounds.
+    fn get(&self, i: usize) -> T;
+
+    /// Replaces node at position `i`.
+    ///
+    /// Panics if `i` is out of bounds.
+    fn set(&mut self, i: usize, item: T);
+
+    /// Returns number of stored nodes.
+    fn len(&self) -> usize;
+
+    /// Appends a node.
+    fn push(&mut self, item: T);
+
+    /// Returns `true` if the store holds no nodes.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reserves space for at least `additional` more nodes. No-op by default.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+}
+
+/// In memory store, the nodes vec [`MerkleTree`] uses by default.
+///
+/// [`MerkleTree`]: crate::merkle::MerkleTree
+pub type VecStore<T> = Vec<T>;
+
+impl<T: Clone> Store<T> for Vec<T> {
+    #[inline]
+    fn get(&self, i: usize) -> T {
+        self[i].clone()
+    }
+
+    #[inline]
+    fn set(&mut self, i: usize, item: T) {
+        self[i] = item;
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    #[inline]
+    fn push(&mut self, item: T) {
+        Vec::push(self, item)
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve_exact(self, additional)
+    }
+}
+
+/// Number of nodes [`DiskStore`] allocates space for at least.
+#[cfg(feature = "mmap")]
+const DISK_MIN_CAPACITY: usize = 64;
+
+/// File backed store, which maps the nodes file into memory, so trees larger
+/// than RAM spill to disk.
+///
+/// Nodes are stored as their raw bytes one after another, `T` must be of the
+/// fixed size of `T::default()`. File grows by doubling, its tail beyond
+/// [`Store::len`] is unused.
+///
+/// Panics on IO errors growing the file, as [`Store`] is infallible.
+#[cfg(feature = "mmap")]
+pub struct DiskStore<T> {
+    file: File,
+    map: MmapMut,
+    len: usize,
+    capacity: usize,
+    size: usize,
+    _t: PhantomData<T>,
+}
+
+#[cfg(feature = "mmap")]
+impl<T: Default + AsRef<[u8]> + AsMut<[u8]>> DiskStore<T> {
+    /// Creates new empty store at `path`, truncating the file if it exists.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<DiskStore<T>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Self::from_file(file)
+    }
+
+    /// Creates new empty store in `file`, opened for reading and writing.
+    /// Previous content of the file is discarded.
+    pub fn from_file(file: File) -> io::Result<DiskStore<T>> {
+        let size = T::default().as_ref().len();
+        assert!(size > 0, "zero sized items");
+
+        file.set_len((DISK_MIN_CAPACITY * size) as u64)?;
+        let map = map(&file)?;
+        Ok(DiskStore {
+            file,
+            map,
+            len: 0,
+            capacity: DISK_MIN_CAPACITY,
+            size,
+            _t: PhantomData,
+        })
+    }
+
+    /// Flushes the stored nodes to the file.
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+
+    /// Grows the file and the mapping to `capacity` nodes.
+    fn grow(&mut self, capacity: usize) {
+        self.map.flush().expect("failed to flush disk store");
+        self.file
+            .set_len((capacity * self.size) as u64)
+            .expect("failed to grow disk store");
+        self.map = map(&self.file).expect("failed to map disk store");
+        self.capacity = capacity;
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<T: Default + AsRef<[u8]> + AsMut<[u8]>> Store<T> for DiskStore<T> {
+    fn get(&self, i: usize) -> T {
+        assert!(i < self.len); // i in [0 .. self.len)
+
+        let mut item = T::default();
+        item.as_mut()
+            .copy_from_slice(&self.map[i * self.size..(i + 1) * self.size]);
+        item
+    }
+
+    fn set(&mut self, i: usize, item: T) {
+        assert!(i < self.len); // i in [0 .. self.len)
+
+        self.map[i * self.size..(i + 1) * self.size].copy_from_slice(item.as_ref());
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn push(&mut self, item: T) {
+        if self.len == self.capacity {
+            self.grow(2 * self.capacity);
+        }
+        self.len += 1;
+        self.set(self.len - 1, item);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        if self.len + additional > self.capacity {
+            self.grow(self.len + additional);
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<T> fmt::Debug for DiskStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiskStore")
+            .field("file", &self.file)
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// Maps the whole `file` for reading and writing.
+#[cfg(feature = "mmap")]
+#[allow(unsafe_code)]
+fn map(file: &File) -> io::Result<MmapMut> {
+    // SAFETY: the file is owned by the store and is not resized or written
+    // through other handles while mapped, except by the store itself, which
+    // remaps it after every resize.
+    unsafe { MmapMut::map_mut(file) }
+}
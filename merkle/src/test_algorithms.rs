@@ -0,0 +1,258 @@
+#![cfg(test)]
+
+extern crate std;
+
+use std::string::String;
+
+#[allow(dead_code)]
+fn hex(h: &[u8]) -> String {
+    h.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(feature = "blake2b")]
+mod blake2b {
+    use super::hex;
+    use crate::algorithms::Blake2bAlgorithm;
+    use crate::hash::Algorithm;
+    use crate::merkle::MerkleTree;
+    use core::hash::Hasher;
+
+    #[test]
+    fn test_blake2b_vector() {
+        let mut a = Blake2bAlgorithm::new();
+        a.write(b"abc");
+        // finish does not consume the state
+        assert_eq!(a.finish(), 0x7239_4263_3c81_ddbd);
+        assert_eq!(
+            hex(&a.hash()),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+
+        // hash resets the state
+        a.write(b"abc");
+        let h = a.hash();
+        a.write(b"x");
+        a.reset();
+        a.write(b"abc");
+        assert_eq!(a.hash(), h);
+    }
+
+    #[test]
+    fn test_blake2b_tree() {
+        let mt: MerkleTree<[u8; 32], Blake2bAlgorithm> = MerkleTree::from_data([b"a", b"b", b"c"]);
+        assert_eq!(
+            hex(&mt[0]),
+            "c9278a6b5aae2f854a7cd3f947719b82efcf067af05a8062f8edd8a66a743347"
+        );
+        assert_eq!(
+            hex(&mt.root()),
+            "1edc65d9b0aaa7525adf0f22cc14b42baf79343f186df14c6def16ea10271674"
+        );
+        assert!(mt.gen_proof(2).validate::<Blake2bAlgorithm>());
+    }
+}
+
+#[cfg(feature = "keccak")]
+mod keccak {
+    use super::hex;
+    use crate::algorithms::Keccak256Algorithm;
+    use crate::hash::Algorithm;
+    use crate::merkle::{MerkleTree, NodeOrder};
+    use core::hash::Hasher;
+
+    /// Hex decodes an address.
+    fn address(s: &str) -> [u8; 20] {
+        let mut a = [0u8; 20];
+        for (i, b) in a.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        a
+    }
+
+    #[test]
+    fn test_keccak256_vector() {
+        let mut a = Keccak256Algorithm::new();
+        assert_eq!(
+            hex(&a.hash()),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        a.write(b"abc");
+        // finish does not consume the state
+        assert_eq!(a.finish(), 0x4fa9_45ea_7a65_034e);
+        assert_eq!(
+            hex(&a.hash()),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
+
+    #[test]
+    fn test_keccak256_openzeppelin_fixture() {
+        // default Remix accounts, leafs are keccak256(abi.encodePacked(address))
+        let addresses = [
+            address("5B38Da6a701c568545dCfcB03FcB875f56beddC4"),
+            address("Ab8483F64d9C6d1EcF9b849Ae677dD3315835cb2"),
+            address("4B20993Bc481177ec7E8f571ceCaE8A9e22C02db"),
+            address("78731D3Ca6b7E34aC0F824c42a7cC18A495cabaB"),
+        ];
+        let mt: MerkleTree<[u8; 32], Keccak256Algorithm> =
+            MerkleTree::from_data_with_order(addresses, NodeOrder::Sorted);
+
+        assert_eq!(
+            hex(&mt[0]),
+            "5931b4ed56ace4c46b68524cb5bcbf4195f1bbaacbe5228fbd090546c88dd229"
+        );
+        assert_eq!(
+            mt.root_hex(),
+            "eeefd63003e0e702cb41cd0043015a6e26ddb38073cc6ffeb0ba3e808ba8c097"
+        );
+        for i in 0..mt.leafs() {
+            assert!(mt.gen_proof(i).validate_sorted::<Keccak256Algorithm>());
+        }
+    }
+}
+
+/// OpenZeppelin `MerkleProof` style sorted pairs tree.
+mod openzeppelin {
+    use crate::hash::Algorithm;
+    use crate::merkle::{MerkleTree, NodeOrder};
+    use core::hash::Hasher;
+    use sha3::{Digest, Keccak256};
+
+    #[derive(Clone, Default)]
+    struct Keccak(Keccak256);
+
+    impl Hasher for Keccak {
+        fn write(&mut self, msg: &[u8]) {
+            self.0.update(msg)
+        }
+
+        fn finish(&self) -> u64 {
+            let h = self.0.clone().finalize();
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&h[..8]);
+            u64::from_le_bytes(bytes)
+        }
+    }
+
+    impl Algorithm<[u8; 32]> for Keccak {
+        fn hash(&mut self) -> [u8; 32] {
+            self.0.finalize_reset().into()
+        }
+
+        // leafs are hashed by the caller, nodes are not prefixed
+        fn leaf(&mut self, leaf: [u8; 32]) -> [u8; 32] {
+            leaf
+        }
+
+        fn node(&mut self, left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+            self.write(&left);
+            self.write(&right);
+            self.hash()
+        }
+    }
+
+    fn keccak(data: &[u8]) -> [u8; 32] {
+        Keccak256::digest(data).into()
+    }
+
+    /// `MerkleProof.processProof` with `Hashes.commutativeKeccak256`.
+    fn process_proof(proof: &[[u8; 32]], leaf: [u8; 32]) -> [u8; 32] {
+        proof.iter().fold(leaf, |h, s| {
+            let (a, b) = if h < *s { (h, *s) } else { (*s, h) };
+            keccak(&[a, b].concat())
+        })
+    }
+
+    #[test]
+    fn test_openzeppelin_sorted_root() {
+        let leafs: [[u8; 32]; 4] = [keccak(b"a"), keccak(b"b"), keccak(b"c"), keccak(b"d")];
+        let mt: MerkleTree<[u8; 32], Keccak> =
+            MerkleTree::from_data_with_order([b"a", b"b", b"c", b"d"], NodeOrder::Sorted);
+
+        // reference root as OpenZeppelin computes it
+        let ab = process_proof(&[leafs[1]], leafs[0]);
+        let cd = process_proof(&[leafs[3]], leafs[2]);
+        assert_eq!(mt.root(), process_proof(&[cd], ab));
+
+        let mut a = Keccak::default();
+        a.write(b"a");
+        assert_eq!(a.finish().to_le_bytes(), leafs[0][..8]);
+
+        for (i, leaf) in leafs.iter().enumerate() {
+            let p = mt.gen_proof(i);
+            assert!(p.path().is_empty());
+            assert!(p.validate_sorted::<Keccak>());
+            let siblings = &p.lemma()[1..p.lemma().len() - 1];
+            assert_eq!(process_proof(siblings, *leaf), mt.root());
+        }
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+mod sha256d {
+    extern crate alloc;
+
+    use super::hex;
+    use crate::algorithms::Sha256dAlgorithm;
+    use crate::hash::Algorithm;
+    use crate::merkle::MerkleTree;
+    use alloc::vec::Vec;
+    use core::hash::Hasher;
+
+    /// Hex decodes a txid as displayed, into the internal byte order.
+    fn txid(s: &str) -> [u8; 32] {
+        let mut h = [0u8; 32];
+        for (i, b) in h.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        h.reverse();
+        h
+    }
+
+    /// Transactions of block 100000.
+    const TXIDS: [&str; 4] = [
+        "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+    ];
+
+    #[test]
+    fn test_sha256d_vector() {
+        let mut a = Sha256dAlgorithm::new();
+        a.write(b"abc");
+        // finish does not consume the state
+        assert_eq!(a.finish(), 0x9b72_d32d_c242_8b4f);
+        assert_eq!(
+            hex(&a.hash()),
+            "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"
+        );
+    }
+
+    #[test]
+    fn test_sha256d_block_100000() {
+        let txids: Vec<[u8; 32]> = TXIDS.iter().map(|s| txid(s)).collect();
+        let mt: MerkleTree<[u8; 32], Sha256dAlgorithm> = MerkleTree::new(txids.clone());
+
+        let mut root = mt.root();
+        root.reverse();
+        assert_eq!(
+            hex(&root),
+            "f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766"
+        );
+        for (i, txid) in txids.iter().enumerate() {
+            let p = mt.gen_proof(i);
+            assert_eq!(p.item(), *txid);
+            assert!(p.validate::<Sha256dAlgorithm>());
+        }
+
+        // odd level duplicates the last txid
+        let mt: MerkleTree<[u8; 32], Sha256dAlgorithm> = MerkleTree::new(txids[..3].to_vec());
+        let mut root = mt.root();
+        root.reverse();
+        assert_eq!(
+            hex(&root),
+            "fa435470825de273081dcc706b25514c936fa6dc80ab965ce6970d68ddd0b553"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::cached::CachedMerkleTree;
+use crate::merkle::MerkleTree;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_cached_tree() {
+    for leafs in [0u64, 1, 2, 3, 5, 8, 13, 16] {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..leafs).map(Item));
+
+        for depth in 0..=mt.height() + 1 {
+            let ct: CachedMerkleTree<Item, DefaultHasher> =
+                CachedMerkleTree::from_data((0..leafs).map(Item), depth);
+
+            assert_eq!(ct.root(), mt.root());
+            assert_eq!(ct.height(), mt.height());
+            assert_eq!(ct.leafs(), mt.leafs());
+            assert_eq!(ct.depth(), depth.min(mt.height()));
+
+            for i in 0..mt.leafs() {
+                let p = ct.gen_proof(i);
+                assert_eq!(p, mt.gen_proof(i));
+                assert!(p.validate::<DefaultHasher>());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cached_single_leaf() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data([Item(7)]);
+    let ct: CachedMerkleTree<Item, DefaultHasher> = CachedMerkleTree::from_data([Item(7)], 2);
+    assert_eq!(ct.height(), 1);
+    assert_eq!(ct.root(), mt.root());
+    assert_eq!(ct.root(), mt[0]);
+    let p = ct.gen_proof(0);
+    assert_eq!(p, mt.gen_proof(0));
+    assert!(p.validate::<DefaultHasher>());
+}
@@ -10,6 +10,7 @@ use std::hash::Hasher;
 use std::iter::FromIterator;
 
 /// Custom merkle hash util test
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Default)]
 struct CMH(DefaultHasher);
 
@@ -0,0 +1,111 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::consistency::ConsistencyProof;
+use crate::hash::{Algorithm, Hashable};
+use crate::merkle::{MerkleTree, PaddingPolicy};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+use std::vec::Vec;
+
+fn log(leafs: u64) -> MerkleTree<Item, DefaultHasher> {
+    MerkleTree::from_data_with_policy(0..leafs, PaddingPolicy::PromoteOdd)
+}
+
+#[test]
+fn test_consistency_grow() {
+    let mut mt = log(3);
+    let old = mt.root();
+
+    let mut a = DefaultHasher::default();
+    mt.extend((3..7u64).map(|x| {
+        a.reset();
+        x.hash(&mut a);
+        a.hash()
+    }));
+    assert_eq!(mt.root(), log(7).root());
+
+    let p = mt.gen_consistency_proof(3);
+    assert_eq!(p.old_size(), 3);
+    assert_eq!(p.new_size(), 7);
+    assert!(p.validate::<DefaultHasher>(&old, &mt.root()));
+
+    // RFC 6962 section 2.1.3 example, proof between d0..d2 and d0..d6 is
+    // [c, d, g, l]: leaf 2, leaf 3, node of leafs 0..2, node of leafs 4..7
+    assert_eq!(
+        p.nodes(),
+        &[
+            mt[2],
+            mt[3],
+            mt.subtree_root(1, 0).unwrap(),
+            mt.subtree_root(2, 1).unwrap()
+        ]
+    );
+
+    assert!(!p.validate::<DefaultHasher>(&mt.root(), &old));
+    assert!(!p.validate::<DefaultHasher>(&old, &old));
+    assert!(!p.validate::<DefaultHasher>(&log(4).root(), &mt.root()));
+}
+
+#[test]
+fn test_consistency_all_sizes() {
+    for n in 1..=20u64 {
+        let mt = log(n);
+        for m in 1..=n {
+            let old = log(m).root();
+            let p = mt.gen_consistency_proof(m as usize);
+            assert!(p.validate::<DefaultHasher>(&old, &mt.root()));
+            if m < n {
+                assert!(!p.nodes().is_empty());
+                assert!(!p.validate::<DefaultHasher>(&mt.root(), &mt.root()));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_consistency_forged() {
+    let mt = log(7);
+    let old = log(3).root();
+    let p = mt.gen_consistency_proof(3);
+
+    for i in 0..p.nodes().len() {
+        let mut nodes = p.nodes().to_vec();
+        nodes[i] = Item(nodes[i].0 ^ 1);
+        let forged = ConsistencyProof::new(3, 7, nodes);
+        assert!(!forged.validate::<DefaultHasher>(&old, &mt.root()));
+    }
+
+    // truncated, extended and resized
+    let nodes = p.nodes();
+    let truncated = ConsistencyProof::new(3, 7, nodes[..nodes.len() - 1].to_vec());
+    assert!(!truncated.validate::<DefaultHasher>(&old, &mt.root()));
+    let mut extended: Vec<Item> = nodes.to_vec();
+    extended.push(mt.root());
+    let extended = ConsistencyProof::new(3, 7, extended);
+    assert!(!extended.validate::<DefaultHasher>(&old, &mt.root()));
+    let resized = ConsistencyProof::new(3, 4, nodes.to_vec());
+    assert!(!resized.validate::<DefaultHasher>(&old, &mt.root()));
+    let resized = ConsistencyProof::new(2, 7, nodes.to_vec());
+    assert!(!resized.validate::<DefaultHasher>(&old, &mt.root()));
+
+    // a tree which rewrote a leaf of the old one
+    let mut values: Vec<u64> = (0..7).collect();
+    values[1] = 100;
+    let rewritten: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy(values, PaddingPolicy::PromoteOdd);
+    let p = rewritten.gen_consistency_proof(3);
+    assert!(!p.validate::<DefaultHasher>(&old, &rewritten.root()));
+
+    let same = ConsistencyProof::new(7, 7, Vec::new());
+    assert!(same.validate::<DefaultHasher>(&mt.root(), &mt.root()));
+    assert!(!same.validate::<DefaultHasher>(&old, &mt.root()));
+}
+
+#[test]
+#[should_panic(expected = "consistency proofs need RFC 6962 tree")]
+fn test_consistency_padded() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    mt.gen_consistency_proof(3);
+}
@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::fixed::{FixedMerkleTree, FixedProof};
+use crate::merkle::MerkleTree;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+type Fixed8 = FixedMerkleTree<Item, DefaultHasher, 3, 8>;
+
+#[test]
+fn test_fixed_tree() {
+    let data = [10u64, 11, 12, 13, 14, 15, 16, 17];
+    let mt = Fixed8::from_data(&data);
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(data);
+    assert_eq!(mt.root(), expected.root());
+    assert_eq!(mt.height(), expected.height());
+    assert_eq!(&mt.leafs()[..], &expected[..8]);
+
+    for i in 0..8 {
+        let p = mt.gen_proof(i);
+        assert!(p.validate::<DefaultHasher>());
+        assert_eq!(p.index(), i);
+        assert_eq!(p.item(), expected[i]);
+        assert_eq!(p.root(), mt.root());
+        assert_eq!(p.to_proof(), expected.gen_proof(i));
+    }
+
+    // the proof of another position or leaf
+    let p = mt.gen_proof(2);
+    let moved = FixedProof::new(p.item(), *p.siblings(), 3, p.root());
+    assert!(!moved.validate::<DefaultHasher>());
+    let outside = FixedProof::new(p.item(), *p.siblings(), 2 + 8, p.root());
+    assert!(!outside.validate::<DefaultHasher>());
+    let other = FixedProof::new(Item(0), *p.siblings(), 2, p.root());
+    assert!(!other.validate::<DefaultHasher>());
+}
+
+#[test]
+fn test_fixed_tree_shapes() {
+    let one: FixedMerkleTree<Item, DefaultHasher, 0, 1> = FixedMerkleTree::from_data(&[7u64]);
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data([7u64]);
+    assert_eq!(one.root(), expected.root());
+    assert!(one.gen_proof(0).validate::<DefaultHasher>());
+    assert!(one.gen_proof(0).siblings().is_empty());
+
+    let wide: FixedMerkleTree<Item, DefaultHasher, 5, 32> =
+        FixedMerkleTree::new(core::array::from_fn(|i| Item(i as u64)));
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::new((0..32).map(Item));
+    assert_eq!(wide.root(), expected.root());
+    for i in 0..32 {
+        assert!(wide.gen_proof(i).validate::<DefaultHasher>());
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_fixed_proof_out_of_bounds() {
+    Fixed8::from_data(&[0u64; 8]).gen_proof(8);
+}
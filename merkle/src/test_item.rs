@@ -4,15 +4,15 @@
 extern crate std;
 
 use crate::hash::{Algorithm, Hashable};
-use std::mem;
 use std::slice;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Item(pub u64);
 
 impl AsRef<[u8]> for Item {
     fn as_ref(&self) -> &[u8] {
-        unsafe { slice::from_raw_parts(mem::transmute(&self.0), 8) }
+        unsafe { slice::from_raw_parts(core::ptr::addr_of!(self.0).cast::<u8>(), 8) }
     }
 }
 
@@ -28,9 +28,9 @@ impl From<u64> for Item {
     }
 }
 
-impl Into<u64> for Item {
-    fn into(self) -> u64 {
-        self.0
+impl From<Item> for u64 {
+    fn from(x: Item) -> Self {
+        x.0
     }
 }
 
@@ -0,0 +1,71 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::layout::{level_len, level_offset, parent, sibling, stored_len};
+use crate::merkle::{MerkleTree, PaddingPolicy};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_layout_8_leafs() {
+    let offsets = [0, 8, 12, 14];
+    let lens = [8, 4, 2, 1];
+    for level in 0..4 {
+        assert_eq!(level_offset(8, level, true), offsets[level]);
+        assert_eq!(level_offset(8, level, false), offsets[level]);
+        assert_eq!(level_len(8, level), lens[level]);
+        assert_eq!(stored_len(lens[level], true), lens[level]);
+    }
+    assert_eq!(level_offset(8, 4, true), 15);
+
+    assert_eq!(parent(0), 0);
+    assert_eq!(parent(5), 2);
+    assert_eq!(parent(7), 3);
+    assert_eq!(sibling(0, 8), Some(1));
+    assert_eq!(sibling(5, 8), Some(4));
+    assert_eq!(sibling(3, 4), Some(2));
+    assert_eq!(sibling(0, 1), None);
+    assert_eq!(sibling(8, 8), None);
+
+    // leaf 5 up to the root: 5, 10, 13, 14, siblings 4, 11, 12
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..8u64);
+    let p = mt.gen_proof(5);
+    let mut i = 5;
+    for level in 0..3 {
+        let s = sibling(i, level_len(8, level)).unwrap();
+        assert_eq!(p.lemma()[level + 1], mt[level_offset(8, level, true) + s]);
+        i = parent(i);
+    }
+    assert_eq!(mt[level_offset(8, 3, true) + i], mt.root());
+}
+
+#[test]
+fn test_layout_odd_levels() {
+    // [h0 .. h4 h4] [h01 h23 h44 h44] [h0123 h4444] [root]
+    assert_eq!(stored_len(5, true), 6);
+    assert_eq!(stored_len(5, false), 5);
+    assert_eq!(stored_len(1, true), 1);
+    assert_eq!(sibling(4, 5), None);
+
+    for (policy, padded) in [
+        (PaddingPolicy::DuplicateLast, true),
+        (PaddingPolicy::HashWithZero, true),
+        (PaddingPolicy::PromoteOdd, false),
+    ] {
+        for leafs in 1..20u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::from_data_with_policy(0..leafs, policy);
+            let n = leafs as usize;
+            for level in 0..mt.height() {
+                for i in 0..level_len(n, level) {
+                    assert_eq!(
+                        mt.get_node(level, i),
+                        Some(&mt[level_offset(n, level, padded) + i])
+                    );
+                }
+            }
+            assert_eq!(level_offset(n, mt.height(), padded), mt.len());
+        }
+    }
+}
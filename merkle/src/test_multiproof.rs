@@ -0,0 +1,149 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::error::MerkleError;
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleTree, NodeOrder, PaddingPolicy};
+use crate::multiproof::MultiProof;
+use crate::proof::Proof;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+use std::vec::Vec;
+
+fn tree(leafs: u64) -> MerkleTree<Item, DefaultHasher> {
+    MerkleTree::from_data((0..leafs).map(Item))
+}
+
+#[test]
+fn test_multi_proof() {
+    for leafs in 2..20 {
+        let mt = tree(leafs);
+        let all: Vec<usize> = (0..mt.leafs()).collect();
+        let sets: Vec<Vec<usize>> = vec![
+            vec![0],
+            vec![mt.leafs() - 1],
+            vec![0, mt.leafs() - 1],
+            vec![1, 2],
+            all.iter().cloned().filter(|i| i % 3 == 0).collect(),
+            all.clone(),
+        ];
+
+        for set in sets.iter().filter(|s| s.iter().all(|&i| i < mt.leafs())) {
+            let p = mt.gen_multi_proof(set).unwrap();
+            let leaves: Vec<(usize, Item)> = set.iter().map(|&i| (i, mt[i])).collect();
+            assert!(p.validate::<DefaultHasher>(&mt.root(), &leaves));
+
+            let mut other = leaves.clone();
+            other[0].1 = Item(other[0].1 .0 ^ 1);
+            assert!(!p.validate::<DefaultHasher>(&mt.root(), &other));
+        }
+
+        if mt.leafs().is_power_of_two() {
+            assert!(mt.gen_multi_proof(&all).unwrap().nodes().is_empty());
+        }
+    }
+}
+
+#[test]
+fn test_multi_proof_dedup() {
+    let mt = tree(8);
+    let p = mt.gen_multi_proof(&[0, 1]).unwrap();
+    let mut a = DefaultHasher::default();
+
+    // leafs 0 and 1 are siblings: only h23 and h4567 are left to prove
+    assert_eq!(p.nodes().len(), 2);
+
+    a.reset();
+    let h23 = a.node(mt[2], mt[3]);
+    assert_eq!(p.nodes()[0], Some(h23));
+
+    let q = mt.gen_multi_proof(&[5, 1, 1, 5]).unwrap();
+    assert_eq!(q.indices(), &[1, 5]);
+    assert!(q.validate::<DefaultHasher>(&mt.root(), &[(5, mt[5]), (1, mt[1])]));
+    assert!(!q.validate::<DefaultHasher>(&mt.root(), &[(1, mt[1])]));
+}
+
+#[test]
+fn test_multi_proof_edge_cases() {
+    let mt = tree(5);
+
+    let p = mt.gen_multi_proof(&[]).unwrap();
+    assert!(p.nodes().is_empty());
+    assert!(p.validate::<DefaultHasher>(&mt.root(), &[]));
+    assert!(p.validate::<DefaultHasher>(&Item(42), &[]));
+
+    assert_eq!(
+        mt.gen_multi_proof(&[1, 5]),
+        Err(MerkleError::IndexOutOfBounds(5))
+    );
+
+    let mut a = DefaultHasher::default();
+    let leaf = a.leaf(Item(42));
+    let q = mt.gen_multi_proof(&[4]).unwrap();
+    assert!(!q.validate::<DefaultHasher>(&mt.root(), &[(4, leaf)]));
+    assert!(!q.validate::<DefaultHasher>(&mt.root(), &[(3, mt[4])]));
+
+    let sorted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_order((0..5).map(Item), NodeOrder::Sorted);
+    assert_eq!(
+        sorted.gen_multi_proof(&[1]),
+        Err(MerkleError::UnsupportedOrder)
+    );
+}
+
+#[test]
+fn test_multi_proof_from_proofs() {
+    for leafs in [13, 16] {
+        let mt = tree(leafs);
+        let proofs: Vec<Proof<Item>> = [3, 2, 9, 3].iter().map(|&i| mt.gen_proof(i)).collect();
+        let merged = MultiProof::from_proofs::<DefaultHasher>(&proofs).unwrap();
+        assert_eq!(merged, mt.gen_multi_proof(&[2, 3, 9]).unwrap());
+        assert_eq!(merged.indices(), &[2, 3, 9]);
+
+        // shared level 1 and 3 nodes are carried once
+        let single: usize = proofs[..3].iter().map(|p| p.lemma().len() - 2).sum();
+        assert!(merged.nodes().len() < single);
+
+        let leaves: Vec<(usize, Item)> = [2, 3, 9].iter().map(|&i| (i, mt[i])).collect();
+        assert!(merged.validate::<DefaultHasher>(&mt.root(), &leaves));
+    }
+
+    let empty = MultiProof::<Item>::from_proofs::<DefaultHasher>(&[]).unwrap();
+    assert!(empty.validate::<DefaultHasher>(&Item(0), &[]));
+}
+
+#[test]
+fn test_multi_proof_from_proofs_invalid() {
+    let mt = tree(16);
+    let other = tree(15);
+    assert_eq!(
+        MultiProof::from_proofs::<DefaultHasher>(&[mt.gen_proof(1), other.gen_proof(2)]),
+        Err(MerkleError::RootMismatch)
+    );
+
+    // no index recorded
+    let p = mt.gen_proof(1);
+    let bare = Proof::new(p.lemma().to_vec(), p.path().to_vec());
+    assert_eq!(
+        MultiProof::from_proofs::<DefaultHasher>(&[bare]),
+        Err(MerkleError::InvalidProof)
+    );
+
+    // tampered lemma
+    let mut lemma = p.lemma().to_vec();
+    lemma[1] = Item(lemma[1].0 ^ 1);
+    let tampered = Proof::new(lemma, p.path().to_vec()).with_index(1);
+    assert_eq!(
+        MultiProof::from_proofs::<DefaultHasher>(&[tampered]),
+        Err(MerkleError::InvalidProof)
+    );
+
+    // promoted leaf path is short of the tree height
+    let promoted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy((0..5).map(Item), PaddingPolicy::PromoteOdd);
+    assert_eq!(
+        MultiProof::from_proofs::<DefaultHasher>(&[promoted.gen_proof(0), promoted.gen_proof(4)]),
+        Err(MerkleError::InvalidProof)
+    );
+}
@@ -0,0 +1,154 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::hash::{Algorithm, Hashable};
+use crate::merkle::{MerkleTree, NodeOrder};
+use crate::nary::{NaryMerkleTree, NaryProof};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+use std::vec;
+use std::vec::Vec;
+
+#[test]
+fn test_binary_tree() {
+    for leafs in [1u64, 2, 3, 5, 7, 13, 16] {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        let nt: NaryMerkleTree<Item, DefaultHasher, 2> = NaryMerkleTree::from_data(0..leafs);
+        assert_eq!(nt.root(), mt.root());
+        assert_eq!(nt.height(), mt.height());
+        assert_eq!(nt.leafs(), mt.leafs());
+
+        for i in 0..nt.leafs() {
+            let p = nt.gen_proof(i);
+            assert!(p.validate::<DefaultHasher>());
+            assert_eq!(p.lemma(), mt.gen_proof(i).lemma());
+        }
+    }
+}
+
+#[test]
+fn test_empty_tree() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    let nt: NaryMerkleTree<Item, DefaultHasher, 2> = NaryMerkleTree::from_data(0..0u64);
+    assert_eq!(nt.root(), mt.root());
+    assert_eq!(nt.height(), 0);
+    assert_eq!(nt.leafs(), 0);
+
+    let nt: NaryMerkleTree<Item, DefaultHasher, 4> = NaryMerkleTree::new(Vec::new());
+    assert_eq!(nt.root(), mt.root());
+    assert_eq!(nt.height(), 0);
+}
+
+#[test]
+fn test_quaternary_tree() {
+    let nt: NaryMerkleTree<Item, DefaultHasher, 4> = NaryMerkleTree::from_data(0..16u64);
+    assert_eq!(nt.height(), 3);
+    assert_eq!(nt.arity(), 4);
+
+    let mut a = DefaultHasher::default();
+    let leafs: Vec<Item> = (0..16u64)
+        .map(|x| {
+            let mut h = DefaultHasher::default();
+            x.hash(&mut h);
+            let item = h.hash();
+            h.reset();
+            h.leaf(item)
+        })
+        .collect();
+    let nodes: Vec<Item> = leafs
+        .chunks(4)
+        .map(|c| {
+            a.reset();
+            a.nary_node(c)
+        })
+        .collect();
+    a.reset();
+    assert_eq!(nt.root(), a.nary_node(&nodes));
+
+    for i in 0..nt.leafs() {
+        let p = nt.gen_proof(i);
+        assert_eq!(p.path(), &[i % 4, i / 4]);
+        assert_eq!(p.lemma().len(), 2 * 3 + 2);
+        assert!(p.validate::<DefaultHasher>());
+
+        // siblings in positional order
+        let group: Vec<Item> = (i / 4 * 4..i / 4 * 4 + 4)
+            .filter(|&k| k != i)
+            .map(|k| leafs[k])
+            .collect();
+        assert_eq!(&p.lemma()[1..4], &group[..]);
+
+        let mut lemma = p.lemma().to_vec();
+        lemma[2] = Item(lemma[2].0 ^ 1);
+        assert!(!NaryProof::new(lemma, p.path().to_vec(), 4).validate::<DefaultHasher>());
+
+        let mut path = p.path().to_vec();
+        path[0] = (path[0] + 1) % 4;
+        assert!(!NaryProof::new(p.lemma().to_vec(), path, 4).validate::<DefaultHasher>());
+    }
+
+    let bt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..16u64);
+    assert_ne!(nt.root(), bt.root());
+}
+
+#[test]
+fn test_quaternary_positions() {
+    let nt: NaryMerkleTree<Item, DefaultHasher, 4> = NaryMerkleTree::from_data(0..16u64);
+    let root = nt.root();
+
+    // leafs 4..8 are the positions 0..4 of the second group
+    for pos in 0..4 {
+        let i = 4 + pos;
+        let p = nt.gen_proof(i);
+        assert_eq!(p.path(), &[pos, 1]);
+        assert_eq!(p.index(), i);
+        assert!(p.validate::<DefaultHasher>());
+
+        // the proven node is put back among the siblings at its position
+        let mut a = DefaultHasher::default();
+        let mut group = p.lemma()[1..4].to_vec();
+        group.insert(pos, p.item());
+        a.reset();
+        let parent = a.nary_node(&group);
+        let mut nodes = p.lemma()[4..7].to_vec();
+        nodes.insert(1, parent);
+        a.reset();
+        assert_eq!(a.nary_node(&nodes), root);
+
+        // any other position folds to another root
+        for other in (0..4).filter(|&other| other != pos) {
+            let moved = NaryProof::new(p.lemma().to_vec(), vec![other, 1], 4);
+            assert!(!moved.validate::<DefaultHasher>());
+        }
+    }
+}
+
+#[test]
+fn test_from_binary_proof() {
+    for leafs in [1u64, 2, 5, 8, 13] {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        for i in 0..mt.leafs() {
+            let p = NaryProof::from_proof(&mt.gen_proof(i)).unwrap();
+            assert_eq!(p.arity(), 2);
+            assert_eq!(p.index(), i);
+            assert!(p.validate::<DefaultHasher>());
+        }
+    }
+
+    let sorted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_order(0..5u64, NodeOrder::Sorted);
+    assert!(NaryProof::from_proof(&sorted.gen_proof(1)).is_none());
+}
+
+#[test]
+fn test_padded_levels() {
+    for leafs in [1u64, 2, 3, 5, 7, 9, 17, 30] {
+        let t3: NaryMerkleTree<Item, DefaultHasher, 3> = NaryMerkleTree::from_data(0..leafs);
+        let t8: NaryMerkleTree<Item, DefaultHasher, 8> = NaryMerkleTree::from_data(0..leafs);
+        for i in 0..t3.leafs() {
+            assert!(t3.gen_proof(i).validate::<DefaultHasher>());
+            assert!(t8.gen_proof(i).validate::<DefaultHasher>());
+        }
+    }
+}
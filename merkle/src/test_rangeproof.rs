@@ -0,0 +1,124 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleTree, NodeOrder, PaddingPolicy};
+use crate::rangeproof::RangeProof;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+use std::vec::Vec;
+
+fn tree(leafs: u64) -> MerkleTree<Item, DefaultHasher> {
+    MerkleTree::from_data((0..leafs).map(Item))
+}
+
+#[test]
+fn test_range_proof_mid() {
+    let mt = tree(16);
+    let p = mt.gen_range_proof(5..11);
+    assert!(p.validate::<DefaultHasher>(&mt.root(), &mt[5..11]));
+
+    // level 1: leafs 4 and 11, level 3: subtrees of leafs 0..4 and 12..16
+    let subtree = |i: usize| {
+        let mut a = DefaultHasher::default();
+        a.reset();
+        let l = a.node(mt[i], mt[i + 1]);
+        a.reset();
+        let r = a.node(mt[i + 2], mt[i + 3]);
+        a.reset();
+        a.node(l, r)
+    };
+    assert_eq!(
+        p.nodes(),
+        &[
+            Some(mt[4]),
+            Some(mt[11]),
+            Some(subtree(0)),
+            Some(subtree(12))
+        ]
+    );
+
+    let mut other = mt[5..11].to_vec();
+    other[3] = Item(other[3].0 ^ 1);
+    assert!(!p.validate::<DefaultHasher>(&mt.root(), &other));
+    assert!(!p.validate::<DefaultHasher>(&mt.root(), &mt[5..10]));
+    assert!(!p.validate::<DefaultHasher>(&mt.root(), &mt[6..12]));
+    assert!(!p.validate::<DefaultHasher>(&Item(0), &mt[5..11]));
+}
+
+#[test]
+fn test_range_proof_left_edge() {
+    let mt = tree(13);
+    for end in 1..=mt.leafs() {
+        let p = mt.gen_range_proof(0..end);
+        assert!(p.validate::<DefaultHasher>(&mt.root(), &mt[..end]));
+        assert!(p.nodes().len() < mt.height());
+    }
+}
+
+#[test]
+fn test_range_proof_whole_tree() {
+    for leafs in 1..20 {
+        let mt = tree(leafs);
+        let p = mt.gen_range_proof(0..mt.leafs());
+        assert!(p.validate::<DefaultHasher>(&mt.root(), &mt[..mt.leafs()]));
+        if mt.leafs().is_power_of_two() {
+            assert!(p.nodes().is_empty());
+        }
+    }
+}
+
+#[test]
+fn test_range_proof_all_ranges() {
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+    for &policy in &policies {
+        for leafs in 1..14u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::from_data_with_policy((0..leafs).map(Item), policy);
+            let n = mt.leafs();
+            for start in 0..n {
+                for end in start + 1..=n {
+                    let p = mt.gen_range_proof(start..end);
+                    assert_eq!(p.range(), start..end);
+                    assert!(p.nodes().len() <= 2 * mt.height());
+                    assert!(p.validate::<DefaultHasher>(&mt.root(), &mt[start..end]));
+
+                    let mut other: Vec<Item> = mt[start..end].to_vec();
+                    other[0] = Item(other[0].0 ^ 1);
+                    assert!(!p.validate::<DefaultHasher>(&mt.root(), &other));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_range_proof_empty() {
+    let mt = tree(7);
+    let p = mt.gen_range_proof(3..3);
+    assert!(p.nodes().is_empty());
+    assert!(p.validate::<DefaultHasher>(&mt.root(), &[]));
+    assert!(!p.validate::<DefaultHasher>(&mt.root(), &mt[3..4]));
+
+    let p = RangeProof::new(0..0, Vec::new(), 0);
+    assert!(p.validate::<DefaultHasher>(&Item(0), &[]));
+}
+
+#[test]
+#[should_panic]
+fn test_range_proof_out_of_bounds() {
+    tree(7).gen_range_proof(5..8);
+}
+
+#[test]
+#[should_panic(expected = "range proofs need positional tree")]
+fn test_range_proof_sorted() {
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_order((0..5).map(Item), NodeOrder::Sorted);
+    mt.gen_range_proof(1..3);
+}
@@ -0,0 +1,122 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::hash::{to_hex, Algorithm};
+use crate::merkle::{MerkleTree, MerkleTreeBuilder, NodeOrder, PaddingPolicy};
+use crate::scheme::{scheme_version, SchemeId, ENCODING_VERSION, SCHEME_VERSION};
+use core::hash::Hasher;
+
+/// FNV-1a 64, stable across platforms and releases unlike the std hashers.
+#[derive(Debug, Clone)]
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Algorithm<[u8; 8]> for Fnv {
+    fn hash(&mut self) -> [u8; 8] {
+        self.0.to_be_bytes()
+    }
+}
+
+const LEAFS: [&str; 5] = ["alpha", "beta", "gamma", "delta", "epsilon"];
+
+#[test]
+fn test_scheme_version() {
+    assert_eq!(scheme_version(), SCHEME_VERSION);
+    assert_eq!(SchemeId::default().version(), SCHEME_VERSION);
+    assert_eq!(SchemeId::default().encoding(), ENCODING_VERSION);
+}
+
+#[test]
+fn test_scheme_of_tree() {
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTree::from_data(LEAFS);
+    assert_eq!(mt.scheme(), SchemeId::default());
+    assert_eq!(mt.scheme().policy(), PaddingPolicy::DuplicateLast);
+    assert_eq!(mt.scheme().order(), NodeOrder::Positional);
+    assert!(mt.scheme().domain_separation());
+
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTreeBuilder::new()
+        .padding(PaddingPolicy::PromoteOdd)
+        .ordering(NodeOrder::Sorted)
+        .domain_separation(false)
+        .build(0..5u64);
+    assert_eq!(
+        mt.scheme(),
+        SchemeId::new(PaddingPolicy::PromoteOdd, NodeOrder::Sorted, false)
+    );
+    assert_ne!(mt.scheme(), SchemeId::default());
+}
+
+/// Roots of the current scheme and encoding versions, a change of these is
+/// a change of the scheme, see [`SCHEME_VERSION`] and [`ENCODING_VERSION`].
+#[test]
+#[cfg(not(feature = "scheme_v1"))]
+fn test_scheme_roots() {
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTree::from_data(LEAFS);
+    assert_eq!(mt.scheme().version(), 1);
+    assert_eq!(mt.scheme().encoding(), 2);
+    assert_eq!(to_hex(&mt.root()), "c698f99d4b54e04c");
+
+    let promoted: MerkleTree<[u8; 8], Fnv> = MerkleTreeBuilder::new()
+        .padding(PaddingPolicy::PromoteOdd)
+        .build(LEAFS);
+    assert_eq!(to_hex(&promoted.root()), "4874afa4f0d831c9");
+}
+
+/// Roots of 0.4.0 of the same data, as the `scheme_v1` feature pins them.
+///
+/// Encoding version 1 feeds the native integers of the target, the roots
+/// are the ones of the little-endian 64 bit targets.
+#[test]
+#[cfg(all(
+    feature = "scheme_v1",
+    target_endian = "little",
+    target_pointer_width = "64"
+))]
+fn test_scheme_v1_roots() {
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTree::from_data(LEAFS);
+    assert_eq!(mt.scheme().version(), 1);
+    assert_eq!(mt.scheme().encoding(), 1);
+    assert_eq!(to_hex(&mt.root()), "65a53a67321c1a2a");
+
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTree::from_data(0..5u64);
+    assert_eq!(to_hex(&mt.root()), "1c186a94de9e5269");
+
+    let bytes: Vec<Vec<u8>> = (0..5u8)
+        .map(|i| alloc::vec![i; usize::from(i) + 1])
+        .collect();
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTree::from_data(&bytes);
+    assert_eq!(to_hex(&mt.root()), "3254fbdd06b78267");
+
+    let tuples: Vec<(&str, usize, char, u16)> = LEAFS
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (*s, i, 'x', i as u16))
+        .collect();
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTree::from_data(&tuples);
+    assert_eq!(to_hex(&mt.root()), "fabc5e8508921a3b");
+
+    let slices: [&[u32]; 4] = [&[1, 2], &[3], &[], &[4, 5, 6]];
+    let mt: MerkleTree<[u8; 8], Fnv> = MerkleTree::from_data(slices);
+    assert_eq!(to_hex(&mt.root()), "649ceb11ec12893b");
+}
@@ -0,0 +1,135 @@
+#![cfg(test)]
+#![cfg(feature = "serde")]
+
+extern crate std;
+
+use crate::error::{MerkleError, ProofError};
+use crate::merkle::{MerkleTree, PaddingPolicy};
+use crate::proof::{CompactProof, Proof};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+use std::vec::Vec;
+
+type Tree = MerkleTree<Item, DefaultHasher>;
+
+fn trees() -> Vec<Tree> {
+    vec![
+        MerkleTree::from_data((0..7).map(Item)),
+        MerkleTree::from_data_with_policy((0..5).map(Item), PaddingPolicy::PromoteOdd),
+        MerkleTree::from_data_with_policy((0..5).map(Item), PaddingPolicy::HashWithZero),
+    ]
+}
+
+#[test]
+fn test_serde_json() {
+    for mt in trees() {
+        let json = serde_json::to_string(&mt).unwrap();
+        let back: Tree = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.root(), mt.root());
+        assert_eq!(back.as_slice(), mt.as_slice());
+        assert_eq!(back.leafs(), mt.leafs());
+
+        let p = mt.gen_proof(3);
+        let json = serde_json::to_string(&p).unwrap();
+        let back: Proof<Item> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, p);
+        assert!(back.validate::<DefaultHasher>());
+    }
+}
+
+#[test]
+fn test_serde_bincode() {
+    for mt in trees() {
+        let bytes = bincode::serialize(&mt).unwrap();
+        let back: Tree = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back.root(), mt.root());
+        assert_eq!(back.gen_proof(4), mt.gen_proof(4));
+
+        let p = mt.gen_proof(4);
+        let bytes = bincode::serialize(&p).unwrap();
+        let back: Proof<Item> = bincode::deserialize(&bytes).unwrap();
+        assert!(back.validate::<DefaultHasher>());
+    }
+}
+
+#[test]
+fn test_validate_detailed_malformed() {
+    let root = Item(0);
+
+    let empty: Proof<Item> = serde_json::from_str(r#"{"lemma":[],"path":[]}"#).unwrap();
+    assert_eq!(
+        empty.validate_detailed::<DefaultHasher>(&root),
+        Err(ProofError::EmptyLemma)
+    );
+
+    let short: Proof<Item> =
+        serde_json::from_str(r#"{"lemma":[1,2,3],"path":[true,false]}"#).unwrap();
+    assert_eq!(
+        short.validate_detailed::<DefaultHasher>(&root),
+        Err(ProofError::LengthMismatch { lemma: 3, path: 2 })
+    );
+
+    let single: Proof<Item> = serde_json::from_str(r#"{"lemma":[1],"path":[]}"#).unwrap();
+    assert_eq!(
+        single.validate_detailed::<DefaultHasher>(&root),
+        Err(ProofError::LengthMismatch { lemma: 1, path: 0 })
+    );
+}
+
+#[test]
+fn test_verify_self() {
+    for mt in trees() {
+        assert!(mt.verify_self());
+
+        let mut json: serde_json::Value = serde_json::to_value(&mt).unwrap();
+        let back: Tree = serde_json::from_value(json.clone()).unwrap();
+        assert!(back.verify_self());
+
+        // a node of level 1
+        let node = &mut json["data"][mt.leafs() + 1];
+        *node = serde_json::to_value(Item(42)).unwrap();
+        let corrupted: Tree = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(corrupted.root(), mt.root());
+        assert!(!corrupted.verify_self());
+        assert!(corrupted
+            .first_mismatch()
+            .is_some_and(|(level, _)| level == 1));
+
+        // leafs are gone
+        json["data"].as_array_mut().unwrap().truncate(2);
+        let truncated: Tree = serde_json::from_value(json).unwrap();
+        assert!(!truncated.verify_self());
+        assert_eq!(truncated.first_mismatch(), Some((0, 2)));
+    }
+
+    let empty: Tree = MerkleTree::empty();
+    assert!(empty.verify_self());
+}
+
+#[test]
+fn test_compact_proof_malformed() {
+    let zero = Item::default();
+    let mt: Tree =
+        MerkleTree::from_data_with_policy((0..17).map(Item), PaddingPolicy::HashWithZero);
+    let c = mt.gen_proof(16).compact(&zero);
+    let json = serde_json::to_value(&c).unwrap();
+    let back: CompactProof<Item> = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(back.expand(&zero), Ok(mt.gen_proof(16)));
+
+    // a run cut short of the path
+    let mut truncated = json.clone();
+    truncated["siblings"][0]["Default"] = serde_json::Value::from(3);
+    let truncated: CompactProof<Item> = serde_json::from_value(truncated).unwrap();
+    assert_eq!(truncated.expand(&zero), Err(MerkleError::InvalidEncoding));
+
+    // a run too long to restore
+    let mut long = json;
+    long["siblings"][0]["Default"] = serde_json::Value::from(u64::MAX);
+    let long: CompactProof<Item> = serde_json::from_value(long).unwrap();
+    assert_eq!(long.expand(&zero), Err(MerkleError::InvalidEncoding));
+
+    let sorted: CompactProof<Item> =
+        serde_json::from_str(r#"{"item":1,"siblings":[{"Default":65}],"root":2,"path":[]}"#)
+            .unwrap();
+    assert_eq!(sorted.expand(&zero), Err(MerkleError::InvalidEncoding));
+}
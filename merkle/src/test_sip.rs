@@ -6,14 +6,18 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 
-use crate::hash::{Algorithm, Hashable};
+use crate::error::{MerkleError, ProofError};
+use crate::hash::{Algorithm, Hashable, Unprefixed};
 use crate::merkle::log2_pow2;
 use crate::merkle::next_pow2;
-use crate::merkle::MerkleTree;
+use crate::merkle::{MerkleTree, MerkleTreeBuilder, NodeOrder, PaddingPolicy, RetainedMerkleTree};
+use crate::proof::{CompactProof, Proof, ProofRef, Side};
 use crate::test_item::Item;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::Hasher;
 use std::iter::FromIterator;
+use std::ops::Range;
+use std::string::ToString;
 
 impl Algorithm<Item> for DefaultHasher {
     #[inline]
@@ -124,3 +128,2701 @@ fn test_simple_tree() {
         }
     }
 }
+
+#[test]
+fn test_padding_policies() {
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+
+    for items in [3, 5] {
+        let roots: Vec<Item> = policies
+            .iter()
+            .map(|&policy| {
+                let mt: MerkleTree<Item, DefaultHasher> =
+                    MerkleTree::from_data_with_policy((1..=items).map(Item), policy);
+                assert_eq!(mt.policy(), policy);
+                assert_eq!(mt.leafs(), items as usize);
+
+                for i in 0..mt.leafs() {
+                    let p = mt.gen_proof(i);
+                    assert!(p.validate::<DefaultHasher>());
+                }
+                let all: Vec<usize> = (0..mt.leafs()).collect();
+                let leaves: Vec<(usize, Item)> = all.iter().map(|&i| (i, mt[i])).collect();
+                let p = mt.gen_multi_proof(&all).unwrap();
+                assert!(p.validate::<DefaultHasher>(&mt.root(), &leaves));
+                let p = mt.gen_multi_proof(&[items as usize - 1]).unwrap();
+                assert!(p.validate::<DefaultHasher>(&mt.root(), &leaves[items as usize - 1..]));
+
+                mt.root()
+            })
+            .collect();
+
+        assert_ne!(roots[0], roots[1]);
+        assert_ne!(roots[0], roots[2]);
+        assert_ne!(roots[1], roots[2]);
+
+        let default: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((1..=items).map(Item));
+        assert_eq!(default.policy(), PaddingPolicy::DuplicateLast);
+        assert_eq!(default.root(), roots[0]);
+    }
+
+    // MTH(D[3]) = ALG(0x01 || MTH(D[0:2]) || MTH(D[2:3]))
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy((1..=3).map(Item), PaddingPolicy::PromoteOdd);
+    let mut a = DefaultHasher::new();
+    let h01 = a.node(mt[0], mt[1]);
+    a.reset();
+    assert_eq!(mt.root(), a.node(h01, mt[2]));
+    assert_eq!(mt.len(), 6);
+    assert_eq!(mt.gen_proof(2).lemma().len(), 3);
+}
+
+#[test]
+fn test_from_iter_streaming() {
+    let hashed = || {
+        let mut a = DefaultHasher::new();
+        (0..1000u64).map(move |x| {
+            a.reset();
+            x.hash(&mut a);
+            a.hash()
+        })
+    };
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..1000u64);
+    let exact: MerkleTree<Item, DefaultHasher> = MerkleTree::from_iter(hashed());
+    let unknown: MerkleTree<Item, DefaultHasher> = MerkleTree::from_iter(hashed().filter(|_| true));
+
+    assert_eq!(mt.leafs(), 1000);
+    assert_eq!(exact.root(), mt.root());
+    assert_eq!(unknown.root(), mt.root());
+    assert_eq!(unknown.as_slice(), mt.as_slice());
+}
+
+#[test]
+fn test_push_extend() {
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+
+    for &policy in policies.iter() {
+        let mut mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_data_with_policy((0..2).map(Item), policy);
+        let mut a = DefaultHasher::new();
+        let mut n = 2u64;
+        let mut seed = 0x2545_f491u64;
+
+        while n < 300 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let k = (seed >> 59) % 11;
+            let hashes: Vec<Item> = (n..n + k)
+                .map(|x| {
+                    a.reset();
+                    Item(x).hash(&mut a);
+                    a.hash()
+                })
+                .collect();
+            if k == 1 {
+                mt.push(hashes[0]);
+            } else {
+                mt.extend(hashes);
+            }
+            n += k;
+
+            let fresh: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::from_data_with_policy((0..n).map(Item), policy);
+            assert_eq!(mt.leafs(), fresh.leafs());
+            assert_eq!(mt.height(), fresh.height());
+            assert_eq!(mt.root(), fresh.root());
+            assert_eq!(mt.as_slice(), fresh.as_slice());
+
+            let i = (seed as usize) % mt.leafs();
+            assert_eq!(mt.gen_proof(i), fresh.gen_proof(i));
+        }
+    }
+}
+
+/// Hashes leafs and nodes without domain separation prefixes.
+#[derive(Default)]
+struct RawHasher(DefaultHasher);
+
+impl Hasher for RawHasher {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for RawHasher {
+    fn hash(&mut self) -> Item {
+        Item(self.finish())
+    }
+
+    fn leaf(&mut self, leaf: Item) -> Item {
+        leaf
+    }
+
+    fn node(&mut self, left: Item, right: Item) -> Item {
+        self.write(left.as_ref());
+        self.write(right.as_ref());
+        self.hash()
+    }
+}
+
+#[test]
+fn test_second_preimage() {
+    // verifier hashes the claimed data into a leaf and checks its proof
+    fn forge<A: Algorithm<Item>>() -> bool {
+        let mt: MerkleTree<Item, A> = MerkleTree::from_data((1..=4).map(Item));
+
+        // data shaped as interior node h12 = h(h1 + h2), not a leaf of the tree
+        let mut data = Vec::new();
+        data.extend_from_slice(mt[0].as_ref());
+        data.extend_from_slice(mt[1].as_ref());
+
+        let mut a = A::default();
+        a.write(&data);
+        let h = a.hash();
+        a.reset();
+        let leaf = a.leaf(h);
+
+        Proof::new(vec![leaf, mt[5], mt.root()], vec![true]).validate::<A>()
+    }
+
+    assert!(forge::<RawHasher>());
+    assert!(!forge::<DefaultHasher>());
+}
+
+#[test]
+fn test_proof_path_fold() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((1..=7).map(Item));
+    let mut a = DefaultHasher::new();
+
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        let lemma = p.lemma();
+        assert_eq!(p.path().len(), lemma.len() - 2);
+        assert_eq!(p.indices()[0], i);
+
+        let mut h = lemma[0];
+        for (k, &left) in p.path().iter().enumerate() {
+            assert_eq!(p.indices()[k], i >> k);
+            a.reset();
+            h = if left {
+                a.node(h, lemma[k + 1])
+            } else {
+                a.node(lemma[k + 1], h)
+            };
+        }
+
+        assert_eq!(h, p.root());
+        assert_eq!(h, mt.root());
+    }
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_par_from_data() {
+    use rayon::prelude::*;
+
+    for leafs in [2u32, 3, 5, 8, 1000, 4097, 5000] {
+        let mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_data((0..leafs).map(|x| Item(x.into())));
+        let par: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::par_from_data((0..leafs).into_par_iter().map(|x| Item(x.into())));
+        assert_eq!(par.as_slice(), mt.as_slice());
+        assert_eq!(par.height(), mt.height());
+        assert_eq!(par.gen_proof(1), mt.gen_proof(1));
+    }
+}
+
+#[test]
+fn test_from_data_with_progress() {
+    use crate::merkle::BuildProgress;
+    use core::ops::ControlFlow;
+
+    for &leafs in &[1u64, 7, 2048, 3000] {
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        let mut reports = Vec::new();
+        let mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_data_with_progress(0..leafs, |p| {
+                reports.push(p);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(mt, expected);
+
+        // leafs hashed, then every level completed
+        let n = leafs as usize;
+        let hashed = (1..=n / 1024).map(|k| BuildProgress {
+            leafs: k * 1024,
+            levels: 0,
+        });
+        let levels = (1..=mt.height()).map(|levels| BuildProgress { leafs: n, levels });
+        assert_eq!(reports, hashed.chain(levels).collect::<Vec<_>>());
+    }
+
+    // cancel after the leaf layer
+    let mut reports = 0;
+    let r: Result<MerkleTree<Item, DefaultHasher>, MerkleError> =
+        MerkleTree::from_data_with_progress(0..100u64, |p| {
+            reports += 1;
+            if p.levels >= 1 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+    assert_eq!(r, Err(MerkleError::Cancelled));
+    assert_eq!(reports, 1);
+
+    // cancel while hashing the leafs
+    let r: Result<MerkleTree<Item, DefaultHasher>, MerkleError> =
+        MerkleTree::from_data_with_progress(0..5000u64, |p| {
+            assert!(p.leafs <= 1024);
+            ControlFlow::Break(())
+        });
+    assert_eq!(r, Err(MerkleError::Cancelled));
+
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_progress(0..0u64, |_| ControlFlow::Break(())).unwrap();
+    assert_eq!(mt.leafs(), 0);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_par_from_data_with_progress() {
+    use core::ops::ControlFlow;
+    use rayon::prelude::*;
+
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..3000u64);
+    let mut levels = Vec::new();
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::par_from_data_with_progress((0..3000u32).into_par_iter().map(u64::from), |p| {
+            assert_eq!(p.leafs, 3000);
+            levels.push(p.levels);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+    assert_eq!(mt, expected);
+    assert_eq!(levels, (1..=mt.height()).collect::<Vec<_>>());
+
+    let r: Result<MerkleTree<Item, DefaultHasher>, MerkleError> =
+        MerkleTree::par_from_data_with_progress((0..3000u32).into_par_iter().map(u64::from), |p| {
+            if p.levels >= 1 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+    assert_eq!(r, Err(MerkleError::Cancelled));
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_par_from_data_with_threads() {
+    use rayon::prelude::*;
+
+    for leafs in [1u32, 7, 3000] {
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..u64::from(leafs));
+        for threads in [1, 2, 8, 0] {
+            let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::par_from_data_with_threads(
+                (0..leafs).into_par_iter().map(u64::from),
+                threads,
+            )
+            .unwrap();
+            assert_eq!(mt.root(), expected.root());
+            assert_eq!(mt, expected);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_par_gen_proofs() {
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy(0..1000u64, PaddingPolicy::PromoteOdd);
+    let indices: Vec<usize> = (0..mt.leafs())
+        .rev()
+        .step_by(3)
+        .chain([0, 0, 999])
+        .collect();
+    let proofs: Vec<Proof<Item>> = indices.iter().map(|&i| mt.gen_proof(i)).collect();
+    assert_eq!(mt.par_gen_proofs(&indices), proofs);
+    assert!(mt.par_gen_proofs(&[]).is_empty());
+
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_order(0..77u64, NodeOrder::Sorted);
+    let indices: Vec<usize> = (0..mt.leafs()).collect();
+    let proofs = mt.par_gen_proofs(&indices);
+    for (i, p) in proofs.iter().enumerate() {
+        assert_eq!(*p, mt.gen_proof(i));
+        assert!(p.validate_sorted::<DefaultHasher>());
+    }
+}
+
+#[test]
+fn test_verify_proof() {
+    type Tree = MerkleTree<Item, DefaultHasher>;
+
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+    for &policy in &policies {
+        for leafs in 2..20u64 {
+            let mt: Tree = MerkleTree::from_data_with_policy((0..leafs).map(Item), policy);
+            let root = mt.root();
+            let n = mt.leafs();
+            for i in 0..n {
+                let p = mt.gen_proof(i);
+                let leaf = p.item();
+                assert!(Tree::verify_proof_with_policy(
+                    &root, &p, &leaf, i, n, policy
+                ));
+                let promoted = p.path().len() + 1 < mt.height();
+                assert_eq!(Tree::verify_proof(&root, &p, &leaf, i), !promoted);
+                assert!(!Tree::verify_proof_with_policy(
+                    &Item(0),
+                    &p,
+                    &leaf,
+                    i,
+                    n,
+                    policy
+                ));
+                assert!(!Tree::verify_proof_with_policy(
+                    &root,
+                    &p,
+                    &mt[(i + 1) % n],
+                    i,
+                    n,
+                    policy
+                ));
+
+                // same proof claimed for another position
+                for k in (0..2 * n).filter(|&k| k != i) {
+                    assert!(!Tree::verify_proof(&root, &p, &leaf, k));
+                    assert!(!Tree::verify_proof_with_policy(
+                        &root, &p, &leaf, k, n, policy
+                    ));
+                }
+            }
+        }
+    }
+
+    // the path of leaf 3 has no left bit, as have the indices 5 and 6 of
+    // two bits set
+    let mt: Tree = MerkleTree::from_data((0..4).map(Item));
+    let p = mt.gen_proof(3);
+    assert_eq!(p.path(), &[false, false]);
+    assert!(Tree::verify_proof(&mt.root(), &p, &mt[3], 3));
+    for k in [5, 6] {
+        assert!(!Tree::verify_proof(&mt.root(), &p, &mt[3], k));
+        assert!(!p.clone().with_index(k).validate::<DefaultHasher>());
+    }
+}
+
+#[test]
+fn test_proof_index() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..5).map(Item));
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert_eq!(p.index(), Some(i));
+        assert!(p.validate::<DefaultHasher>());
+        assert_eq!(p.validate_detailed::<DefaultHasher>(&mt.root()), Ok(()));
+        assert_eq!(p.compact(&Item(0)).expand(&Item(0)), Ok(p.clone()));
+    }
+
+    // the last leaf is its own sibling, so the replayed path folds the same
+    let p = mt.gen_proof(4);
+    let mut path = p.path().to_vec();
+    path[0] = !path[0];
+    let replayed = Proof::new(p.lemma().to_vec(), path);
+    assert_eq!(replayed.index(), None);
+    assert!(replayed.validate::<DefaultHasher>());
+
+    let replayed = replayed.with_index(4);
+    assert!(!replayed.validate::<DefaultHasher>());
+    assert_eq!(
+        replayed.validate_detailed::<DefaultHasher>(&mt.root()),
+        Err(ProofError::IndexMismatch { index: 4 })
+    );
+    assert_eq!(
+        ProofError::<Item>::IndexMismatch { index: 4 }.to_string(),
+        "proof path does not match leaf index 4"
+    );
+
+    let sorted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_order((0..5).map(Item), NodeOrder::Sorted);
+    let p = sorted.gen_proof(3);
+    assert_eq!(p.index(), Some(3));
+    assert!(p.validate_sorted::<DefaultHasher>());
+}
+
+#[test]
+fn test_computed_root() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert_eq!(p.computed_root::<DefaultHasher>(), Some(mt.root()));
+    }
+
+    // folds to another root than the lemma carries
+    let p = mt.gen_proof(2);
+    let mut lemma = p.lemma().to_vec();
+    lemma[1] = Item(lemma[1].0 ^ 1);
+    let forged = Proof::new(lemma, p.path().to_vec());
+    let computed = forged.computed_root::<DefaultHasher>().unwrap();
+    assert_ne!(computed, mt.root());
+    assert!(!forged.validate::<DefaultHasher>());
+    assert_eq!(
+        forged.validate_detailed::<DefaultHasher>(&mt.root()),
+        Err(ProofError::RootMismatch {
+            expected: mt.root(),
+            computed
+        })
+    );
+
+    let mut path = p.path().to_vec();
+    path[0] = !path[0];
+    let replayed = Proof::new(p.lemma().to_vec(), path).with_index(2);
+    assert_eq!(replayed.computed_root::<DefaultHasher>(), None);
+}
+
+#[test]
+fn test_matches_any() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    let before: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..6u64);
+    let fork: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(1..8u64);
+    let roots = [before.root(), mt.root(), fork.root()];
+
+    for i in 0..mt.leafs() {
+        assert_eq!(
+            mt.gen_proof(i).matches_any::<DefaultHasher>(&roots),
+            Some(1)
+        );
+    }
+    assert_eq!(
+        fork.gen_proof(3).matches_any::<DefaultHasher>(&roots),
+        Some(2)
+    );
+
+    // the first of the equal candidates
+    let twice = [fork.root(), mt.root(), mt.root()];
+    assert_eq!(
+        mt.gen_proof(0).matches_any::<DefaultHasher>(&twice),
+        Some(1)
+    );
+
+    let p = mt.gen_proof(2);
+    assert_eq!(p.matches_any::<DefaultHasher>(&roots[..1]), None);
+    assert_eq!(p.matches_any::<DefaultHasher>(&[]), None);
+
+    let mut path = p.path().to_vec();
+    path[0] = !path[0];
+    let replayed = Proof::new(p.lemma().to_vec(), path).with_index(2);
+    assert_eq!(replayed.matches_any::<DefaultHasher>(&roots), None);
+}
+
+#[test]
+fn test_proof_position() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..8u64);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert_eq!(p.leaf_position(), i);
+        assert_eq!(p.tree_size_hint(), 8);
+
+        // from the path bits alone
+        let bare = Proof::new(p.lemma().to_vec(), p.path().to_vec());
+        assert_eq!(bare.leaf_position(), i);
+    }
+
+    // an upper bound for the other sizes
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let p = mt.gen_proof(4);
+    assert_eq!((p.leaf_position(), p.tree_size_hint()), (4, 8));
+
+    let one: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..1u64);
+    assert_eq!(one.gen_proof(0).tree_size_hint(), 1);
+}
+
+#[test]
+fn test_tree_shape() {
+    let mt1: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data([Item(1)]);
+    assert_eq!(mt1.leafs(), 1);
+    assert_eq!(mt1.height(), 1);
+    assert_eq!(mt1.as_slice().len(), 1);
+    assert_eq!(mt1.as_slice()[0], mt1.root());
+    let p = mt1.gen_proof(0);
+    assert_eq!(p.lemma().len(), 2);
+    assert!(p.validate::<DefaultHasher>());
+
+    let mt2: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data([Item(1), Item(2)]);
+    assert_eq!(mt2.leafs(), 2);
+    assert_eq!(mt2.height(), 2);
+    assert_eq!(mt2.as_slice().len(), 3);
+
+    let mut a = DefaultHasher::new();
+    let mt8: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..8).map(Item));
+    assert_eq!(mt8.leafs(), 8);
+    assert_eq!(mt8.height(), 4);
+    assert_eq!(mt8.as_slice().len(), 15);
+
+    // leafs first, then each level up
+    let nodes = mt8.as_slice();
+    let mut base = 0;
+    for width in [8, 4, 2] {
+        for k in 0..width / 2 {
+            a.reset();
+            let h = a.node(nodes[base + 2 * k], nodes[base + 2 * k + 1]);
+            assert_eq!(nodes[base + width + k], h);
+        }
+        base += width;
+    }
+    assert_eq!(nodes[14], mt8.root());
+}
+
+#[test]
+fn test_compact_proof() {
+    let zero = Item::default();
+
+    // sparse authenticated set, zero padded odd levels
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy((0..17).map(Item), PaddingPolicy::HashWithZero);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        let c: CompactProof<Item> = p.compact(&zero);
+        assert!(c.hashes() <= p.lemma().len());
+        let e = c.expand(&zero).unwrap();
+        assert_eq!(e, p);
+        assert!(e.validate::<DefaultHasher>());
+        assert_eq!(e.root(), mt.root());
+    }
+
+    // last leaf is zero padded on every level but the top one
+    let p = mt.gen_proof(16);
+    assert_eq!(p.lemma().len(), 7);
+    assert_eq!(p.compact(&zero).hashes(), 3);
+
+    // runs at both ends of the lemma
+    let lemma = vec![
+        Item(1),
+        zero,
+        zero,
+        Item(2),
+        zero,
+        Item(3),
+        zero,
+        zero,
+        Item(4),
+    ];
+    let p = Proof::new(lemma, vec![true; 7]);
+    let c = p.compact(&zero);
+    assert_eq!(c.hashes(), 4);
+    assert_eq!(c.expand(&zero), Ok(p));
+}
+
+#[test]
+fn test_compact_sorted_proof() {
+    let zero = Item::default();
+    let mt: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_order((0..5).map(Item), NodeOrder::Sorted);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.path().is_empty());
+        let e = p.compact(&zero).expand(&zero).unwrap();
+        assert_eq!(e, p);
+        assert!(e.validate_sorted::<DefaultHasher>());
+    }
+}
+
+#[test]
+fn test_levels() {
+    let mt8: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..8).map(Item));
+    let levels: Vec<&[Item]> = mt8.levels().collect();
+    let widths: Vec<usize> = levels.iter().map(|l| l.len()).collect();
+    assert_eq!(widths, vec![8, 4, 2, 1]);
+    assert_eq!(levels[0], &mt8[..8]);
+    assert_eq!(levels[3], &[mt8.root()]);
+
+    // padding node is part of its level
+    let mt5: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..5).map(Item));
+    let widths: Vec<usize> = mt5.levels().map(|l| l.len()).collect();
+    assert_eq!(widths, vec![6, 4, 2, 1]);
+
+    let mt5: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy((0..5).map(Item), PaddingPolicy::PromoteOdd);
+    let widths: Vec<usize> = mt5.levels().map(|l| l.len()).collect();
+    assert_eq!(widths, vec![5, 3, 2, 1]);
+
+    let mt1: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data([Item(1)]);
+    assert_eq!(mt1.levels().count(), 1);
+    let mt0: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(mt0.levels().count(), 0);
+}
+
+#[test]
+fn test_from_data_checked() {
+    type Mt = MerkleTree<Item, DefaultHasher>;
+
+    // [1 2 3] pads to [1 2 3 3]
+    let mt = Mt::from_data_checked((1..=3).map(Item)).unwrap();
+    assert_eq!(mt.root(), Mt::from_data((1..=3).map(Item)).root());
+    let forged = [1, 2, 3, 3].map(Item);
+    assert_eq!(Mt::from_data(forged).root(), mt.root());
+    assert_eq!(
+        Mt::from_data_checked(forged).err(),
+        Some(MerkleError::DuplicateAmbiguity)
+    );
+
+    // [1 2 3 4 5 6] pads h56 to [h12 h34 h56 h56]
+    let mt = Mt::from_data_checked((1..=6).map(Item)).unwrap();
+    let forged = [1, 2, 3, 4, 5, 6, 5, 6].map(Item);
+    assert_eq!(Mt::from_data(forged).root(), mt.root());
+    assert_eq!(
+        Mt::from_data_checked(forged).err(),
+        Some(MerkleError::DuplicateAmbiguity)
+    );
+
+    assert!(Mt::from_data_checked([Item(1)]).is_ok());
+    assert!(Mt::from_data_checked(Vec::<Item>::new()).is_ok());
+}
+
+#[test]
+fn test_subtree_root() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..16).map(Item));
+    assert_eq!(mt.height(), 5);
+
+    let mut a = DefaultHasher::new();
+    let mut node = |l: Item, r: Item| {
+        a.reset();
+        a.node(l, r)
+    };
+    let leafs: Vec<Item> = (0..16).map(|i| mt[i]).collect();
+    let h01 = node(leafs[0], leafs[1]);
+    let h23 = node(leafs[2], leafs[3]);
+    let hef = node(leafs[14], leafs[15]);
+    let hcd = node(leafs[12], leafs[13]);
+    let h0123 = node(h01, h23);
+    let hcdef = node(hcd, hef);
+
+    assert_eq!(mt.subtree_root(0, 5), Some(leafs[5]));
+    assert_eq!(mt.subtree_root(1, 0), Some(h01));
+    assert_eq!(mt.subtree_root(1, 7), Some(hef));
+    assert_eq!(mt.subtree_root(2, 0), Some(h0123));
+    assert_eq!(mt.subtree_root(2, 3), Some(hcdef));
+    assert_eq!(
+        mt.subtree_root(3, 1),
+        Some(node(mt.subtree_root(2, 2).unwrap(), hcdef))
+    );
+    assert_eq!(mt.subtree_root(4, 0), Some(mt.root()));
+
+    assert_eq!(mt.subtree_root(0, 16), None);
+    assert_eq!(mt.subtree_root(1, 8), None);
+    assert_eq!(mt.subtree_root(4, 1), None);
+    assert_eq!(mt.subtree_root(5, 0), None);
+
+    // padding node is not addressable
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..5).map(Item));
+    // [l0 l1 l2 l3 l4 l4 | h01 h23 h44 h44 | ...]
+    assert_eq!(mt.subtree_root(0, 5), None);
+    assert_eq!(mt.subtree_root(1, 2), Some(mt[8]));
+    assert_eq!(mt.subtree_root(1, 3), None);
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(empty.subtree_root(0, 0), None);
+}
+
+#[test]
+fn test_gen_proof_for() {
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..7).map(Item));
+    let leaf = mt[4];
+
+    // found, scanned and indexed
+    assert_eq!(mt.index_of(&leaf), Some(4));
+    let p = mt.gen_proof_for(&leaf).unwrap();
+    assert_eq!(p, mt.gen_proof(4));
+    assert!(MerkleTree::<Item, DefaultHasher>::verify_proof(
+        &mt.root(),
+        &p,
+        &leaf,
+        4
+    ));
+    assert_eq!(mt.index_of(&leaf), Some(4));
+
+    // not found, interior nodes are not leafs
+    let root = mt.root();
+    // leafs and the padding leaf, then h01
+    let node = mt[8];
+    assert_eq!(mt.index_of(&root), None);
+    assert_eq!(mt.gen_proof_for(&node), None);
+    assert_eq!(mt.gen_proof_for(&Item(42)), None);
+
+    // appended leafs are indexed again
+    let mut a = DefaultHasher::new();
+    Item(7).hash(&mut a);
+    mt.push(a.hash());
+    let leaf = mt[7];
+    assert_eq!(mt.gen_proof_for(&leaf), Some(mt.gen_proof(7)));
+}
+
+#[test]
+fn test_gen_proof_for_duplicates() {
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data([1, 2, 1, 3, 2].map(Item));
+    let (one, two) = (mt[0], mt[1]);
+    assert_eq!(mt[2], one);
+
+    assert_eq!(mt.index_of(&one), Some(0));
+    assert_eq!(mt.index_of(&two), Some(1));
+    mt.build_index();
+    assert_eq!(mt.index_of(&one), Some(0));
+    assert_eq!(mt.index_of(&two), Some(1));
+    assert_eq!(mt.gen_proof_for(&one), Some(mt.gen_proof(0)));
+}
+
+#[test]
+fn test_from_byte_slices() {
+    let data = vec![b"foo".to_vec(), b"bar".to_vec(), b"bazz".to_vec()];
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_byte_slices(data.clone());
+
+    let mut a = DefaultHasher::new();
+    let leafs: Vec<Item> = data
+        .iter()
+        .map(|x| {
+            a.reset();
+            a.write(x);
+            let h = a.hash();
+            a.reset();
+            a.leaf(h)
+        })
+        .collect();
+    a.reset();
+    let h01 = a.node(leafs[0], leafs[1]);
+    a.reset();
+    let h22 = a.node(leafs[2], leafs[2]);
+    a.reset();
+    let root = a.node(h01, h22);
+
+    assert_eq!(mt.leafs(), 3);
+    assert_eq!(mt.root(), root);
+}
+
+#[test]
+fn test_validate_leaf() {
+    let data = [b"foo".to_vec(), b"bar".to_vec(), b"bazz".to_vec()];
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_byte_slices(&data);
+    let root = mt.root();
+
+    for (i, raw) in data.iter().enumerate() {
+        let p = mt.gen_proof(i);
+
+        // pre-hashed leaf
+        assert!(p.validate::<DefaultHasher>());
+        assert_eq!(p.item(), mt[i]);
+
+        // raw leaf
+        assert!(p.validate_leaf::<DefaultHasher>(&root, raw));
+        assert!(!p.validate_leaf::<DefaultHasher>(&root, b"qux"));
+        assert!(!p.validate_leaf::<DefaultHasher>(&p.lemma()[1], raw));
+
+        // leaf node itself is not a raw leaf
+        assert!(!p.validate_leaf::<DefaultHasher>(&root, p.item().as_ref()));
+    }
+}
+
+#[test]
+fn test_empty_tree() {
+    let empty = DefaultHasher::new().hash();
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(mt.root(), empty);
+    assert_eq!(mt.leafs(), 0);
+    assert_eq!(mt.height(), 0);
+    assert_eq!(mt.try_gen_proof(0), Err(MerkleError::EmptyTree));
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(Vec::<u64>::new());
+    assert_eq!(mt.root(), empty);
+    assert_eq!(mt.try_gen_proof(0), Err(MerkleError::EmptyTree));
+
+    // filtered iterator has no exact size hint
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..10u64).filter(|&x| x > 10));
+    assert_eq!(mt.root(), empty);
+
+    // empty tree grows as any other
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    mt.extend((0..5).map(Item));
+    let built: MerkleTree<Item, DefaultHasher> = MerkleTree::new((0..5).map(Item));
+    assert_eq!(mt.as_slice(), built.as_slice());
+    assert!(mt.try_gen_proof(4).unwrap().validate::<DefaultHasher>());
+    assert_eq!(mt.try_gen_proof(5), Err(MerkleError::IndexOutOfBounds(5)));
+}
+
+#[test]
+fn test_try_gen_proof() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    for i in 0..mt.leafs() {
+        assert_eq!(mt.try_gen_proof(i), Ok(mt.gen_proof(i)));
+    }
+    assert_eq!(mt.try_gen_proof(7), Err(MerkleError::IndexOutOfBounds(7)));
+    assert_eq!(
+        mt.try_gen_proof(usize::MAX),
+        Err(MerkleError::IndexOutOfBounds(usize::MAX))
+    );
+
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(empty.try_gen_proof(0), Err(MerkleError::EmptyTree));
+
+    #[cfg(feature = "std")]
+    {
+        let e: &dyn std::error::Error = &MerkleError::IndexOutOfBounds(7);
+        assert_eq!(e.to_string(), "leaf index 7 is out of bounds");
+    }
+    assert_eq!(MerkleError::EmptyTree.to_string(), "merkle tree is empty");
+    assert_eq!(MerkleError::InvalidProof.to_string(), "proof is invalid");
+    assert_eq!(
+        MerkleError::DuplicateAmbiguity.to_string(),
+        "equal sibling nodes make the root ambiguous"
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid leaf index")]
+fn test_gen_proof_out_of_bounds() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    mt.gen_proof(7);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_hash_reader() {
+    use crate::hash::hash_reader;
+    use std::io::{Cursor, Read};
+
+    let data: Vec<u8> = (0..1 << 20).map(|x: u32| (x % 251) as u8).collect();
+
+    let mut whole = DefaultHasher::new();
+    whole.write(&data);
+
+    let mut streamed = DefaultHasher::new();
+    hash_reader(&mut Cursor::new(&data), &mut streamed).unwrap();
+    assert_eq!(streamed.finish(), whole.finish());
+
+    // reader handing out short reads
+    let mut chunked = DefaultHasher::new();
+    hash_reader(
+        &mut Cursor::new(&data).take(1000).chain(&data[1000..]),
+        &mut chunked,
+    )
+    .unwrap();
+    assert_eq!(chunked.finish(), whole.finish());
+}
+
+#[test]
+#[cfg(not(feature = "scheme_v1"))]
+fn test_hash_all() {
+    use crate::hash::{hash_all, HashExt};
+
+    let digest = |items: &[&str]| {
+        let mut h = DefaultHasher::new();
+        hash_all(items, &mut h);
+        h.finish()
+    };
+    assert_ne!(digest(&["a", "bc"]), digest(&["ab", "c"]));
+    assert_ne!(digest(&["a", ""]), digest(&["a"]));
+    assert_eq!(digest(&["a", "bc"]), digest(&["a", "bc"]));
+
+    // plain hashing of the same strings does not collide either, they are
+    // terminated
+    let mut a = DefaultHasher::new();
+    ["a", "bc"][..].hash(&mut a);
+    let mut b = DefaultHasher::new();
+    ["ab", "c"][..].hash(&mut b);
+    assert_ne!(a.finish(), b.finish());
+    let mut a = DefaultHasher::new();
+    ("ab", "").hash(&mut a);
+    let mut b = DefaultHasher::new();
+    ("a", "b").hash(&mut b);
+    assert_ne!(a.finish(), b.finish());
+
+    // nested sequences and fixed size values
+    let mut a = DefaultHasher::new();
+    vec![vec![1u8], vec![2, 3]].hash_prefixed(&mut a);
+    let mut b = DefaultHasher::new();
+    vec![vec![1u8, 2], vec![3]].hash_prefixed(&mut b);
+    assert_ne!(a.finish(), b.finish());
+
+    let mut a = DefaultHasher::new();
+    7u32.hash_prefixed(&mut a);
+    let mut b = DefaultHasher::new();
+    7u32.hash(&mut b);
+    assert_eq!(a.finish(), b.finish());
+}
+
+/// Value tagged by the node it is hashed into.
+struct Tagged(u64);
+
+impl Hashable<DefaultHasher> for Tagged {
+    fn hash(&self, state: &mut DefaultHasher) {
+        self.0.hash(state)
+    }
+
+    fn hash_with_context(&self, state: &mut DefaultHasher, ctx: crate::hash::HashContext) {
+        match ctx {
+            crate::hash::HashContext::Leaf => state.write(b"leaf"),
+        }
+        self.0.hash(state)
+    }
+}
+
+#[test]
+fn test_hash_context() {
+    use crate::hash::HashContext;
+
+    let digest = |ctx: Option<HashContext>| {
+        let mut h = DefaultHasher::new();
+        match ctx {
+            Some(ctx) => Tagged(7).hash_with_context(&mut h, ctx),
+            None => Tagged(7).hash(&mut h),
+        }
+        h.finish()
+    };
+    let leaf = digest(Some(HashContext::Leaf));
+    assert_ne!(leaf, digest(None));
+    assert_eq!(digest(None), {
+        let mut h = DefaultHasher::new();
+        7u64.hash(&mut h);
+        h.finish()
+    });
+
+    // the default is the plain hash, references forward the context
+    let mut h = DefaultHasher::new();
+    7u64.hash_with_context(&mut h, HashContext::Leaf);
+    assert_eq!(h.finish(), digest(None));
+    let mut h = DefaultHasher::new();
+    Hashable::hash_with_context(&&Tagged(7), &mut h, HashContext::Leaf);
+    assert_eq!(h.finish(), leaf);
+
+    // trees hash their data as leafs
+    let data: Vec<Tagged> = (0..5).map(Tagged).collect();
+    let tagged: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(&data);
+    let plain: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::new((0..5u64).map(|x| {
+        let mut h = DefaultHasher::new();
+        h.write(b"leaf");
+        x.hash(&mut h);
+        Item(h.finish())
+    }));
+    assert_eq!(tagged, expected);
+    assert_ne!(tagged.root(), plain.root());
+    let built: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new().build(&data);
+    assert_eq!(built, expected);
+}
+
+#[test]
+fn test_hash_maps() {
+    use crate::hash::HashExt;
+    use std::collections::{BTreeMap, HashMap};
+    use std::string::String;
+
+    let digest = |f: &dyn Fn(&mut DefaultHasher)| {
+        let mut h = DefaultHasher::new();
+        f(&mut h);
+        h.finish()
+    };
+
+    let entries: Vec<(u64, String)> = (0..50).map(|i| (i * 7 % 50, i.to_string())).collect();
+    let forward: HashMap<u64, String> = entries.iter().cloned().collect();
+    let backward: HashMap<u64, String> = entries.iter().rev().cloned().collect();
+    let mut small = HashMap::with_capacity(1);
+    for (k, v) in entries.iter().skip(25).chain(entries.iter().take(25)) {
+        small.insert(*k, v.clone());
+    }
+    let ordered: BTreeMap<u64, String> = entries.iter().cloned().collect();
+
+    let h = digest(&|h| forward.hash(h));
+    assert_eq!(h, digest(&|h| backward.hash(h)));
+    assert_eq!(h, digest(&|h| small.hash(h)));
+    assert_eq!(h, digest(&|h| ordered.hash(h)));
+    assert_eq!(
+        digest(&|h| forward.hash_prefixed(h)),
+        digest(&|h| ordered.hash_prefixed(h))
+    );
+    assert_ne!(
+        digest(&|h| forward.hash(h)),
+        digest(&|h| forward.hash_prefixed(h))
+    );
+
+    // entry count, then the entries in the key order
+    let pair: BTreeMap<u32, u32> = [(2, 20), (1, 10)].into_iter().collect();
+    let mut bytes = 2u64.to_le_bytes().to_vec();
+    for x in [1u32, 10, 2, 20] {
+        bytes.extend_from_slice(&x.to_le_bytes());
+    }
+    assert_eq!(digest(&|h| pair.hash(h)), digest(&|h| h.write(&bytes)));
+
+    let mut other = forward.clone();
+    other.insert(0, String::from("other"));
+    assert_ne!(h, digest(&|h| other.hash(h)));
+    let empty: HashMap<u64, u64> = HashMap::new();
+    assert_eq!(digest(&|h| empty.hash(h)), digest(&|h| 0usize.hash(h)));
+}
+
+#[test]
+fn test_hash_time_net() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let digest = |f: &dyn Fn(&mut DefaultHasher)| {
+        let mut h = DefaultHasher::new();
+        f(&mut h);
+        h.finish()
+    };
+
+    let d = Duration::new(5, 7);
+    let mut bytes = 5u64.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&7u32.to_le_bytes());
+    assert_eq!(digest(&|h| d.hash(h)), digest(&|h| h.write(&bytes)));
+
+    let t = UNIX_EPOCH + d;
+    assert_eq!(
+        digest(&|h| t.hash(h)),
+        digest(&|h| {
+            h.write(&[0]);
+            h.write(&bytes)
+        })
+    );
+    assert_ne!(
+        digest(&|h| t.hash(h)),
+        digest(&|h| (UNIX_EPOCH - d).hash(h))
+    );
+
+    let v4 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let same: IpAddr = "10.0.0.1".parse().unwrap();
+    assert_eq!(digest(&|h| v4.hash(h)), digest(&|h| same.hash(h)));
+    assert_eq!(
+        digest(&|h| v4.hash(h)),
+        digest(&|h| {
+            h.write(&[4]);
+            h.write(&[10, 0, 0, 1])
+        })
+    );
+
+    // the same bytes in a v6 address
+    let v6 = IpAddr::V6(Ipv6Addr::from([
+        10, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ]));
+    let mapped = IpAddr::V6(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped());
+    assert_ne!(digest(&|h| v4.hash(h)), digest(&|h| v6.hash(h)));
+    assert_ne!(digest(&|h| v4.hash(h)), digest(&|h| mapped.hash(h)));
+}
+
+#[test]
+fn test_hash_float() {
+    fn digest<T: Hashable<DefaultHasher>>(x: T) -> u64 {
+        let mut h = DefaultHasher::new();
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    assert_eq!(digest(0.0f64), digest(-0.0f64));
+    assert_eq!(digest(0.0f32), digest(-0.0f32));
+    assert_eq!(digest(1.5f64), digest(1.5f64.to_bits()));
+    assert_ne!(digest(1.5f64), digest(-1.5f64));
+
+    let nans = [
+        f64::NAN,
+        -f64::NAN,
+        f64::from_bits(0x7ff0_0000_0000_0001),
+        f64::from_bits(0xfff8_dead_beef_0000),
+        f64::INFINITY - f64::INFINITY,
+    ];
+    for &nan in &nans {
+        assert!(nan.is_nan());
+        assert_eq!(digest(nan), digest(f64::NAN));
+    }
+    assert_eq!(digest(f32::from_bits(0x7f80_0001)), digest(f32::NAN));
+    assert_eq!(digest(-f32::NAN), digest(f32::NAN));
+    assert_ne!(digest(f64::NAN), digest(f64::INFINITY));
+}
+
+#[test]
+fn test_hash_types() {
+    use crate::hash::{Hash32, Hash64};
+
+    let bytes: Vec<u8> = (0..64).collect();
+    let h = Hash32::try_from(&bytes[..32]).unwrap();
+    assert_eq!(h.as_ref(), &bytes[..32]);
+    assert_eq!(h, Hash32::from(<[u8; 32]>::try_from(&bytes[..32]).unwrap()));
+    assert!(Hash32::try_from(&bytes[..31]).is_err());
+    assert!(Hash32::try_from(&bytes[..33]).is_err());
+    assert!(Hash32::try_from(&[][..]).is_err());
+
+    let h64 = Hash64::try_from(&bytes[..]).unwrap();
+    assert_eq!(h64.as_ref(), &bytes[..]);
+    assert!(Hash64::try_from(&bytes[..32]).is_err());
+
+    assert_eq!(
+        h.to_string(),
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+    );
+    assert_eq!(h64.to_string().len(), 128);
+    assert!(h64.to_string().ends_with("3d3e3f"));
+    assert_eq!(
+        format!("{:?}", Hash32::default()),
+        format!("Hash32({})", "00".repeat(32))
+    );
+    assert!(Hash32::default() < h);
+
+    let mut a = DefaultHasher::new();
+    h.hash(&mut a);
+    let mut b = DefaultHasher::new();
+    b.write(&bytes[..32]);
+    assert_eq!(a.finish(), b.finish());
+}
+
+#[test]
+fn test_collect_extend() {
+    let mut a = DefaultHasher::new();
+    let hashes: Vec<Item> = (0..11u64)
+        .map(|x| {
+            a.reset();
+            x.hash(&mut a);
+            a.hash()
+        })
+        .collect();
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..11u64);
+
+    let collected: MerkleTree<Item, DefaultHasher> = hashes.iter().cloned().collect();
+    assert_eq!(collected.root(), mt.root());
+
+    let mut extended: MerkleTree<Item, DefaultHasher> = hashes[..4].iter().cloned().collect();
+    Extend::extend(&mut extended, hashes[4..].iter().cloned());
+    assert_eq!(extended.root(), mt.root());
+    assert_eq!(extended.as_slice(), mt.as_slice());
+
+    fn extend_all<E: Extend<Item>>(e: &mut E, items: &[Item]) {
+        e.extend(items.iter().cloned());
+    }
+    let mut empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    extend_all(&mut empty, &hashes);
+    assert_eq!(empty.root(), mt.root());
+}
+
+#[test]
+fn test_validate_ct() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..9u64);
+    let root = mt.root();
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate_ct::<DefaultHasher>(&root));
+        assert!(!p.validate_ct::<DefaultHasher>(&Item(root.0 ^ 1)));
+        assert!(!p.validate_ct::<DefaultHasher>(&Item(root.0 ^ (1 << 63))));
+
+        // tampered sibling
+        let mut lemma = p.lemma().to_vec();
+        lemma[1] = Item(lemma[1].0 + 1);
+        let bad = Proof::new(lemma, p.path().to_vec());
+        assert!(!bad.validate_ct::<DefaultHasher>(&root));
+    }
+}
+
+#[test]
+fn test_sorted_order() {
+    for leafs in 1..20u64 {
+        let mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_data_with_order(0..leafs, NodeOrder::Sorted);
+        let positional: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        assert_eq!(mt.order(), NodeOrder::Sorted);
+        if leafs > 2 {
+            assert_ne!(mt.root(), positional.root());
+        }
+
+        for i in 0..mt.leafs() {
+            // no index information is needed
+            let p = mt.gen_proof(i);
+            assert!(p.path().is_empty());
+            assert!(p.validate_sorted::<DefaultHasher>());
+
+            let lemma = p.lemma().to_vec();
+            let stripped = Proof::new_sorted(lemma.clone());
+            assert!(stripped.validate_sorted::<DefaultHasher>());
+
+            // leaf of another tree is rejected
+            let mut forged = lemma;
+            forged[0] = Item(forged[0].0 ^ 1);
+            assert!(!Proof::new_sorted(forged).validate_sorted::<DefaultHasher>());
+        }
+    }
+}
+
+/// Hasher keyed with a secret prefix, which `Default` can not express.
+#[derive(Clone, Default)]
+struct KeyedHasher(DefaultHasher);
+
+impl KeyedHasher {
+    fn new(key: &[u8]) -> KeyedHasher {
+        let mut h = DefaultHasher::new();
+        h.write(key);
+        KeyedHasher(h)
+    }
+}
+
+impl Hasher for KeyedHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for KeyedHasher {
+    fn hash(&mut self) -> Item {
+        Item(self.0.finish())
+    }
+}
+
+#[test]
+fn test_from_data_with_hasher() {
+    let keyed = KeyedHasher::new(b"secret");
+    let mt: MerkleTree<Item, KeyedHasher> =
+        MerkleTree::from_data_with_hasher(0..7u64, keyed.clone());
+    let unkeyed: MerkleTree<Item, KeyedHasher> = MerkleTree::from_data(0..7u64);
+    let default: MerkleTree<Item, KeyedHasher> =
+        MerkleTree::from_data_with_hasher(0..7u64, KeyedHasher::default());
+    assert_ne!(mt.root(), unkeyed.root());
+    assert_eq!(default.as_slice(), unkeyed.as_slice());
+
+    let other: MerkleTree<Item, KeyedHasher> =
+        MerkleTree::from_data_with_hasher(0..7u64, KeyedHasher::new(b"other"));
+    assert_ne!(mt.root(), other.root());
+
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate_with_hasher(&keyed));
+        assert!(!p.validate::<KeyedHasher>());
+        assert!(!p.validate_with_hasher(&KeyedHasher::new(b"other")));
+    }
+
+    let empty: MerkleTree<Item, KeyedHasher> =
+        MerkleTree::from_data_with_hasher(Vec::<u64>::new(), keyed.clone());
+    assert_eq!(empty.root(), keyed.clone().hash());
+}
+
+/// Hasher keyed with a secret prefix, which `reset` restores.
+#[derive(Clone, Default)]
+struct ResetKeyedHasher {
+    key: Vec<u8>,
+    state: DefaultHasher,
+}
+
+impl ResetKeyedHasher {
+    fn new(key: &[u8]) -> ResetKeyedHasher {
+        let mut h = ResetKeyedHasher {
+            key: key.to_vec(),
+            state: DefaultHasher::new(),
+        };
+        h.reset();
+        h
+    }
+}
+
+impl Hasher for ResetKeyedHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.state.write(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.state.finish()
+    }
+}
+
+impl Algorithm<Item> for ResetKeyedHasher {
+    fn hash(&mut self) -> Item {
+        Item(self.state.finish())
+    }
+
+    fn reset(&mut self) {
+        self.state = DefaultHasher::new();
+        self.state.write(&self.key);
+    }
+}
+
+#[test]
+fn test_validate_with() {
+    let mut keyed = ResetKeyedHasher::new(b"secret");
+    let mt: MerkleTree<Item, ResetKeyedHasher> =
+        MerkleTree::from_data_with_hasher(0..7u64, keyed.clone());
+    let root = mt.root();
+
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(!p.validate::<ResetKeyedHasher>());
+        assert!(p.validate_with(&mut keyed, &root));
+        assert!(!p.validate_with(&mut ResetKeyedHasher::new(b"other"), &root));
+        assert!(!p.validate_with(&mut keyed, &Item(0)));
+    }
+
+    // default constructed instance is the unkeyed algorithm
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    let p = mt.gen_proof(3);
+    assert!(p.validate_with(&mut DefaultHasher::default(), &mt.root()));
+}
+
+#[test]
+fn test_validate_detailed() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    let root = mt.root();
+    for i in 0..mt.leafs() {
+        assert_eq!(
+            mt.gen_proof(i).validate_detailed::<DefaultHasher>(&root),
+            Ok(())
+        );
+    }
+
+    let p = mt.gen_proof(2);
+    assert_eq!(
+        p.validate_detailed::<DefaultHasher>(&Item(0)),
+        Err(ProofError::RootMismatch {
+            expected: Item(0),
+            computed: root,
+        })
+    );
+
+    // tampered sibling
+    let mut lemma = p.lemma().to_vec();
+    lemma[1] = Item(lemma[1].0 ^ 1);
+    let forged = Proof::new(lemma, p.path().to_vec());
+    match forged.validate_detailed::<DefaultHasher>(&root) {
+        Err(ProofError::RootMismatch { expected, computed }) => {
+            assert_eq!(expected, root);
+            assert_ne!(computed, root);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // sorted proofs carry no path bits
+    let sorted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_order(0..7u64, NodeOrder::Sorted);
+    assert_eq!(
+        sorted
+            .gen_proof(1)
+            .validate_detailed::<DefaultHasher>(&sorted.root()),
+        Err(ProofError::LengthMismatch { lemma: 5, path: 0 })
+    );
+
+    let e = ProofError::RootMismatch {
+        expected: Item(0),
+        computed: Item(u64::MAX),
+    };
+    assert_eq!(
+        e.to_string(),
+        "proof root ffffffffffffffff does not match expected root 0000000000000000"
+    );
+}
+
+/// Sip hasher salted with a byte, another algorithm over the same items.
+struct SaltedHasher(DefaultHasher);
+
+impl Default for SaltedHasher {
+    fn default() -> SaltedHasher {
+        let mut h = DefaultHasher::new();
+        h.write_u8(0x5a);
+        SaltedHasher(h)
+    }
+}
+
+impl Hasher for SaltedHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for SaltedHasher {
+    fn hash(&mut self) -> Item {
+        Item(self.0.finish())
+    }
+}
+
+#[test]
+fn test_rebuild_with() {
+    let values: Vec<Item> = (0..7).map(Item).collect();
+    let mt: RetainedMerkleTree<Item, DefaultHasher, Item> =
+        MerkleTree::retain_leaves(values.clone());
+    let plain: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(&values);
+    assert_eq!(mt.tree(), &plain);
+    assert_eq!(mt.values(), &values[..]);
+
+    let salted: MerkleTree<Item, SaltedHasher> = mt.rebuild_with();
+    let fresh: MerkleTree<Item, SaltedHasher> = MerkleTree::from_data(&values);
+    assert_eq!(salted.as_slice(), fresh.as_slice());
+    assert_ne!(salted.root(), mt.tree().root());
+}
+
+#[test]
+#[should_panic(expected = "leaf values are not retained")]
+fn test_rebuild_with_not_retained() {
+    let mt = MerkleTreeBuilder::<Item, DefaultHasher>::new().build_values((0..7).map(Item));
+    let _: MerkleTree<Item, SaltedHasher> = mt.rebuild_with();
+}
+
+#[test]
+fn test_gen_proof_with_value() {
+    use std::string::String;
+
+    let values: Vec<String> = (0..7).map(|x| std::format!("leaf {}", x)).collect();
+    let mt = MerkleTreeBuilder::<Item, DefaultHasher>::new()
+        .padding(PaddingPolicy::PromoteOdd)
+        .retain_leaves(true)
+        .build_values(values.clone());
+    let plain = MerkleTreeBuilder::<Item, DefaultHasher>::new()
+        .padding(PaddingPolicy::PromoteOdd)
+        .build_values(values.clone());
+    assert_eq!(mt.tree(), plain.tree());
+    assert_eq!(mt.values(), &values[..]);
+
+    for (i, value) in values.iter().enumerate() {
+        let (v, p) = mt.gen_proof_with_value(i).unwrap();
+        assert_eq!(v, value);
+        assert_eq!(p, mt.tree().gen_proof(i));
+        assert!(p.validate::<DefaultHasher>());
+
+        // the proven leaf is the hash of the value
+        let mut a = DefaultHasher::default();
+        v.hash(&mut a);
+        let h = a.hash();
+        a.reset();
+        assert_eq!(p.item(), a.leaf(h));
+    }
+    assert!(mt.gen_proof_with_value(7).is_none());
+    assert_eq!(mt.value(6), Some(&values[6]));
+    assert_eq!(mt.value(7), None);
+
+    assert!(plain.gen_proof_with_value(0).is_none());
+    assert!(plain.values().is_empty());
+    assert_eq!(mt.into_tree(), plain.into_tree());
+}
+
+/// Sip hashes of the data under 4 keys, for the trees of 32 byte hashes.
+#[derive(Debug)]
+struct Sip4;
+
+impl crate::hash::HashFn<crate::hash::Hash32> for Sip4 {
+    fn hash(data: &[u8]) -> crate::hash::Hash32 {
+        let mut h = [0u8; 32];
+        for (k, c) in h.chunks_exact_mut(8).enumerate() {
+            let mut a = DefaultHasher::new();
+            a.write_usize(k);
+            a.write(data);
+            c.copy_from_slice(&a.finish().to_le_bytes());
+        }
+        crate::hash::Hash32(h)
+    }
+}
+
+#[test]
+fn test_packed_bytes() {
+    use crate::hash::{FnAlgorithm, Hash32};
+    type Packed = MerkleTree<Hash32, FnAlgorithm<Sip4, Hash32>>;
+
+    for leafs in 1..20u64 {
+        let mt = Packed::from_data(0..leafs);
+        let bytes = mt.to_packed_bytes();
+        assert_eq!(bytes.len(), mt.len() * 32);
+        assert_eq!(&bytes[..32], mt[0].as_ref());
+        assert_eq!(&bytes[bytes.len() - 32..], mt.root().as_ref());
+
+        let loaded = Packed::from_packed_bytes(&bytes, mt.leafs()).unwrap();
+        assert_eq!(loaded, mt);
+        assert_eq!(loaded.root(), mt.root());
+        assert_eq!(loaded.height(), mt.height());
+        assert!(loaded.verify_self());
+        for i in 0..mt.leafs() {
+            assert_eq!(loaded.gen_proof(i), mt.gen_proof(i));
+        }
+
+        assert_eq!(
+            Packed::from_packed_bytes(&bytes[..bytes.len() - 1], mt.leafs()),
+            Err(MerkleError::InvalidEncoding)
+        );
+        assert_eq!(
+            Packed::from_packed_bytes(&bytes[..bytes.len() - 32], mt.leafs()),
+            Err(MerkleError::InvalidEncoding)
+        );
+        if leafs > 2 {
+            // other number of leafs of the same node count
+            let other = Packed::from_packed_bytes(&bytes, mt.leafs() - 1);
+            assert!(other.map_or(true, |t| !t.verify_self()));
+        }
+    }
+
+    assert_eq!(
+        Packed::from_packed_bytes(&[], 0),
+        Err(MerkleError::EmptyTree)
+    );
+    assert_eq!(
+        Packed::from_packed_bytes(&[0; 32], 2),
+        Err(MerkleError::InvalidEncoding)
+    );
+}
+
+#[test]
+fn test_first_mismatch() {
+    use crate::hash::{FnAlgorithm, Hash32};
+    type Packed = MerkleTree<Hash32, FnAlgorithm<Sip4, Hash32>>;
+
+    let mt = Packed::from_data(0..13u64);
+    assert_eq!(mt.first_mismatch(), None);
+    let bytes = mt.to_packed_bytes();
+    let corrupt = |level: usize, index: usize| {
+        let node = mt.subtree_root(level, index).unwrap();
+        let at = mt.as_slice().iter().position(|h| *h == node).unwrap();
+        let mut bytes = bytes.clone();
+        bytes[at * 32] ^= 1;
+        Packed::from_packed_bytes(&bytes, mt.leafs()).unwrap()
+    };
+
+    let broken = corrupt(2, 1);
+    assert_eq!(broken.first_mismatch(), Some((2, 1)));
+    assert!(!broken.verify_self());
+    assert_eq!(corrupt(1, 6).first_mismatch(), Some((1, 6)));
+    assert_eq!(
+        corrupt(mt.height() - 1, 0).first_mismatch(),
+        Some((mt.height() - 1, 0))
+    );
+
+    // leafs are not hashed from anything, their parents mismatch
+    assert_eq!(corrupt(0, 9).first_mismatch(), Some((1, 4)));
+
+    for &policy in &[PaddingPolicy::PromoteOdd, PaddingPolicy::HashWithZero] {
+        let mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_data_with_policy(0..13u64, policy);
+        assert_eq!(mt.first_mismatch(), None);
+    }
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(empty.first_mismatch(), None);
+}
+
+#[test]
+fn test_into_leaves() {
+    for leafs in 0..20u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        let n = mt.leafs();
+        assert_eq!(mt.leaves_slice().len(), n);
+        assert_eq!(mt.leaves_slice(), &mt.as_slice()[..n]);
+
+        let root = mt.root();
+        let leaves = mt.into_leaves();
+        assert_eq!(leaves.len(), n);
+        let back: MerkleTree<Item, DefaultHasher> = MerkleTree::from_leaf_hashes(leaves.clone());
+        assert_eq!(back.root(), root);
+
+        // leaf hashes, not the data hashes the tree is built from
+        if n > 0 {
+            let rehashed: MerkleTree<Item, DefaultHasher> = MerkleTree::new(leaves);
+            assert_ne!(rehashed.root(), root);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "tree is pruned")]
+fn test_into_leaves_pruned() {
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..8u64);
+    mt.prune_to_depth(1);
+    mt.into_leaves();
+}
+
+#[test]
+fn test_subtree() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..16u64);
+
+    // left half
+    let left = mt.subtree(3, 0).unwrap();
+    assert_eq!(left.leafs(), 8);
+    assert_eq!(Some(left.root()), mt.subtree_root(3, 0));
+    assert_eq!(left, MerkleTree::from_data(0..8u64));
+    for i in 0..left.leafs() {
+        let p = left.gen_proof(i);
+        assert!(p.validate::<DefaultHasher>());
+        assert_eq!(p.item(), mt[i]);
+        assert_eq!(p.lemma()[1..4], mt.gen_proof(i).lemma()[1..4]);
+    }
+    assert_eq!(mt.subtree(4, 0).unwrap(), mt);
+    assert_eq!(mt.subtree(2, 3).unwrap(), MerkleTree::from_data(12..16u64));
+    assert_eq!(mt.subtree(0, 5).unwrap().root(), mt[5]);
+    assert!(mt.subtree(3, 2).is_none());
+    assert!(mt.subtree(5, 0).is_none());
+
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+    for &policy in &policies {
+        for leafs in 1..20u64 {
+            let values: Vec<Item> = (0..leafs).map(Item).collect();
+            let mt: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new()
+                .padding(policy)
+                .build(values.clone());
+            for level in 0..mt.height() {
+                for index in 0..mt.leafs().div_ceil(1 << level) {
+                    let range = mt.leaf_range(level, index);
+                    let sub = match mt.subtree(level, index) {
+                        Some(sub) => sub,
+                        None => {
+                            assert_ne!(policy, PaddingPolicy::PromoteOdd);
+                            assert!(range.len() <= 1 << (level - 1));
+                            continue;
+                        }
+                    };
+                    assert_eq!(Some(sub.root()), mt.subtree_root(level, index));
+                    let rebuilt: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new()
+                        .padding(policy)
+                        .build(values[range.clone()].to_vec());
+                    assert_eq!(sub, rebuilt);
+                    assert!(sub.verify_self());
+                    assert!(sub.gen_proof(0).validate::<DefaultHasher>());
+                }
+            }
+        }
+    }
+
+    // retained leaf values are copied
+    let values: Vec<Item> = (0..13).map(Item).collect();
+    let retained: RetainedMerkleTree<Item, DefaultHasher, Item> =
+        MerkleTree::retain_leaves(values.clone());
+    let sub = retained.subtree(2, 1).unwrap();
+    assert_eq!(sub.values(), &values[4..8]);
+    let rebuilt: MerkleTree<Item, DefaultHasher> = sub.rebuild_with();
+    assert_eq!(&rebuilt, sub.tree());
+
+    let mut pruned = mt.clone();
+    pruned.prune_to_depth(1);
+    assert!(pruned.subtree(3, 0).is_none());
+}
+
+#[test]
+fn test_proof_ref() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..13u64);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        let r = p.as_ref();
+        assert_eq!(r.validate::<DefaultHasher>(), p.validate::<DefaultHasher>());
+        assert!(r.validate::<DefaultHasher>());
+        assert_eq!(*r.item(), p.item());
+        assert_eq!(*r.root(), p.root());
+        assert_eq!(r.index(), Some(i));
+        assert_eq!(r.to_proof(), p);
+
+        // borrowed right from the buffers
+        let lemma = p.lemma().to_vec();
+        let mut path = p.path().to_vec();
+        assert!(ProofRef::new(&lemma, &path).validate::<DefaultHasher>());
+        let top = path.len() - 1;
+        path[top] = !path[top];
+        let flipped = ProofRef::new(&lemma, &path);
+        assert!(!flipped.validate::<DefaultHasher>());
+        assert!(!flipped.to_proof().validate::<DefaultHasher>());
+        assert_eq!(ProofRef::from(&p), r);
+    }
+
+    // the last leaf is its own sibling, so only the index tells the
+    // replayed path
+    let p = mt.gen_proof(12);
+    let mut path = p.path().to_vec();
+    path[0] = !path[0];
+    let replayed = Proof::new(p.lemma().to_vec(), path.clone());
+    assert!(replayed.as_ref().validate::<DefaultHasher>());
+
+    let replayed = replayed.with_index(12);
+    assert!(!replayed.validate::<DefaultHasher>());
+    assert!(!replayed.as_ref().validate::<DefaultHasher>());
+    assert!(!ProofRef::new(p.lemma(), &path)
+        .with_index(12)
+        .validate::<DefaultHasher>());
+}
+
+#[test]
+fn test_proof_len() {
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+    for &policy in &policies {
+        for leafs in [1u64, 2, 3, 5, 8, 13, 64, 100] {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::from_data_with_policy(0..leafs, policy);
+            for i in 0..mt.leafs() {
+                assert_eq!(mt.proof_len(i), mt.gen_proof(i).lemma().len());
+                if policy != PaddingPolicy::PromoteOdd {
+                    assert_eq!(mt.proof_len(i), mt.height() + 1);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_verify_batch() {
+    type Tree = MerkleTree<Item, DefaultHasher>;
+
+    let mt: Tree = MerkleTree::from_data(0..11u64);
+    let root = mt.root();
+    let mut batch: Vec<(usize, Item, Proof<Item>)> = (0..mt.leafs())
+        .map(|i| (i, mt[i], mt.gen_proof(i)))
+        .collect();
+    assert_eq!(Tree::verify_batch(&root, &batch), Ok(()));
+    assert_eq!(Tree::verify_batch(&root, &[]), Ok(()));
+
+    // proof of another tree
+    let other: Tree = MerkleTree::from_data(1..12u64);
+    let mut mixed = batch.clone();
+    mixed[7] = (7, other[7], other.gen_proof(7));
+    assert_eq!(Tree::verify_batch(&root, &mixed), Err(7));
+
+    // tampered sibling
+    let mut lemma = batch[4].2.lemma().to_vec();
+    lemma[2] = Item(lemma[2].0 ^ 1);
+    batch[4].2 = Proof::new(lemma, batch[4].2.path().to_vec());
+    assert_eq!(Tree::verify_batch(&root, &batch), Err(4));
+    assert_eq!(Tree::verify_batch(&root, &batch[5..]), Ok(()));
+}
+
+#[test]
+fn test_builder() {
+    type Tree = MerkleTree<Item, DefaultHasher>;
+
+    for leafs in [0u64, 1, 2, 3, 5, 8, 13] {
+        let mt: Tree = MerkleTreeBuilder::new().build(0..leafs);
+        assert_eq!(mt.as_slice(), Tree::from_data(0..leafs).as_slice());
+        assert!(mt.domain_separation());
+
+        let mt: Tree = MerkleTreeBuilder::new()
+            .padding(PaddingPolicy::PromoteOdd)
+            .build(0..leafs);
+        let direct = Tree::from_data_with_policy(0..leafs, PaddingPolicy::PromoteOdd);
+        assert_eq!(mt.as_slice(), direct.as_slice());
+        assert_eq!(mt.policy(), direct.policy());
+
+        let mt: Tree = MerkleTreeBuilder::new()
+            .ordering(NodeOrder::Sorted)
+            .build(0..leafs);
+        let direct = Tree::from_data_with_order(0..leafs, NodeOrder::Sorted);
+        assert_eq!(mt.as_slice(), direct.as_slice());
+        assert_eq!(mt.order(), direct.order());
+
+        let keyed = KeyedHasher::new(b"secret");
+        let mt: MerkleTree<Item, KeyedHasher> = MerkleTreeBuilder::new()
+            .hasher(keyed.clone())
+            .build(0..leafs);
+        let direct: MerkleTree<Item, KeyedHasher> =
+            MerkleTree::from_data_with_hasher(0..leafs, keyed);
+        assert_eq!(mt.as_slice(), direct.as_slice());
+    }
+}
+
+#[test]
+fn test_builder_without_domain_separation() {
+    let unprefixed: MerkleTree<Item, Unprefixed<DefaultHasher>> = MerkleTree::from_data(0..7u64);
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new()
+        .domain_separation(false)
+        .build(0..7u64);
+    assert!(!mt.domain_separation());
+    assert_eq!(mt.as_slice(), unprefixed.as_slice());
+    assert_ne!(
+        mt.root(),
+        MerkleTree::<Item, DefaultHasher>::from_data(0..7u64).root()
+    );
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate::<Unprefixed<DefaultHasher>>());
+        assert!(!p.validate::<DefaultHasher>());
+    }
+
+    // appended leafs are hashed the same way
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new()
+        .domain_separation(false)
+        .build(0..5u64);
+    mt.extend((5..7u64).map(|x| {
+        let mut a = DefaultHasher::default();
+        x.hash(&mut a);
+        a.hash()
+    }));
+    assert_eq!(mt.as_slice(), unprefixed.as_slice());
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new()
+        .domain_separation(false)
+        .ordering(NodeOrder::Sorted)
+        .build(0..7u64);
+    for i in 0..mt.leafs() {
+        assert!(mt
+            .gen_proof(i)
+            .validate_sorted::<Unprefixed<DefaultHasher>>());
+    }
+}
+
+#[test]
+fn test_update_leaf() {
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+
+    // xorshift, reproducible without a rng dependency
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut rand = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    for &policy in &policies {
+        for order in [NodeOrder::Positional, NodeOrder::Sorted] {
+            for leafs in [1usize, 2, 3, 5, 7, 8, 13, 33] {
+                let mut items: Vec<u64> = (0..leafs as u64).collect();
+                let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new()
+                    .padding(policy)
+                    .ordering(order)
+                    .build(&items);
+                for _ in 0..20 {
+                    let i = rand() as usize % leafs;
+                    items[i] = rand();
+                    let mut a = DefaultHasher::default();
+                    items[i].hash(&mut a);
+                    assert_eq!(mt.update_leaf(i, a.hash()), Ok(()));
+
+                    let fresh: MerkleTree<Item, DefaultHasher> = MerkleTreeBuilder::new()
+                        .padding(policy)
+                        .ordering(order)
+                        .build(&items);
+                    assert_eq!(mt.as_slice(), fresh.as_slice());
+                }
+            }
+        }
+    }
+
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let before = mt.as_slice().to_vec();
+    assert_eq!(
+        mt.update_leaf(5, Item(0)),
+        Err(MerkleError::IndexOutOfBounds(5))
+    );
+    assert_eq!(mt.as_slice(), &before[..]);
+
+    let mut empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(
+        empty.update_leaf(0, Item(0)),
+        Err(MerkleError::IndexOutOfBounds(0))
+    );
+}
+
+#[test]
+fn test_tree_eq_hash() {
+    use std::collections::HashSet;
+
+    type Tree = MerkleTree<Item, DefaultHasher>;
+
+    let mut set: HashSet<Tree> = HashSet::new();
+    assert!(set.insert(Tree::from_data(0..7u64)));
+    assert!(!set.insert(Tree::from_data(0..7u64)));
+    assert!(set.insert(Tree::from_data(0..8u64)));
+    assert!(set.insert(Tree::from_data(1..8u64)));
+    assert_eq!(set.len(), 3);
+
+    let mut grown = Tree::from_data(0..5u64);
+    grown.extend((5..7u64).map(|x| {
+        let mut a = DefaultHasher::default();
+        x.hash(&mut a);
+        a.hash()
+    }));
+    assert!(set.contains(&grown));
+    assert_eq!(grown, Tree::from_data(0..7u64));
+
+    // same nodes, one leaf less
+    let mut a = DefaultHasher::default();
+    2u64.hash(&mut a);
+    let h2 = a.hash();
+    let short = Tree::from_data(0..3u64);
+    let long = Tree::new(
+        (0..2u64)
+            .map(|x| {
+                let mut a = DefaultHasher::default();
+                x.hash(&mut a);
+                a.hash()
+            })
+            .chain([h2, h2]),
+    );
+    assert_eq!(short.as_slice(), long.as_slice());
+    assert_ne!(short, long);
+}
+
+#[test]
+fn test_witness() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..8u64);
+
+    let w = mt.witness(0);
+    assert_eq!(w.len(), 3);
+    assert!(w.iter().all(|e| e.side == Side::Right));
+    assert_eq!(w[0].sibling, mt[1]);
+    assert_eq!(w[1].sibling, mt[9]);
+    assert_eq!(w[2].sibling, mt[13]);
+
+    let w = mt.witness(5);
+    let sides: Vec<Side> = w.iter().map(|e| e.side).collect();
+    assert_eq!(sides, [Side::Left, Side::Right, Side::Left]);
+
+    for policy in [PaddingPolicy::DuplicateLast, PaddingPolicy::PromoteOdd] {
+        let mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_data_with_policy(0..11u64, policy);
+        for i in 0..mt.leafs() {
+            let p = mt.gen_proof(i);
+            let w = mt.witness(i);
+            let siblings: Vec<Item> = w.iter().map(|e| e.sibling).collect();
+            let sides: Vec<bool> = w.iter().map(|e| e.side == Side::Right).collect();
+            assert_eq!(&siblings[..], &p.lemma()[1..p.lemma().len() - 1]);
+            assert_eq!(&sides[..], p.path());
+        }
+    }
+}
+
+#[test]
+fn test_prune_to_depth() {
+    for leafs in [1u64, 2, 5, 8, 13] {
+        let full: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        let height = full.height();
+
+        for depth in 0..height + 1 {
+            let mut mt = full.clone();
+            mt.prune_to_depth(depth);
+            assert_eq!(mt.root(), full.root());
+            assert_eq!(mt.height(), height);
+            assert_eq!(mt.leafs(), full.leafs());
+
+            let pruned = height - (depth + 1).min(height);
+            assert_eq!(mt.is_pruned(), pruned > 0);
+            assert_eq!(mt.levels().count(), height - pruned);
+            for (a, b) in mt.levels().zip(full.levels().skip(pruned)) {
+                assert_eq!(a, b);
+            }
+
+            // kept levels are still addressable
+            for level in 0..height {
+                for index in 0..full.leafs() {
+                    let expected = if level < pruned {
+                        None
+                    } else {
+                        full.subtree_root(level, index)
+                    };
+                    assert_eq!(mt.subtree_root(level, index), expected);
+                }
+            }
+
+            if pruned == 0 {
+                assert_eq!(mt, full);
+                for i in 0..mt.leafs() {
+                    assert!(mt.try_gen_proof(i).unwrap().validate::<DefaultHasher>());
+                }
+            } else {
+                assert_eq!(mt.try_gen_proof(0).err(), Some(MerkleError::Pruned));
+                assert_eq!(mt.gen_multi_proof(&[0]).err(), Some(MerkleError::Pruned));
+                assert_eq!(mt.update_leaf(0, Item(0)), Err(MerkleError::Pruned));
+                assert_eq!(mt.index_of(&full[0]), None);
+                assert_eq!(mt.gen_proof_for(&full[0]), None);
+                assert!(mt.as_slice().len() < full.as_slice().len());
+            }
+
+            // pruning deeper than before keeps the tree
+            let before = mt.clone();
+            mt.prune_to_depth(depth + 1);
+            assert_eq!(mt, before);
+        }
+    }
+
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..8u64);
+    let root = mt.root();
+    mt.prune_to_depth(0);
+    assert_eq!(mt.as_slice(), &[root]);
+    assert_eq!(mt.into_root_only(), root);
+}
+
+#[test]
+#[should_panic(expected = "tree is pruned")]
+fn test_prune_push() {
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..8u64);
+    mt.prune_to_depth(1);
+    mt.push(Item(8));
+}
+
+/// Sip hasher which is neither `Send` nor `Sync`.
+#[derive(Default)]
+struct LocalHasher(DefaultHasher, core::marker::PhantomData<*const ()>);
+
+impl Hasher for LocalHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for LocalHasher {
+    fn hash(&mut self) -> Item {
+        Item(self.0.finish())
+    }
+}
+
+#[test]
+fn test_shared_across_threads() {
+    use std::sync::Arc;
+    use std::thread;
+
+    fn assert_send_sync<X: Send + Sync>() {}
+    assert_send_sync::<MerkleTree<Item, LocalHasher>>();
+    assert_send_sync::<crate::cached::CachedMerkleTree<Item, LocalHasher>>();
+    assert_send_sync::<crate::nary::NaryMerkleTree<Item, LocalHasher, 4>>();
+
+    let mt: Arc<MerkleTree<Item, LocalHasher>> = Arc::new(MerkleTree::from_data(0..64u64));
+    let workers: Vec<_> = (0..8)
+        .map(|t| {
+            let mt = Arc::clone(&mt);
+            thread::spawn(move || {
+                (t * 8..t * 8 + 8)
+                    .map(|i| {
+                        let p = mt.gen_proof(i);
+                        assert!(p.validate::<LocalHasher>());
+                        assert_eq!(p.root(), mt.root());
+                        (i, p)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut proven = 0;
+    for w in workers {
+        for (i, p) in w.join().unwrap() {
+            assert_eq!(p, mt.gen_proof(i));
+            proven += 1;
+        }
+    }
+    assert_eq!(proven, mt.leafs());
+}
+
+#[test]
+fn test_root_ref() {
+    for leafs in [0u64, 1, 2, 7, 16] {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        assert_eq!(*mt.root_ref(), mt.root());
+        assert!(core::ptr::eq(mt.root_ref(), &mt[mt.len() - 1]));
+    }
+
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..16u64);
+    mt.prune_to_depth(1);
+    assert_eq!(*mt.root_ref(), mt.root());
+}
+
+#[test]
+fn test_leaf_proofs() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..16u64);
+    let mut n = 0;
+    for (i, leaf, proof) in mt.leaf_proofs() {
+        assert_eq!(i, n);
+        assert_eq!(*leaf, mt[i]);
+        assert_eq!(proof.item(), *leaf);
+        assert_eq!(proof.root(), mt.root());
+        assert!(proof.validate::<DefaultHasher>());
+        assert!(MerkleTree::<Item, DefaultHasher>::verify_proof(
+            &mt.root(),
+            &proof,
+            leaf,
+            i
+        ));
+        n += 1;
+    }
+    assert_eq!(n, 16);
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(mt.leaf_proofs().count(), 0);
+}
+
+/// Hash wrapper which deliberately does not implement `Ord`, of the same
+/// bytes as `Item`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Digest([u8; 8]);
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[derive(Default)]
+struct DigestHasher(DefaultHasher);
+
+impl Hasher for DigestHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Digest> for DigestHasher {
+    fn hash(&mut self) -> Digest {
+        Digest(self.0.finish().to_ne_bytes())
+    }
+
+    fn reset(&mut self) {
+        self.0 = DefaultHasher::default()
+    }
+}
+
+#[test]
+fn test_unordered_hash() {
+    for order in [NodeOrder::Positional, NodeOrder::Sorted] {
+        let mt: MerkleTree<Digest, DigestHasher> =
+            MerkleTree::from_data_with_order(0..13u64, order);
+        let items: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_data_with_order(0..13u64, order);
+        assert_eq!(mt.root().as_ref(), items.root().as_ref());
+
+        for i in 0..mt.leafs() {
+            let p = mt.gen_proof(i);
+            match order {
+                NodeOrder::Positional => assert!(p.validate::<DigestHasher>()),
+                NodeOrder::Sorted => assert!(p.validate_sorted::<DigestHasher>()),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_from_leaf_hashes() {
+    for leafs in [0u64, 1, 2, 3, 7, 16] {
+        let mut a = DefaultHasher::default();
+        let hashes: Vec<Item> = (0..leafs)
+            .map(|x| {
+                a.reset();
+                x.hash(&mut a);
+                let h = a.hash();
+                a.reset();
+                a.leaf(h)
+            })
+            .collect();
+
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..leafs);
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_leaf_hashes(hashes.clone());
+        assert_eq!(mt, expected);
+        assert_eq!(MerkleTree::from(hashes.clone()), expected);
+        assert_eq!(mt[..mt.leafs()], hashes[..]);
+        for i in 0..mt.leafs() {
+            assert!(mt.gen_proof(i).validate::<DefaultHasher>());
+        }
+
+        // `new` hashes the leafs once more
+        if leafs > 0 {
+            let rehashed: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes);
+            assert_ne!(rehashed.root(), mt.root());
+        }
+    }
+}
+
+#[test]
+fn test_get_leaf_node() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    // [h0 h1 h2 h3 h4 h4] [h01 h23 h44 h44] [h0123 h4444] [root]
+    assert_eq!(mt.height(), 4);
+    for i in 0..5 {
+        assert_eq!(mt.get_leaf(i), Some(&mt[i]));
+        assert_eq!(mt.get_node(0, i), Some(&mt[i]));
+    }
+    assert_eq!(mt.get_leaf(5), None);
+    assert_eq!(mt.get_leaf(usize::MAX), None);
+
+    for (level, width) in [(1, 3), (2, 2), (3, 1)] {
+        for i in 0..width {
+            assert_eq!(mt.get_node(level, i).cloned(), mt.subtree_root(level, i));
+        }
+        assert_eq!(mt.get_node(level, width), None);
+    }
+    assert_eq!(mt.get_node(1, 0), Some(&mt[6]));
+    assert_eq!(mt.get_node(2, 1), Some(&mt[11]));
+    assert_eq!(mt.get_node(3, 0), Some(mt.root_ref()));
+    assert_eq!(mt.get_node(4, 0), None);
+
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(empty.get_leaf(0), None);
+    assert_eq!(empty.get_node(0, 0), None);
+
+    let mut pruned: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    pruned.prune_to_depth(1);
+    assert_eq!(pruned.get_leaf(0), None);
+    assert_eq!(pruned.get_node(2, 1), Some(&mt[11]));
+}
+
+#[test]
+fn test_diff() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..16u64);
+    let other: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data((0..16u64).map(|x| if x == 3 || x == 12 { x + 100 } else { x }));
+    assert_eq!(mt.diff(&other), Ok(vec![3, 12]));
+    assert_eq!(other.diff(&mt), Ok(vec![3, 12]));
+    assert_eq!(mt.diff(&mt), Ok(vec![]));
+
+    // the padded last leaf of the odd tree
+    let odd: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..13u64);
+    let changed: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..12u64).chain([99]));
+    assert_eq!(odd.diff(&changed), Ok(vec![12]));
+
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(empty.diff(&empty), Ok(vec![]));
+
+    assert_eq!(mt.diff(&odd), Err(MerkleError::LeafsMismatch(16, 13)));
+    assert_eq!(
+        MerkleError::LeafsMismatch(16, 13).to_string(),
+        "trees have 16 and 13 leafs"
+    );
+    let mut pruned = other.clone();
+    pruned.prune_to_depth(1);
+    assert_eq!(mt.diff(&pruned), Err(MerkleError::Pruned));
+}
+
+#[test]
+fn test_too_large() {
+    for n in [usize::MAX / 2 + 2, usize::MAX / 2 + 1, usize::MAX] {
+        assert_eq!(
+            MerkleTree::<Item, DefaultHasher>::try_with_capacity(n),
+            Err(MerkleError::TooLarge)
+        );
+    }
+    assert_eq!(MerkleError::TooLarge.to_string(), "tree is too large");
+
+    let mt = MerkleTree::<Item, DefaultHasher>::try_with_capacity(10).unwrap();
+    assert_eq!(mt, MerkleTree::empty());
+
+    // size hints of too large a tree are no hints
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..3u64);
+    let hinted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data((0..usize::MAX).take_while(|&x| x < 3).map(|x| x as u64));
+    assert_eq!(hinted, expected);
+    let built = MerkleTreeBuilder::<Item, DefaultHasher>::new()
+        .capacity(usize::MAX)
+        .build(0..3u64);
+    assert_eq!(built, expected);
+}
+
+#[test]
+fn test_leaf_range() {
+    // [h0 .. h9] [h01 h23 h45 h67 h89 h89] [h0-3 h4-7 h8-9 h8-9] [h0-7 h8-9] [root]
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..10u64);
+    assert_eq!(mt.leaf_range(0, 0), 0..1);
+    assert_eq!(mt.leaf_range(0, 9), 9..10);
+    assert_eq!(mt.leaf_range(1, 2), 4..6);
+    assert_eq!(mt.leaf_range(1, 4), 8..10);
+    assert_eq!(mt.leaf_range(2, 1), 4..8);
+    // padded edges
+    assert_eq!(mt.leaf_range(2, 2), 8..10);
+    assert_eq!(mt.leaf_range(3, 1), 8..10);
+    assert_eq!(mt.leaf_range(4, 0), 0..10);
+
+    let promoted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy(0..10u64, PaddingPolicy::PromoteOdd);
+    for (level, width) in [(0, 10), (1, 5), (2, 3), (3, 2), (4, 1)] {
+        for i in 0..width {
+            assert_eq!(promoted.leaf_range(level, i), mt.leaf_range(level, i));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "no node 3 at level 2")]
+fn test_leaf_range_padding() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..10u64);
+    mt.leaf_range(2, 3);
+}
+
+std::thread_local! {
+    static HASHES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Counts the hashes computed.
+#[derive(Clone, Default)]
+struct CountingHasher(DefaultHasher);
+
+impl Hasher for CountingHasher {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for CountingHasher {
+    fn hash(&mut self) -> Item {
+        HASHES.with(|n| n.set(n.get() + 1));
+        Item(self.finish())
+    }
+}
+
+#[test]
+fn test_merge() {
+    type Tree = MerkleTree<Item, CountingHasher>;
+
+    for (l, r) in [(5, 6), (3, 1), (1, 1), (6, 10), (0, 4), (4, 0), (0, 0)] {
+        let left: Tree = MerkleTree::from_data(0..l);
+        let right: Tree = MerkleTree::from_data(l..l + r);
+        let expected: Tree = MerkleTree::from_data(0..l + r);
+        let merged = left.merge(right);
+        assert_eq!(merged, expected);
+        assert_eq!(merged.root(), expected.root());
+        assert_eq!(merged.leafs(), l + r);
+    }
+
+    // only the nodes over the right leafs are hashed, the left tree is
+    // the left subtree of the result
+    let left: Tree = MerkleTree::from_data(0..8u64);
+    let right: Tree = MerkleTree::from_data(8..13u64);
+    HASHES.with(|n| n.set(0));
+    let merged = left.clone().merge(right);
+    // [8 + 5] [4 + 3] [2 + 2] [1 + 1] [root]
+    assert_eq!(HASHES.with(|n| n.get()), 3 + 2 + 1 + 1);
+    assert_eq!(merged, MerkleTree::from_data(0..13u64));
+    assert_eq!(merged.subtree_root(3, 0), Some(left.root()));
+}
+
+#[test]
+#[should_panic(expected = "trees are built by different rules")]
+fn test_merge_different_rules() {
+    let left: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..4u64);
+    let right: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with_policy(4..8u64, PaddingPolicy::PromoteOdd);
+    left.merge(right);
+}
+
+#[test]
+fn test_combine_partials() {
+    type Tree = MerkleTree<Item, DefaultHasher>;
+
+    let data: Vec<Item> = (0..16).map(Item).collect();
+    let partials: Vec<(Range<usize>, Item)> = (0..4)
+        .map(|k| {
+            (
+                4 * k..4 * k + 4,
+                Tree::partial_root(&data, 4 * k..4 * k + 4),
+            )
+        })
+        .collect();
+    let mt: Tree = MerkleTree::new(data.clone());
+    assert_eq!(partials[1].1, mt.subtree_root(2, 1).unwrap());
+    assert_eq!(Tree::combine_partials(&partials), Ok(mt.root()));
+
+    // any order, then chunks of different sizes
+    let mut shuffled = partials.clone();
+    shuffled.swap(0, 3);
+    assert_eq!(Tree::combine_partials(&shuffled), Ok(mt.root()));
+    let mixed: Vec<(Range<usize>, Item)> = [0..8, 8..12, 12..14, 14..15, 15..16]
+        .iter()
+        .map(|chunk| (chunk.clone(), Tree::partial_root(&data, chunk.clone())))
+        .collect();
+    assert_eq!(Tree::combine_partials(&mixed), Ok(mt.root()));
+
+    // short last chunks
+    for leafs in 1..20 {
+        let data: Vec<Item> = (0..leafs).map(Item).collect();
+        let n = data.len();
+        let mt: Tree = MerkleTree::new(data.clone());
+        let chunks: Vec<Range<usize>> = (0..n).step_by(4).map(|s| s..(s + 4).min(n)).collect();
+        let partials: Vec<(Range<usize>, Item)> = chunks
+            .iter()
+            .map(|chunk| (chunk.clone(), Tree::partial_root(&data, chunk.clone())))
+            .collect();
+        assert_eq!(Tree::combine_partials(&partials), Ok(mt.root()));
+    }
+
+    let invalid = |chunks: &[Range<usize>]| {
+        let partials: Vec<(Range<usize>, Item)> = chunks
+            .iter()
+            .map(|chunk| (chunk.clone(), Item(0)))
+            .collect();
+        Tree::combine_partials(&partials)
+    };
+    assert_eq!(invalid(&[]), Err(MerkleError::EmptyTree));
+    assert_eq!(invalid(&[0..4, 8..16]), Err(MerkleError::InvalidChunks));
+    assert_eq!(invalid(&[0..8, 4..16]), Err(MerkleError::InvalidChunks));
+    assert_eq!(invalid(&[0..6, 6..16]), Err(MerkleError::InvalidChunks));
+    assert_eq!(
+        invalid(&[0..4, 4..12, 12..16]),
+        Err(MerkleError::InvalidChunks)
+    );
+    assert_eq!(
+        MerkleError::InvalidChunks.to_string(),
+        "chunks do not tile the tree leafs"
+    );
+}
+
+#[test]
+#[should_panic(expected = "chunk 2..6 is not a subtree of 16 leafs")]
+fn test_partial_root_unaligned() {
+    let data: Vec<Item> = (0..16).map(Item).collect();
+    MerkleTree::<Item, DefaultHasher>::partial_root(&data, 2..6);
+}
+
+#[test]
+fn test_debug_render() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..4u64);
+    assert_eq!(
+        format!("{:?}", mt),
+        "MerkleTree { leafs: 4, height: 3 }
+  2: 7bdb6b17
+  1: 83bb3e35 1ff7eab6
+  0: 8f1f8d76 17ebb5ce 60d53b46 99a16d89"
+    );
+    assert_eq!(
+        mt.fmt_tree(),
+        "7bdb6b17
++-- 83bb3e35
+|   +-- 8f1f8d76
+|   `-- 17ebb5ce
+`-- 1ff7eab6
+    +-- 60d53b46
+    `-- 99a16d89
+"
+    );
+
+    // padding nodes are not rendered
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..3u64);
+    assert!(mt.fmt_tree().ends_with("`-- 319ed0fc\n    `-- 60d53b46\n"));
+
+    let mut pruned: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..4u64);
+    pruned.prune_to_depth(1);
+    assert_eq!(
+        format!("{:?}", pruned),
+        "MerkleTree { leafs: 4, height: 3 }
+  2: 7bdb6b17
+  1: 83bb3e35 1ff7eab6"
+    );
+    assert_eq!(pruned.fmt_tree(), "7bdb6b17\n+-- 83bb3e35\n`-- 1ff7eab6\n");
+
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(
+        format!("{:?}", empty),
+        "MerkleTree { leafs: 0, height: 0 }\n  root: 2c530c15"
+    );
+}
+
+#[test]
+fn test_hash_bytes() {
+    let mut manual = DefaultHasher::new();
+    manual.write(b"hello");
+    let expected = manual.hash();
+
+    let mut a = DefaultHasher::new();
+    assert_eq!(a.hash_bytes(b"hello"), expected);
+
+    // previous writes are discarded
+    a.write(b"garbage");
+    assert_eq!(a.hash_bytes(b"hello"), expected);
+    assert_ne!(a.hash_bytes(b"hello!"), expected);
+
+    // keyed state is kept by `reset`
+    let mut keyed = ResetKeyedHasher::new(b"secret");
+    let mut manual = ResetKeyedHasher::new(b"secret");
+    manual.write(b"hello");
+    keyed.write(b"garbage");
+    assert_eq!(keyed.hash_bytes(b"hello"), manual.hash());
+}
+
+std::thread_local! {
+    static BATCHED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Counts the leaves hashed in batches.
+#[derive(Default)]
+struct BatchHasher(DefaultHasher);
+
+impl Hasher for BatchHasher {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for BatchHasher {
+    fn hash(&mut self) -> Item {
+        Item(self.finish())
+    }
+
+    fn hash_leaves_batch(&mut self, leaves: &[&[u8]]) -> Vec<Item> {
+        BATCHED.with(|n| n.set(n.get() + leaves.len()));
+        leaves.iter().map(|leaf| self.hash_bytes(leaf)).collect()
+    }
+}
+
+#[test]
+fn test_hash_leaves_batch() {
+    let data: [&[u8]; 4] = [b"", b"foo", b"bar", b"bazz"];
+    let mut a = DefaultHasher::new();
+    let single: Vec<Item> = data.iter().map(|x| a.hash_bytes(x)).collect();
+    a.write(b"garbage");
+    assert_eq!(a.hash_leaves_batch(&data), single);
+    assert!(a.hash_leaves_batch(&[]).is_empty());
+
+    // construction goes through the batch
+    BATCHED.with(|n| n.set(0));
+    let batched: MerkleTree<Item, BatchHasher> = MerkleTree::from_byte_slices(data);
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_byte_slices(data);
+    assert_eq!(BATCHED.with(|n| n.get()), data.len());
+    assert_eq!(batched.root(), expected.root());
+}
+
+/// FNV-1a 64 as a plain function.
+struct Fnv;
+
+impl crate::hash::HashFn<Item> for Fnv {
+    fn hash(data: &[u8]) -> Item {
+        Item(data.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+            (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        }))
+    }
+}
+
+#[test]
+fn test_keyed_algorithm() {
+    use crate::hash::KeyedAlgorithm;
+    type Keyed = KeyedAlgorithm<DefaultHasher>;
+
+    let build = |algo: &Keyed| -> MerkleTree<Item, Keyed> {
+        MerkleTreeBuilder::new().hasher(algo.clone()).build(0..7u64)
+    };
+    let app = Keyed::new(b"app leaf", b"app node");
+    let other = Keyed::new(b"other leaf", b"other node");
+    let swapped = Keyed::new(b"app node", b"app leaf");
+
+    let mt = build(&app);
+    let plain: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    assert_eq!(mt.leafs(), plain.leafs());
+    assert_ne!(mt.root(), plain.root());
+    assert_ne!(mt.root(), build(&other).root());
+    assert_ne!(mt.root(), build(&swapped).root());
+    assert_eq!(mt.root(), build(&app).root());
+
+    // unkeyed adapter is the inner algorithm
+    assert_eq!(build(&Keyed::default()).root(), plain.root());
+
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate_with_hasher(&app));
+        assert!(p.validate_with(&mut app.clone(), &mt.root()));
+        assert!(!p.validate_with_hasher(&other));
+        assert!(!p.validate::<Keyed>());
+    }
+    assert!(!std::format!("{:?}", app).contains("app"));
+}
+
+#[test]
+fn test_fn_algorithm() {
+    use crate::hash::{FnAlgorithm, HashFn};
+
+    let mut a = FnAlgorithm::<Fnv, Item>::default();
+    a.write(b"hel");
+    a.write(b"lo");
+    assert_eq!(a.hash(), Fnv::hash(b"hello"));
+    assert_eq!(a.finish(), Fnv::hash(b"hello").0);
+    a.reset();
+    assert_eq!(a.hash(), Fnv::hash(b""));
+
+    let mt: MerkleTree<Item, FnAlgorithm<Fnv, Item>> = MerkleTreeBuilder::new()
+        .padding(PaddingPolicy::PromoteOdd)
+        .build([b"a", b"b", b"c"]);
+    let leaf = |x: &[u8]| Fnv::hash(&[&[0u8][..], &Fnv::hash(x).0.to_le_bytes()].concat());
+    let node = |l: Item, r: Item| {
+        Fnv::hash(&[&[1u8][..], &l.0.to_le_bytes(), &r.0.to_le_bytes()].concat())
+    };
+    assert_eq!(mt.root(), node(node(leaf(b"a"), leaf(b"b")), leaf(b"c")));
+    for i in 0..mt.leafs() {
+        assert!(mt.gen_proof(i).validate::<FnAlgorithm<Fnv, Item>>());
+    }
+}
+
+#[test]
+fn test_leaf_builder() {
+    use crate::hash::LeafBuilder;
+
+    let mut leaf = LeafBuilder::<DefaultHasher>::new();
+    leaf.feed(b"first ");
+    leaf.feed(b"");
+    leaf.feed(b"second ").feed(b"third");
+    let h: Item = leaf.finish();
+
+    let mut a = DefaultHasher::new();
+    assert_eq!(h, a.hash_bytes(b"first second third"));
+
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_byte_slices([b"zero"]);
+    mt.push(h);
+    let expected: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_byte_slices([&b"zero"[..], b"first second third"]);
+    assert_eq!(mt, expected);
+}
+
+#[test]
+fn test_root_at_size() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    let fresh: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..4u64);
+    assert_eq!(mt.root_at_size(4), Some(fresh.root()));
+    assert_eq!(mt.root_at_size(7), Some(mt.root()));
+    assert_eq!(mt.root_at_size(0), None);
+    assert_eq!(mt.root_at_size(8), None);
+
+    let policies = [
+        PaddingPolicy::DuplicateLast,
+        PaddingPolicy::PromoteOdd,
+        PaddingPolicy::HashWithZero,
+    ];
+    for &policy in &policies {
+        for order in [NodeOrder::Positional, NodeOrder::Sorted] {
+            let tree = |n: u64| -> MerkleTree<Item, DefaultHasher> {
+                MerkleTreeBuilder::new()
+                    .padding(policy)
+                    .ordering(order)
+                    .build(0..n)
+            };
+            let mt = tree(21);
+            for k in 1..=21 {
+                assert_eq!(mt.root_at_size(k as usize), Some(tree(k).root()));
+            }
+        }
+    }
+
+    let mut pruned: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    pruned.prune_to_depth(1);
+    assert_eq!(pruned.root_at_size(4), None);
+}
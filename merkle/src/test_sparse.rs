@@ -0,0 +1,108 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::hash::Algorithm;
+use crate::sparse::SparseMerkleTree;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+type Key = [u8; 8];
+
+#[derive(Debug, Default)]
+struct Sip8(DefaultHasher);
+
+impl Hasher for Sip8 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Key> for Sip8 {
+    fn hash(&mut self) -> Key {
+        self.0.finish().to_be_bytes()
+    }
+}
+
+type Smt = SparseMerkleTree<Key, u64, Sip8>;
+
+fn key(k: u64) -> Key {
+    k.to_be_bytes()
+}
+
+#[test]
+fn test_sparse_empty() {
+    let smt = Smt::new();
+    assert_eq!(smt.depth(), 64);
+    assert!(smt.is_empty());
+    assert_eq!(smt.root(), smt.default_hash(64));
+
+    let p = smt.gen_proof(&key(7));
+    assert_eq!(p.lemma().len(), 66);
+    assert!(Smt::verify_proof(&smt.root(), &p, &key(7), None));
+    assert!(!Smt::verify_proof(&smt.root(), &p, &key(7), Some(&0)));
+}
+
+#[test]
+fn test_sparse_proofs() {
+    let keys = [0, 1, 2, 0xff, 1 << 63, u64::MAX, 0x1234_5678_9abc_def0];
+    let mut smt = Smt::new();
+    for (i, &k) in keys.iter().enumerate() {
+        assert_eq!(smt.insert(key(k), i as u64), None);
+    }
+    assert_eq!(smt.len(), keys.len());
+    let root = smt.root();
+
+    // inclusion
+    for (i, &k) in keys.iter().enumerate() {
+        let v = i as u64;
+        assert_eq!(smt.get(&key(k)), Some(&v));
+        let p = smt.gen_proof(&key(k));
+        assert!(p.validate::<Sip8>());
+        assert!(Smt::verify_proof(&root, &p, &key(k), Some(&v)));
+        assert!(!Smt::verify_proof(&root, &p, &key(k), Some(&(v + 1))));
+        assert!(!Smt::verify_proof(&root, &p, &key(k), None));
+        assert!(!Smt::verify_proof(&root, &p, &key(k ^ 1), Some(&v)));
+    }
+
+    // non-inclusion
+    for &k in &[3, 4, 0xfe, (1 << 63) + 1, u64::MAX - 1] {
+        assert_eq!(smt.get(&key(k)), None);
+        let p = smt.gen_proof(&key(k));
+        assert!(Smt::verify_proof(&root, &p, &key(k), None));
+        assert!(!Smt::verify_proof(&root, &p, &key(k), Some(&0)));
+    }
+}
+
+#[test]
+fn test_sparse_update() {
+    let mut smt = Smt::new();
+    smt.insert(key(1), 10);
+    smt.insert(key(2), 20);
+    let root = smt.root();
+    let p2 = smt.gen_proof(&key(2));
+
+    // updating one key changes the root
+    assert_eq!(smt.insert(key(1), 11), Some(10));
+    let updated = smt.root();
+    assert_ne!(updated, root);
+    assert!(!Smt::verify_proof(&updated, &p2, &key(2), Some(&20)));
+
+    // unrelated proofs are recomputed against the new root
+    let p2 = smt.gen_proof(&key(2));
+    assert!(Smt::verify_proof(&updated, &p2, &key(2), Some(&20)));
+
+    // removal restores the empty subtrees
+    assert_eq!(smt.remove(&key(1)), Some(11));
+    assert_eq!(smt.remove(&key(1)), None);
+    let mut only2 = Smt::new();
+    only2.insert(key(2), 20);
+    assert_eq!(smt.root(), only2.root());
+
+    smt.remove(&key(2));
+    assert_eq!(smt.root(), Smt::new().root());
+}
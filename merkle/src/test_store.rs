@@ -0,0 +1,115 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::store::{Store, VecStore};
+use alloc::vec::Vec;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+type Item = [u8; 8];
+
+#[derive(Debug, Default)]
+struct Sip8(DefaultHasher);
+
+impl Hasher for Sip8 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for Sip8 {
+    fn hash(&mut self) -> Item {
+        self.0.finish().to_be_bytes()
+    }
+}
+
+fn assert_same<S: Store<Item>>(mt: &MerkleTree<Item, Sip8, S>, vt: &MerkleTree<Item, Sip8>) {
+    assert_eq!(mt.root(), vt.root());
+    assert_eq!(mt.len(), vt.len());
+    assert_eq!(mt.leafs(), vt.leafs());
+    assert_eq!(mt.height(), vt.height());
+    for i in 0..vt.leafs() {
+        let p = mt.gen_proof(i);
+        assert_eq!(p, vt.gen_proof(i));
+        assert!(p.validate::<Sip8>());
+    }
+}
+
+#[test]
+fn test_vec_store() {
+    let mut s: VecStore<Item> = Vec::new();
+    assert!(Store::is_empty(&s));
+    Store::push(&mut s, [1; 8]);
+    Store::push(&mut s, [2; 8]);
+    s.set(0, [3; 8]);
+    assert_eq!(Store::len(&s), 2);
+    assert_eq!(Store::get(&s, 0), [3; 8]);
+    assert_eq!(Store::get(&s, 1), [2; 8]);
+}
+
+#[test]
+fn test_vec_store_tree() {
+    for items in 1..20u64 {
+        let vt: MerkleTree<Item, Sip8> = MerkleTree::from_data(0..items);
+        let mt: MerkleTree<Item, Sip8, VecStore<Item>> =
+            MerkleTree::from_data_with_store(0..items, Vec::new());
+        assert_same(&mt, &vt);
+        assert_eq!(mt.as_slice(), vt.as_slice());
+    }
+}
+
+#[cfg(feature = "mmap")]
+mod disk {
+    use super::*;
+    use crate::store::DiskStore;
+    use std::path::PathBuf;
+    use std::{env, fs, process};
+
+    fn temp(name: &str) -> PathBuf {
+        env::temp_dir().join(std::format!(
+            "merkle_light_{}_{}.nodes",
+            name,
+            process::id()
+        ))
+    }
+
+    #[test]
+    fn test_disk_store() {
+        let path = temp("store");
+        let mut s: DiskStore<Item> = DiskStore::new(&path).unwrap();
+        assert!(s.is_empty());
+        for i in 0..1000u64 {
+            s.push(i.to_be_bytes());
+        }
+        s.set(7, [0xff; 8]);
+        assert_eq!(s.len(), 1000);
+        assert_eq!(s.get(7), [0xff; 8]);
+        assert_eq!(s.get(999), 999u64.to_be_bytes());
+
+        s.flush().unwrap();
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(&bytes[8 * 999..8 * 1000], &999u64.to_be_bytes());
+
+        drop(s);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_disk_store_tree() {
+        let path = temp("tree");
+        for items in [0u64, 1, 2, 3, 7, 8, 9, 100, 1000] {
+            let vt: MerkleTree<Item, Sip8> = MerkleTree::from_data(0..items);
+            let store: DiskStore<Item> = DiskStore::new(&path).unwrap();
+            let mt = MerkleTree::from_data_with_store(0..items, store);
+            assert_same(&mt, &vt);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::merkle::{MerkleTree, PaddingPolicy};
+use crate::test_item::Item;
+use crate::verify::{verify_inclusion, ProofVerifier};
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_verify_inclusion() {
+    for &policy in &[PaddingPolicy::DuplicateLast, PaddingPolicy::HashWithZero] {
+        for leafs in 1..20u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::from_data_with_policy((0..leafs).map(Item), policy);
+            let root = mt.root();
+            for i in 0..mt.leafs() {
+                let p = mt.gen_proof(i);
+                let lemma = &p.lemma()[1..p.lemma().len() - 1];
+                assert!(p.validate::<DefaultHasher>());
+                assert!(verify_inclusion::<_, DefaultHasher>(
+                    &root, &mt[i], i, lemma
+                ));
+
+                assert!(!verify_inclusion::<_, DefaultHasher>(
+                    &Item(0),
+                    &mt[i],
+                    i,
+                    lemma
+                ));
+                assert!(!verify_inclusion::<_, DefaultHasher>(
+                    &root,
+                    &Item(mt[i].0 ^ 1),
+                    i,
+                    lemma
+                ));
+                // a duplicated last leaf is its own sibling
+                if mt.leafs() > 1 && lemma[0] != mt[i] {
+                    assert!(!verify_inclusion::<_, DefaultHasher>(
+                        &root,
+                        &mt[i],
+                        i ^ 1,
+                        lemma
+                    ));
+                }
+                // same bits, past the tree width
+                assert!(!verify_inclusion::<_, DefaultHasher>(
+                    &root,
+                    &mt[i],
+                    i + (1 << lemma.len()),
+                    lemma
+                ));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_proof_verifier() {
+    for leafs in 1..20u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data((0..leafs).map(Item));
+        for i in 0..mt.leafs() {
+            let p = mt.gen_proof(i);
+            let mut v = ProofVerifier::<DefaultHasher, Item>::new(p.item(), i);
+            for sibling in &p.lemma()[1..p.lemma().len() - 1] {
+                v.feed_sibling(*sibling);
+            }
+            assert_eq!(v.finalize() == mt.root(), p.validate::<DefaultHasher>());
+
+            // a sibling short
+            if mt.leafs() > 1 {
+                let mut v = ProofVerifier::<DefaultHasher, Item>::new(p.item(), i);
+                for sibling in &p.lemma()[1..p.lemma().len() - 2] {
+                    v.feed_sibling(*sibling);
+                }
+                assert_ne!(v.finalize(), mt.root());
+            }
+        }
+    }
+
+    // no siblings, the root is the leaf
+    let v = ProofVerifier::<DefaultHasher, Item>::new(Item(7), 0);
+    assert_eq!(v.finalize(), Item(7));
+}
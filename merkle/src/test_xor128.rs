@@ -4,13 +4,16 @@ extern crate std;
 
 use alloc::vec::Vec;
 
+use crate::error::MerkleError;
 use crate::hash::*;
 use crate::merkle::log2_pow2;
 use crate::merkle::next_pow2;
-use crate::merkle::MerkleTree;
+use crate::merkle::{MerkleTree, NodeOrder, PaddingPolicy};
+use crate::proof::Proof;
 use std::fmt;
 use std::hash::Hasher;
 use std::iter::FromIterator;
+#[cfg(not(feature = "scheme_v1"))]
 use std::string::String;
 
 const SIZE: usize = 0x10;
@@ -60,41 +63,40 @@ impl Algorithm<Item> for XOR128 {
 impl fmt::UpperHex for XOR128 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if f.alternate() {
-            if let Err(e) = f.write_str("0x") {
-                return Err(e);
-            }
+            f.write_str("0x")?;
         }
         for b in self.data.as_ref() {
-            if let Err(e) = write!(f, "{:02X}", b) {
-                return Err(e);
-            }
+            write!(f, "{:02X}", b)?;
         }
         Ok(())
     }
 }
 
 #[test]
+#[cfg(not(feature = "scheme_v1"))]
 fn test_hasher_light() {
     let mut h = XOR128::new();
-    "1234567812345678".hash(&mut h);
+    "123456781234567".hash(&mut h);
     h.reset();
-    String::from("1234567812345678").hash(&mut h);
-    assert_eq!(format!("{:#X}", h), "0x31323334353637383132333435363738");
-    String::from("1234567812345678").hash(&mut h);
+    // strings are terminated by 0xff
+    String::from("123456781234567").hash(&mut h);
+    assert_eq!(format!("{:#X}", h), "0x313233343536373831323334353637FF");
+    String::from("123456781234567").hash(&mut h);
     assert_eq!(format!("{:#X}", h), "0x00000000000000000000000000000000");
-    String::from("1234567812345678").hash(&mut h);
-    assert_eq!(format!("{:#X}", h), "0x31323334353637383132333435363738");
+    String::from("123456781234567").hash(&mut h);
+    assert_eq!(format!("{:#X}", h), "0x313233343536373831323334353637FF");
 }
 
 #[test]
+#[cfg(not(feature = "scheme_v1"))]
 fn test_from_slice() {
     let x = [String::from("ars"), String::from("zxc")];
     let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(&x);
     assert_eq!(
         mt.as_slice(),
         [
-            [0, 97, 114, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 122, 120, 99, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 97, 114, 115, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 122, 120, 99, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
             [1, 0, 27, 10, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         ]
     );
@@ -105,6 +107,8 @@ fn test_from_slice() {
         mt.root(),
         [1, 0, 27, 10, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
     );
+    assert_eq!(mt.root_hex(), "01001b0a100000000000000000000000");
+    assert_eq!(mt.gen_proof(1).root_hex(), mt.root_hex());
 }
 
 #[test]
@@ -217,3 +221,80 @@ fn test_simple_tree() {
         }
     }
 }
+
+#[test]
+fn test_proof_bytes_roundtrip() {
+    for items in [1u8, 2, 3, 9, 200] {
+        let data: Vec<[u8; 16]> = (0..items).map(|i| [i; 16]).collect();
+        let trees: [MerkleTree<[u8; 16], XOR128>; 3] = [
+            MerkleTree::from_data(&data),
+            MerkleTree::from_data_with_policy(&data, PaddingPolicy::PromoteOdd),
+            MerkleTree::from_data_with_order(&data, NodeOrder::Sorted),
+        ];
+        for mt in trees.iter() {
+            for i in 0..mt.leafs() {
+                let p = mt.gen_proof(i);
+                let bytes = p.to_bytes();
+                assert_eq!(bytes.len() % 16, 2 + p.path().len().div_ceil(8));
+                let q: Proof<[u8; 16]> = Proof::from_bytes(&bytes).unwrap();
+                assert_eq!(
+                    (q.lemma(), q.path(), q.index()),
+                    (p.lemma(), p.path(), None)
+                );
+            }
+        }
+    }
+
+    // 3 hashes, 1 path bit, left
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(["a", "b"]);
+    let bytes = mt.gen_proof(0).to_bytes();
+    assert_eq!(&bytes[..3], &[3, 1, 1]);
+    assert_eq!(&bytes[3..19], &mt[0]);
+    assert_eq!(&bytes[35..], &mt.root());
+}
+
+#[test]
+fn test_proof_bytes_invalid() {
+    let data: Vec<[u8; 16]> = (0..9u8).map(|i| [i; 16]).collect();
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(&data);
+    let bytes = mt.gen_proof(5).to_bytes();
+    let decode = |b: &[u8]| Proof::<[u8; 16]>::from_bytes(b);
+
+    for n in 0..bytes.len() {
+        assert_eq!(decode(&bytes[..n]), Err(MerkleError::InvalidEncoding));
+    }
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(decode(&trailing), Err(MerkleError::InvalidEncoding));
+
+    // 6 hashes carry 4 path bits, not 3
+    let mut bits = bytes.clone();
+    bits[1] = 3;
+    assert_eq!(decode(&bits), Err(MerkleError::InvalidEncoding));
+
+    // unused path bits set
+    let mut unused = bytes.clone();
+    unused[2] |= 0x80;
+    assert_eq!(decode(&unused), Err(MerkleError::InvalidEncoding));
+
+    // single hash
+    assert_eq!(decode(&[1, 0]), Err(MerkleError::InvalidEncoding));
+
+    // overflowing and overlong varints
+    let mut huge = vec![0xff; 10];
+    huge.push(0x01);
+    assert_eq!(decode(&huge), Err(MerkleError::InvalidEncoding));
+    assert_eq!(decode(&[0x83, 0x00, 0]), Err(MerkleError::InvalidEncoding));
+
+    // hashes of no bytes
+    assert_eq!(
+        Proof::<[u8; 0]>::from_bytes(&[2, 0]),
+        Err(MerkleError::InvalidEncoding)
+    );
+
+    // corrupted hash decodes, but does not validate
+    let mut corrupted = bytes.clone();
+    corrupted[20] ^= 1;
+    assert!(!decode(&corrupted).unwrap().validate::<XOR128>());
+}
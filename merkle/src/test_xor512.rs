@@ -0,0 +1,73 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::hash::*;
+use crate::merkle::MerkleTree;
+use std::hash::Hasher;
+
+const SIZE: usize = 0x40;
+
+type Item = [u8; SIZE];
+
+#[derive(Debug, Copy, Clone)]
+struct XOR512 {
+    data: Item,
+    i: usize,
+}
+
+impl Default for XOR512 {
+    fn default() -> XOR512 {
+        XOR512 {
+            data: [0; SIZE],
+            i: 0,
+        }
+    }
+}
+
+impl Hasher for XOR512 {
+    fn write(&mut self, bytes: &[u8]) {
+        for x in bytes {
+            // rotate, so that the nodes are not symmetric in children
+            self.data[self.i & (SIZE - 1)] ^= x.rotate_left(self.i as u32 / SIZE as u32);
+            self.i += 1;
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+impl Algorithm<Item> for XOR512 {
+    #[inline]
+    fn hash(&mut self) -> Item {
+        self.data
+    }
+}
+
+#[test]
+fn test_64_bytes_items() {
+    let data: std::vec::Vec<Item> = (0..13u8).map(|i| [i; SIZE]).collect();
+    let mt: MerkleTree<Item, XOR512> = MerkleTree::from_data(&data);
+    assert_eq!(mt.leafs(), 13);
+    assert_eq!(mt.height(), 5);
+
+    let mut a = XOR512::default();
+    assert_eq!(mt[0], a.leaf([0; SIZE]));
+    a.reset();
+    assert_eq!(mt[13], mt[12]);
+    assert_eq!(mt[14], a.node(mt[0], mt[1]));
+    assert!(mt.iter().all(|h| h.len() == SIZE));
+
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate::<XOR512>());
+        assert!(MerkleTree::<Item, XOR512>::verify_proof(
+            &mt.root(),
+            &p,
+            &p.item(),
+            i
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::hash::Algorithm;
+
+/// Verifies that `leaf` is the leaf at position `index` of the tree of
+/// `root`, folding the sibling hashes of `lemma` without constructing any
+/// proof or tree object.
+///
+/// `leaf` is the leaf node as stored in the tree, see
+/// [`Proof::item`](crate::proof::Proof::item), and `lemma` are the sibling
+/// hashes from the leaf up, the lemma of the proof without its first and
+/// last element:
+///
+/// ```text
+/// [ h1x h2y h3z ... ]
+/// ```
+///
+/// The side of each sibling is the bit of `index` of its level, so the tree
+/// has to be a positional tree which pads odd levels, as the one
+/// [`MerkleTree::from_data`] builds. Trees which promote odd nodes have no
+/// sibling on some levels, verify their proofs with
+/// [`MerkleTree::verify_proof_with_policy`].
+///
+/// [`MerkleTree::from_data`]: crate::merkle::MerkleTree::from_data
+/// [`MerkleTree::verify_proof_with_policy`]: crate::merkle::MerkleTree::verify_proof_with_policy
+pub fn verify_inclusion<T, A>(root: &T, leaf: &T, index: usize, lemma: &[T]) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    let mut v = ProofVerifier::<A, T>::new(leaf.clone(), index);
+    for sibling in lemma {
+        v.feed_sibling(sibling.clone());
+    }
+
+    // index bits above the height of the tree
+    v.index == 0 && v.finalize() == *root
+}
+
+/// Incremental verifier of the inclusion proof of leaf `index`, which folds
+/// the sibling hashes one at a time as they come, e.g. over the network,
+/// without buffering the lemma.
+///
+/// Siblings are fed from the leaf up, the side of each is the bit of
+/// `index` of its level as in [`verify_inclusion`], and the computed root is
+/// compared to the trusted one:
+///
+/// ```
+/// # use merkle_light::merkle::MerkleTree;
+/// # use merkle_light::verify::ProofVerifier;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// # struct Item([u8; 8]);
+/// # impl AsRef<[u8]> for Item {
+/// #     fn as_ref(&self) -> &[u8] {
+/// #         &self.0
+/// #     }
+/// # }
+/// # impl merkle_light::hash::Algorithm<Item> for DefaultHasher {
+/// #     fn hash(&mut self) -> Item {
+/// #         Item(std::hash::Hasher::finish(self).to_le_bytes())
+/// #     }
+/// # }
+/// let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+/// let p = mt.gen_proof(3);
+///
+/// let mut v = ProofVerifier::<DefaultHasher, Item>::new(p.item(), 3);
+/// for sibling in &p.lemma()[1..p.lemma().len() - 1] {
+///     v.feed_sibling(*sibling);
+/// }
+/// assert_eq!(v.finalize(), mt.root());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProofVerifier<A, T> {
+    a: A,
+    node: T,
+    index: usize,
+}
+
+impl<A: Algorithm<T>, T: Eq + Clone + AsRef<[u8]>> ProofVerifier<A, T> {
+    /// Creates new verifier of `leaf` at position `index`, `leaf` is the
+    /// leaf node as stored in the tree.
+    pub fn new(leaf: T, index: usize) -> ProofVerifier<A, T> {
+        ProofVerifier {
+            a: A::default(),
+            node: leaf,
+            index,
+        }
+    }
+
+    /// Hashes the node of the current level with its `sibling`, moving one
+    /// level up.
+    pub fn feed_sibling(&mut self, sibling: T) {
+        self.a.reset();
+        let node = self.node.clone();
+        self.node = if self.index & 1 == 0 {
+            self.a.node(node, sibling)
+        } else {
+            self.a.node(sibling, node)
+        };
+        self.index >>= 1;
+    }
+
+    /// Returns the root computed from the siblings fed so far.
+    pub fn finalize(self) -> T {
+        self.node
+    }
+}
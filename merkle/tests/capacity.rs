@@ -0,0 +1,131 @@
+#![cfg(test)]
+
+extern crate merkle_light;
+
+use merkle_light::hash::{Algorithm, Hashable};
+use merkle_light::merkle::{MerkleTree, MerkleTreeBuilder};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+/// System allocator counting the allocations of the current thread.
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        ALLOCS.with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocs<R, F: FnOnce() -> R>(f: F) -> (R, usize) {
+    let before = ALLOCS.with(Cell::get);
+    let r = f();
+    (r, ALLOCS.with(Cell::get) - before)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Item([u8; 8]);
+
+impl AsRef<[u8]> for Item {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Sip(DefaultHasher);
+
+impl Hasher for Sip {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for Sip {
+    fn hash(&mut self) -> Item {
+        Item(self.0.finish().to_le_bytes())
+    }
+
+    fn reset(&mut self) {
+        self.0 = DefaultHasher::default();
+    }
+}
+
+const LEAFS: u64 = 1000;
+
+/// Data iterator which does not know its length.
+fn unsized_data() -> impl Iterator<Item = u64> {
+    (0..).take_while(|&x| x < LEAFS)
+}
+
+#[test]
+fn test_with_capacity() {
+    let expected: MerkleTree<Item, Sip> = MerkleTree::from_data(0..LEAFS);
+    let hashes: Vec<Item> = (0..LEAFS)
+        .map(|x| {
+            let mut a = Sip::default();
+            x.hash(&mut a);
+            a.hash()
+        })
+        .collect();
+
+    let (grown, grown_allocs) = allocs(|| {
+        let mut mt: MerkleTree<Item, Sip> = MerkleTree::empty();
+        mt.extend(hashes.iter().copied().filter(|_| true));
+        mt
+    });
+    let (sized, sized_allocs) = allocs(|| {
+        let mut mt: MerkleTree<Item, Sip> = MerkleTree::with_capacity(LEAFS as usize);
+        mt.extend(hashes.iter().copied().filter(|_| true));
+        mt
+    });
+    assert_eq!(grown, expected);
+    assert_eq!(sized, expected);
+    assert_eq!(sized_allocs, 1);
+    assert!(sized_allocs < grown_allocs);
+}
+
+#[test]
+fn test_builder_capacity() {
+    let expected: MerkleTree<Item, Sip> = MerkleTree::from_data(0..LEAFS);
+
+    let (grown, grown_allocs) =
+        allocs(|| MerkleTreeBuilder::<Item, Sip>::new().build(unsized_data()));
+    let (sized, sized_allocs) = allocs(|| {
+        MerkleTreeBuilder::<Item, Sip>::new()
+            .capacity(LEAFS as usize)
+            .build(unsized_data())
+    });
+    assert_eq!(grown, expected);
+    assert_eq!(sized, expected);
+    assert_eq!(sized_allocs, 1);
+    assert!(sized_allocs < grown_allocs);
+
+    // too small a hint is a hint only
+    let mt = MerkleTreeBuilder::<Item, Sip>::new()
+        .capacity(10)
+        .build(unsized_data());
+    assert_eq!(mt, expected);
+}
@@ -97,7 +97,7 @@ impl<'a> fmt::Display for HexSlice<'a> {
 #[test]
 fn test_crypto_bitcoin_leaf_hash() {
     let mut a = CryptoBitcoinAlgorithm::new();
-    "hello".hash(&mut a);
+    b"hello".hash(&mut a);
     let h1 = a.hash();
     assert_eq!(
         format!("{}", HexSlice::new(h1.as_ref())),
@@ -6,7 +6,7 @@ extern crate merkle_light;
 
 use proc_macro::TokenStream;
 
-#[proc_macro_derive(Hashable)]
+#[proc_macro_derive(Hashable, attributes(hashable))]
 pub fn derive_hashable(input: TokenStream) -> TokenStream {
     let s = input.to_string();
     let ast = syn::parse_derive_input(&s).unwrap();
@@ -14,27 +14,117 @@ pub fn derive_hashable(input: TokenStream) -> TokenStream {
     gen.parse().unwrap()
 }
 
-fn impl_hashable(ast: &syn::DeriveInput) -> quote::Tokens {
-    let body = match ast.body {
-        syn::Body::Struct(ref s) => s,
-        _ => panic!("#[derive(Hashable)] is only defined for structs."),
+/// Implements `Hasher` and `Algorithm<[u8; N]>` for a newtype over a
+/// `digest::Digest` hasher, given as `impl_algorithm!(Name, N)`.
+///
+/// The name may be a generic type, e.g. `Wrapper<CoreWrapper<Foo<A, B>>>`.
+/// The newtype must implement `Default` and the hasher `Clone`, the crate
+/// must depend on `digest`.
+#[proc_macro]
+pub fn impl_algorithm(input: TokenStream) -> TokenStream {
+    let s = input.to_string();
+    let (name, size) = algorithm_args(&s).expect("impl_algorithm! expects `Name, N`.");
+
+    let gen = quote! {
+        const _: () = {
+            extern crate digest;
+            extern crate merkle_light;
+
+            use ::core::hash::Hasher;
+            use merkle_light::hash::Algorithm;
+
+            impl Hasher for #name {
+                #[inline]
+                fn write(&mut self, msg: &[u8]) {
+                    digest::Digest::update(&mut self.0, msg)
+                }
+
+                /// Returns the first 8 bytes of the digest, little-endian.
+                #[inline]
+                fn finish(&self) -> u64 {
+                    let h = digest::Digest::finalize(self.0.clone());
+                    let n = h.len().min(8);
+                    let mut bytes = [0u8; 8];
+                    bytes[..n].copy_from_slice(&h[..n]);
+                    u64::from_le_bytes(bytes)
+                }
+            }
+
+            impl Algorithm<[u8; #size]> for #name {
+                #[inline]
+                fn hash(&mut self) -> [u8; #size] {
+                    let mut h = [0u8; #size];
+                    h.copy_from_slice(&digest::Digest::finalize_reset(&mut self.0));
+                    h
+                }
+
+                #[inline]
+                fn reset(&mut self) {
+                    digest::Digest::reset(&mut self.0)
+                }
+            }
+        };
     };
+    gen.parse().unwrap()
+}
 
-    let stmts: Vec<_> = match *body {
-        syn::VariantData::Struct(ref fields) => {
-            fields.iter().enumerate().map(hash_field_map).collect()
-        }
-        syn::VariantData::Tuple(ref fields) => {
-            fields.iter().enumerate().map(hash_field_map).collect()
-        }
-        syn::VariantData::Unit => panic!("#[derive(Hashable)] is not defined for Unit structs."),
+/// Parses `Name, N` of [`impl_algorithm!`], a trailing comma is allowed.
+fn algorithm_args(input: &str) -> Option<(syn::Ty, usize)> {
+    use syn::parse::IResult;
+
+    let (rest, name) = match syn::parse::ty(input) {
+        IResult::Done(rest, name) => (rest, name),
+        IResult::Error => return None,
     };
+    let rest = rest.trim_start().strip_prefix(',')?;
+    let (rest, size) = match syn::parse::int(rest.trim_start()) {
+        IResult::Done(rest, size) => (rest, size),
+        IResult::Error => return None,
+    };
+    match rest.trim() {
+        "" | "," => usize::try_from(size.value).ok().map(|size| (name, size)),
+        _ => None,
+    }
+}
 
+fn impl_hashable(ast: &syn::DeriveInput) -> quote::Tokens {
     let name = &ast.ident;
-    let dummy_const = syn::Ident::new(format!("_IMPL_HASHABLE_FOR_{}", name).to_uppercase());
+
+    let stmts: Vec<_> = match ast.body {
+        syn::Body::Struct(syn::VariantData::Struct(ref fields))
+        | syn::Body::Struct(syn::VariantData::Tuple(ref fields)) => hashed(fields)
+            .into_iter()
+            .map(|index| {
+                let f = &fields[index];
+                let field = match f.ident {
+                    Some(ref ident) => quote! { self.#ident },
+                    None => {
+                        // unsuffixed, `0usize` is not a tuple index
+                        let index = syn::Ident::from(index.to_string());
+                        quote! { self.#index }
+                    }
+                };
+                quote! { #field.hash(state); }
+            })
+            .collect(),
+        syn::Body::Struct(syn::VariantData::Unit) => {
+            panic!("#[derive(Hashable)] is not defined for Unit structs.")
+        }
+        syn::Body::Enum(ref variants) => {
+            let arms = variants
+                .iter()
+                .enumerate()
+                .map(|(i, v)| hash_variant(name, i, v));
+            vec![quote! {
+                match *self {
+                    #(#arms)*
+                }
+            }]
+        }
+    };
 
     quote! {
-        const #dummy_const: () = {
+        const _: () = {
             extern crate merkle_light;
 
             use std::hash::Hasher;
@@ -49,30 +139,123 @@ fn impl_hashable(ast: &syn::DeriveInput) -> quote::Tokens {
     }
 }
 
-fn hash_field_map(tuple: (usize, &syn::Field)) -> quote::Tokens {
-    hash_field(tuple.0, tuple.1)
+/// Hashes the variant index (`usize`, as `Option` does) and then its fields.
+fn hash_variant(name: &syn::Ident, index: usize, v: &syn::Variant) -> quote::Tokens {
+    let variant = &v.ident;
+    match v.data {
+        syn::VariantData::Struct(ref fields) => {
+            let binds: Vec<_> = fields.iter().map(|f| f.ident.clone().unwrap()).collect();
+            let order = hashed(fields);
+            let pats: Vec<_> = binds
+                .iter()
+                .enumerate()
+                .map(|(i, b)| match order.contains(&i) {
+                    true => quote! { ref #b },
+                    false => quote! { #b: _ },
+                })
+                .collect();
+            let stmts: Vec<_> = order
+                .into_iter()
+                .map(|i| {
+                    let b = &binds[i];
+                    quote! { #b.hash(state); }
+                })
+                .collect();
+            quote! {
+                #name::#variant { #(#pats),* } => {
+                    #index.hash(state);
+                    #(#stmts)*
+                }
+            }
+        }
+        syn::VariantData::Tuple(ref fields) => {
+            let binds: Vec<_> = (0..fields.len())
+                .map(|i| syn::Ident::from(format!("__field{}", i)))
+                .collect();
+            let order = hashed(fields);
+            let pats: Vec<_> = binds
+                .iter()
+                .enumerate()
+                .map(|(i, b)| match order.contains(&i) {
+                    true => quote! { ref #b },
+                    false => quote! { _ },
+                })
+                .collect();
+            let stmts: Vec<_> = order
+                .into_iter()
+                .map(|i| {
+                    let b = &binds[i];
+                    quote! { #b.hash(state); }
+                })
+                .collect();
+            quote! {
+                #name::#variant ( #(#pats),* ) => {
+                    #index.hash(state);
+                    #(#stmts)*
+                }
+            }
+        }
+        syn::VariantData::Unit => quote! {
+            #name::#variant => {
+                #index.hash(state);
+            }
+        },
+    }
 }
 
-fn hash_field(index: usize, f: &syn::Field) -> quote::Tokens {
-    let mut ty = f.ty.clone();
+/// Returns indices of the fields to hash in the order they are fed.
+///
+/// Fields are hashed in the declaration order, `#[hashable(skip)]` fields
+/// are left out. The order may be given explicitly with
+/// `#[hashable(order = N)]` on every hashed field.
+fn hashed(fields: &[syn::Field]) -> Vec<usize> {
+    let mut keyed = Vec::new();
+    let mut implicit = Vec::new();
+    for (i, f) in fields.iter().enumerate() {
+        match field_attrs(f) {
+            (true, None) => {}
+            (true, Some(_)) => panic!("#[hashable(order)] on a skipped field."),
+            (false, Some(n)) => keyed.push((n, i)),
+            (false, None) => implicit.push(i),
+        }
+    }
 
-    loop {
-        match ty {
-            syn::Ty::Path(_, ref path) => {
-                path.segments
-                    .first()
-                    .expect("there must be at least 1 segment");
-                break;
-            }
-            syn::Ty::Rptr(_, bty) => {
-                ty = bty.ty.clone();
-            }
-            _ => panic!("hashing not supported: {:?}", ty),
-        };
+    if keyed.is_empty() {
+        return implicit;
     }
+    if !implicit.is_empty() {
+        panic!("#[hashable(order)] must be given to all hashed fields or none.");
+    }
+    keyed.sort();
+    if keyed.windows(2).any(|w| w[0].0 == w[1].0) {
+        panic!("#[hashable(order)] values must be unique.");
+    }
+    keyed.into_iter().map(|(_, i)| i).collect()
+}
 
-    match f.ident {
-        Some(ref ident) => quote! { self.#ident.hash(state); },
-        None => quote! { self.#index.hash(state); },
+/// Parses `#[hashable(skip)]` and `#[hashable(order = N)]` of the field.
+fn field_attrs(f: &syn::Field) -> (bool, Option<u64>) {
+    let mut skip = false;
+    let mut order = None;
+    for attr in &f.attrs {
+        let items = match attr.value {
+            syn::MetaItem::List(ref ident, ref items) if ident == "hashable" => items,
+            _ => continue,
+        };
+        for item in items {
+            match *item {
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident))
+                    if ident == "skip" =>
+                {
+                    skip = true
+                }
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(
+                    ref ident,
+                    syn::Lit::Int(n, _),
+                )) if ident == "order" => order = Some(n),
+                _ => panic!("#[hashable] expects `skip` or `order = N`."),
+            }
+        }
     }
+    (skip, order)
 }
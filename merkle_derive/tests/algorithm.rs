@@ -0,0 +1,88 @@
+extern crate merkle_light;
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate sha2;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use sha2::Sha256;
+
+#[derive(Clone, Default)]
+struct Sha256Algorithm(Sha256);
+
+impl_algorithm!(Sha256Algorithm, 32);
+
+fn hex(h: &[u8]) -> String {
+    h.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_impl_algorithm() {
+    let mut a = Sha256Algorithm::default();
+    assert_eq!(
+        hex(&a.hash_bytes(b"abc")),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    // state is reset after the hash
+    std::hash::Hasher::write(&mut a, b"a");
+    a.hash();
+    std::hash::Hasher::write(&mut a, b"abc");
+    assert_eq!(a.hash(), a.hash_bytes(b"abc"));
+}
+
+#[test]
+fn test_impl_algorithm_rfc6962_root() {
+    // certificate transparency test vectors
+    let leafs: [&[u8]; 8] = [
+        b"",
+        b"\x00",
+        b"\x10",
+        b"\x20\x21",
+        b"\x30\x31",
+        b"\x40\x41\x42\x43",
+        b"\x50\x51\x52\x53\x54\x55\x56\x57",
+        b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+    ];
+    let mut a = Sha256Algorithm::default();
+    let hashes: Vec<[u8; 32]> = leafs
+        .iter()
+        .map(|leaf| a.hash_bytes(&[&[0u8][..], leaf].concat()))
+        .collect();
+
+    let mt: MerkleTree<[u8; 32], Sha256Algorithm> = MerkleTree::from_leaf_hashes(hashes);
+    assert_eq!(
+        mt.root_hex(),
+        "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328"
+    );
+    for i in 0..mt.leafs() {
+        assert!(mt.gen_proof(i).validate::<Sha256Algorithm>());
+    }
+}
+
+#[derive(Clone, Default)]
+struct Tagged<D, T>(D, std::marker::PhantomData<T>);
+
+impl_algorithm!(Tagged<Sha256, (u8, u16)>, 32);
+
+#[test]
+fn test_impl_algorithm_generic() {
+    let mut a: Tagged<Sha256, (u8, u16)> = Tagged::default();
+    assert_eq!(
+        a.hash_bytes(b"abc"),
+        Sha256Algorithm::default().hash_bytes(b"abc")
+    );
+}
+
+#[test]
+fn test_impl_algorithm_finish() {
+    let mut a = Sha256Algorithm::default();
+    std::hash::Hasher::write(&mut a, b"abc");
+
+    // first 8 bytes of the digest, the state is kept
+    assert_eq!(std::hash::Hasher::finish(&a), 0xeacf_018f_bf16_78ba);
+    assert_eq!(
+        hex(&a.hash()),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
@@ -0,0 +1,13 @@
+extern crate trybuild;
+
+#[test]
+fn test_derive_compiles() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/*.rs");
+}
+
+#[test]
+fn test_derive_rejects() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/fail/*.rs");
+}
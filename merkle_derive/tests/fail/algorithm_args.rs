@@ -0,0 +1,10 @@
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate merkle_light;
+
+#[derive(Default)]
+struct Wrapper([u8; 32]);
+
+impl_algorithm!(Wrapper);
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate merkle_light;
+
+#[derive(Hashable)]
+struct Duplicate {
+    #[hashable(order = 0)]
+    a: u8,
+    #[hashable(order = 0)]
+    b: u8,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate merkle_light;
+
+#[derive(Hashable)]
+struct Partial {
+    #[hashable(order = 0)]
+    a: u8,
+    b: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate merkle_light;
+
+#[derive(Hashable)]
+struct Unknown {
+    #[hashable(rename = "b")]
+    a: u8,
+}
+
+fn main() {}
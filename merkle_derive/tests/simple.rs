@@ -1,3 +1,6 @@
+// expected hashes are the ones of the leaf encoding version 2
+#![cfg(not(feature = "scheme_v1"))]
+
 extern crate merkle_light;
 
 #[macro_use]
@@ -31,5 +34,308 @@ fn test_foo_hash() {
 
     let hr = &mut DefaultHasher::new();
     foo.hash(hr);
-    assert_eq!(hr.finish(), 8466196983143881409)
+    assert_eq!(hr.finish(), 10139684257588699255)
+}
+
+#[derive(Hashable, Debug)]
+struct Bar {
+    a: Vec<u8>,
+    b: Option<u32>,
+    c: [u8; 4],
+    d: [u16; 3],
+    e: (u8, u16, u32),
+    f: &'static [u32],
+    g: Vec<Option<u64>>,
+}
+
+fn bar_hash(bar: &Bar) -> u64 {
+    let hr = &mut DefaultHasher::new();
+    bar.hash(hr);
+    hr.finish()
+}
+
+#[test]
+fn test_bar_hash() {
+    let bar = Bar {
+        a: vec![1, 2, 3],
+        b: Some(4),
+        c: [5, 6, 7, 8],
+        d: [9, 10, 11],
+        e: (12, 13, 14),
+        f: &[14, 15],
+        g: vec![Some(16), None],
+    };
+
+    // fields one after another, sequences prefixed with the length
+    let hr = &mut DefaultHasher::new();
+    hr.write_usize(3);
+    hr.write(&[1, 2, 3]);
+    hr.write_usize(1);
+    hr.write_u32(4);
+    hr.write(&[5, 6, 7, 8]);
+    hr.write_u16(9);
+    hr.write_u16(10);
+    hr.write_u16(11);
+    hr.write_u8(12);
+    hr.write_u16(13);
+    hr.write_u32(14);
+    hr.write_usize(2);
+    hr.write_u32(14);
+    hr.write_u32(15);
+    hr.write_usize(2);
+    hr.write_usize(1);
+    hr.write_u64(16);
+    hr.write_usize(0);
+    assert_eq!(bar_hash(&bar), hr.finish());
+
+    // element moved across the sequence boundary
+    let moved = Bar {
+        a: vec![1, 2],
+        f: &[3, 14, 15],
+        ..bar
+    };
+    assert_ne!(bar_hash(&moved), hr.finish());
+
+    let none = Bar { b: None, ..moved };
+    let none_hash = bar_hash(&none);
+    let zero = Bar { b: Some(0), ..none };
+    assert_ne!(none_hash, bar_hash(&zero));
+}
+
+#[derive(Hashable, Debug)]
+struct Pair {
+    a: String,
+    b: &'static str,
+}
+
+#[test]
+fn test_strings_boundary() {
+    let hash = |p: &Pair| {
+        let hr = &mut DefaultHasher::new();
+        p.hash(hr);
+        hr.finish()
+    };
+
+    // same concatenation "ab", different boundaries
+    let ab = Pair {
+        a: String::from("ab"),
+        b: "",
+    };
+    let a_b = Pair {
+        a: String::from("a"),
+        b: "b",
+    };
+    assert_ne!(hash(&ab), hash(&a_b));
+
+    let hr = &mut DefaultHasher::new();
+    hr.write(b"ab\xff");
+    hr.write(b"\xff");
+    assert_eq!(hash(&ab), hr.finish());
+}
+
+#[derive(Hashable, Debug)]
+struct Nested {
+    a: Option<String>,
+    b: Vec<String>,
+}
+
+#[test]
+fn test_nested_strings_boundary() {
+    let hash = |n: &Nested| {
+        let hr = &mut DefaultHasher::new();
+        n.hash(hr);
+        hr.finish()
+    };
+
+    // strings of containers are terminated, not only the field ones
+    let ab = Nested {
+        a: Some(String::from("ab")),
+        b: vec![String::from("ab"), String::new()],
+    };
+    let a_b = Nested {
+        a: Some(String::from("ab")),
+        b: vec![String::from("a"), String::from("b")],
+    };
+    assert_ne!(hash(&ab), hash(&a_b));
+
+    let hr = &mut Bytes::default();
+    ab.hash(hr);
+    let mut expected = vec![1, 0, 0, 0, 0, 0, 0, 0];
+    expected.extend_from_slice(b"ab\xff");
+    expected.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0]);
+    expected.extend_from_slice(b"ab\xff\xff");
+    assert_eq!(hr.0, expected);
+}
+
+#[derive(Hashable, Debug)]
+enum Baz {
+    Unit,
+    Other,
+    Tuple(u8, String),
+    Struct { a: u8, b: String },
+}
+
+#[test]
+fn test_enum_hash() {
+    let hash = |b: &Baz| {
+        let hr = &mut DefaultHasher::new();
+        b.hash(hr);
+        hr.finish()
+    };
+
+    let all = [
+        Baz::Unit,
+        Baz::Other,
+        Baz::Tuple(1, String::from("x")),
+        Baz::Struct {
+            a: 1,
+            b: String::from("x"),
+        },
+    ];
+    for (i, x) in all.iter().enumerate() {
+        for y in &all[i + 1..] {
+            assert_ne!(hash(x), hash(y));
+        }
+    }
+
+    // variant index first, then the fields
+    let hr = &mut DefaultHasher::new();
+    hr.write_usize(2);
+    hr.write_u8(1);
+    hr.write(b"x\xff");
+    assert_eq!(hash(&all[2]), hr.finish());
+
+    let hr = &mut DefaultHasher::new();
+    hr.write_usize(1);
+    assert_eq!(hash(&all[1]), hr.finish());
+}
+
+/// Records the bytes fed to it.
+#[derive(Default)]
+struct Bytes(Vec<u8>);
+
+impl Hasher for Bytes {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+#[derive(Hashable, Debug)]
+struct Stamp {
+    height: u64,
+    id: u32,
+    tag: String,
+}
+
+#[test]
+fn test_little_endian_encoding() {
+    let stamp = Stamp {
+        height: 0x0102_0304_0506_0708,
+        id: 0x0a0b_0c0d,
+        tag: String::from("ab"),
+    };
+
+    let hr = &mut Bytes::default();
+    stamp.hash(hr);
+
+    // same bytes are fed to the hasher on any target
+    let mut expected = vec![8, 7, 6, 5, 4, 3, 2, 1, 0x0d, 0x0c, 0x0b, 0x0a];
+    expected.extend_from_slice(b"ab\xff");
+    assert_eq!(hr.0, expected);
+
+    let hr = &mut DefaultHasher::new();
+    stamp.hash(hr);
+    let known = &mut DefaultHasher::new();
+    known.write(&expected);
+    assert_eq!(hr.finish(), known.finish());
+}
+
+#[derive(Hashable, Debug)]
+struct Cached {
+    id: u32,
+    #[hashable(skip)]
+    _cache: Option<u64>,
+    name: String,
+}
+
+#[derive(Hashable, Debug)]
+struct Reordered {
+    #[hashable(order = 1)]
+    id: u32,
+    #[hashable(order = 0)]
+    name: String,
+    #[hashable(skip)]
+    _cache: Vec<u8>,
+}
+
+#[test]
+fn test_skip_and_order() {
+    let bytes = |x: &dyn Fn(&mut Bytes)| {
+        let hr = &mut Bytes::default();
+        x(hr);
+        hr.0.clone()
+    };
+
+    let a = Cached {
+        id: 7,
+        _cache: None,
+        name: String::from("ab"),
+    };
+    let b = Cached {
+        id: 7,
+        _cache: Some(42),
+        name: String::from("ab"),
+    };
+    let ha = &mut DefaultHasher::new();
+    a.hash(ha);
+    let hb = &mut DefaultHasher::new();
+    b.hash(hb);
+    assert_eq!(ha.finish(), hb.finish());
+
+    let mut expected = vec![7, 0, 0, 0];
+    expected.extend_from_slice(b"ab\xff");
+    assert_eq!(bytes(&|hr| b.hash(hr)), expected);
+
+    let r = Reordered {
+        id: 7,
+        name: String::from("ab"),
+        _cache: vec![1, 2, 3],
+    };
+    let mut expected = b"ab\xff".to_vec();
+    expected.extend_from_slice(&[7, 0, 0, 0]);
+    assert_eq!(bytes(&|hr| r.hash(hr)), expected);
+}
+
+#[derive(Hashable, Debug)]
+struct Owned {
+    name: String,
+    id: u64,
+}
+
+#[derive(Hashable, Debug)]
+struct Boxed {
+    name: Box<str>,
+    id: std::sync::Arc<u64>,
+}
+
+#[test]
+fn test_smart_pointers_transparent() {
+    let owned = Owned {
+        name: String::from("ab"),
+        id: 7,
+    };
+    let boxed = Boxed {
+        name: Box::from("ab"),
+        id: std::sync::Arc::new(7),
+    };
+
+    let ho = &mut Bytes::default();
+    owned.hash(ho);
+    let hb = &mut Bytes::default();
+    boxed.hash(hb);
+    assert_eq!(ho.0, hb.0);
 }
@@ -0,0 +1,24 @@
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate merkle_light;
+
+use merkle_light::hash::Hashable;
+use std::collections::hash_map::DefaultHasher;
+
+#[derive(Hashable)]
+enum Mixed {
+    Unit,
+    Tuple(u8, String),
+    Struct { a: u32, b: &'static str },
+}
+
+#[derive(Hashable)]
+enum Empty {}
+
+fn main() {
+    let mut hr = DefaultHasher::new();
+    Mixed::Unit.hash(&mut hr);
+    Mixed::Tuple(1, String::from("a")).hash(&mut hr);
+    Mixed::Struct { a: 1, b: "b" }.hash(&mut hr);
+    let _ = |e: &Empty| e.hash(&mut DefaultHasher::new());
+}
@@ -0,0 +1,47 @@
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate merkle_light;
+
+use merkle_light::hash::Hashable;
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+
+#[derive(Hashable)]
+struct Cached {
+    id: u64,
+    #[hashable(skip)]
+    cache: Cell<Option<u64>>,
+}
+
+#[derive(Hashable)]
+struct Reordered(#[hashable(order = 1)] u8, #[hashable(order = 0)] String);
+
+#[derive(Hashable)]
+enum Event {
+    Tuple(u8, #[hashable(skip)] fn()),
+    Struct {
+        #[hashable(order = 2)]
+        a: u32,
+        #[hashable(skip)]
+        b: Cell<u8>,
+        #[hashable(order = 1)]
+        c: &'static str,
+    },
+}
+
+fn main() {
+    let mut hr = DefaultHasher::new();
+    Cached {
+        id: 1,
+        cache: Cell::new(None),
+    }
+    .hash(&mut hr);
+    Reordered(1, String::from("a")).hash(&mut hr);
+    Event::Tuple(1, main).hash(&mut hr);
+    Event::Struct {
+        a: 1,
+        b: Cell::new(2),
+        c: "c",
+    }
+    .hash(&mut hr);
+}
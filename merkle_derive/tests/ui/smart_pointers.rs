@@ -0,0 +1,27 @@
+#[macro_use]
+extern crate merkle_light_derive;
+extern crate merkle_light;
+
+use merkle_light::hash::Hashable;
+use std::collections::hash_map::DefaultHasher;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Hashable)]
+struct Shared {
+    name: Box<String>,
+    id: Arc<u64>,
+    tags: Rc<Vec<u8>>,
+    parent: &'static u32,
+}
+
+fn main() {
+    let mut hr = DefaultHasher::new();
+    Shared {
+        name: Box::new(String::from("a")),
+        id: Arc::new(1),
+        tags: Rc::new(vec![1, 2]),
+        parent: &7,
+    }
+    .hash(&mut hr);
+}
@@ -0,0 +1,90 @@
+//! Proof verification of `merkle_light` on a `no_std` target.
+//!
+//! Builds only the verification path (`Proof`, `ProofRef`,
+//! `verify_inclusion`, `FixedMerkleTree`, `Algorithm`, `Hashable`) without
+//! `std`, so that it can be checked against a bare metal target:
+//!
+//! ```text
+//! cargo build -p merkle_light_no_std --target thumbv7m-none-eabi
+//! ```
+
+#![no_std]
+#![deny(missing_docs, missing_debug_implementations, unsafe_code)]
+
+extern crate alloc;
+extern crate merkle_light;
+
+use alloc::vec::Vec;
+use core::hash::Hasher;
+use merkle_light::fixed::FixedMerkleTree;
+use merkle_light::hash::{Algorithm, Hashable};
+use merkle_light::proof::{Proof, ProofRef};
+use merkle_light::verify::verify_inclusion;
+
+/// 64-bit FNV-1a, a dependency free hash for the check.
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv64(u64);
+
+impl Default for Fnv64 {
+    fn default() -> Fnv64 {
+        Fnv64(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv64 {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Algorithm<[u8; 8]> for Fnv64 {
+    fn hash(&mut self) -> [u8; 8] {
+        self.0.to_le_bytes()
+    }
+}
+
+/// Returns leaf hash of `data`.
+pub fn leaf<D: Hashable<Fnv64>>(data: &D) -> [u8; 8] {
+    let mut a = Fnv64::default();
+    data.hash(&mut a);
+    let h = a.hash();
+    a.reset();
+    a.leaf(h)
+}
+
+/// Verifies borrowed proof `lemma` and `path` of `leaf` against `root`.
+pub fn verify(lemma: &[[u8; 8]], path: &[bool], leaf: &[u8; 8], root: &[u8; 8]) -> bool {
+    if lemma.len() < 2 || lemma.len() - 2 != path.len() {
+        return false;
+    }
+    let p = ProofRef::new(lemma, path);
+    p.item() == leaf && p.root() == root && p.validate::<Fnv64>()
+}
+
+/// Verifies owned proof received as `lemma` and `path`.
+pub fn verify_owned(lemma: Vec<[u8; 8]>, path: Vec<bool>) -> bool {
+    if lemma.len() < 2 || lemma.len() - 2 != path.len() {
+        return false;
+    }
+    Proof::new(lemma, path).validate::<Fnv64>()
+}
+
+/// Verifies the sibling hashes `lemma` of `leaf` at position `index`
+/// against `root`.
+pub fn verify_index(root: &[u8; 8], leaf: &[u8; 8], index: usize, lemma: &[[u8; 8]]) -> bool {
+    verify_inclusion::<_, Fnv64>(root, leaf, index, lemma)
+}
+
+/// Builds the fixed tree of 8 `data` leafs on the stack and verifies the
+/// proof of leaf `index` in it.
+pub fn verify_fixed(data: &[u64; 8], index: usize) -> bool {
+    let mt: FixedMerkleTree<[u8; 8], Fnv64, 3, 8> = FixedMerkleTree::from_data(data);
+    mt.gen_proof(index).validate::<Fnv64>()
+}